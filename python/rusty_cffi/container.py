@@ -9,6 +9,13 @@ def _rust_to_python(symbol):
 
     lib = get_lib()
 
+    # numpy has no 128-bit integer types.
+    unsupported = {lib.Int128: "int128", lib.Unsigned128: "uint128"}
+    if symbol in unsupported:
+        raise TypeError(
+            f"unsupported dtype {unsupported[symbol]}: numpy has no 128-bit integers"
+        )
+
     return {
         lib.Float32: ("float32", "float *"),
         lib.Float64: ("float64", "double *"),
@@ -19,6 +26,9 @@ def _rust_to_python(symbol):
         lib.Unsigned32: ("uint32", "uint32_t *"),
         lib.Unsigned64: ("uint64", "uint64_t *"),
         lib.Usize: ("uintp", "uintptr_t *"),
+        lib.Bool: ("bool", "_Bool *"),
+        lib.Complex32: ("complex64", "float _Complex *"),
+        lib.Complex64: ("complex128", "double _Complex *"),
     }[symbol]
 
 
@@ -38,6 +48,9 @@ def _python_to_rust(symbol):
         "uint32": (lib.Unsigned32, lib.rusty_data_container_new_u32),
         "uint64": (lib.Unsigned64, lib.rusty_data_container_new_u64),
         "uintp": (lib.Usize, lib.rusty_data_container_new_usize),
+        "bool": (lib.Bool, lib.rusty_data_container_new_bool),
+        "complex64": (lib.Complex32, lib.rusty_data_container_new_c32),
+        "complex128": (lib.Complex64, lib.rusty_data_container_new_c64),
     }[symbol]
 
 
@@ -58,15 +71,28 @@ class RustyDataContainer:
         self._is_owner = lib.rusty_data_container_get_is_owner(ptr)
         self._dtype = _rust_to_python(lib.rusty_data_container_get_dtype(ptr))[0]
 
-        self._data = np.frombuffer(
-            ffi.buffer(
-                ffi.cast(
-                    _rust_to_python(lib.rusty_data_container_get_dtype(ptr))[1],
-                    lib.rusty_data_container_get_data(ptr),
-                ),
-                self._itemsize * self._nitems,
-            ),
+        ndim = lib.rusty_data_container_get_ndim(ptr)
+        shape = tuple(lib.rusty_data_container_get_shape(ptr)[i] for i in range(ndim))
+        strides = tuple(
+            self._itemsize * lib.rusty_data_container_get_strides(ptr)[i]
+            for i in range(ndim)
+        )
+
+        # Byte range spanned by the data, relative to the data pointer.
+        # Negative strides move the start of the range below the data pointer.
+        low = sum(min(0, (n - 1) * s) for n, s in zip(shape, strides))
+        high = sum(max(0, (n - 1) * s) for n, s in zip(shape, strides))
+        nbytes = high - low + self._itemsize if self._nitems > 0 else 0
+
+        self._data = np.ndarray(
+            shape,
             dtype=self._dtype,
+            buffer=ffi.buffer(
+                ffi.cast("char *", lib.rusty_data_container_get_const_data(ptr)) + low,
+                nbytes,
+            ),
+            offset=-low,
+            strides=strides,
         )
 
         if not self._is_mutable:
@@ -0,0 +1,108 @@
+//! Conversion of containers to and from Apache Arrow buffers.
+//!
+//! Arrow buffers hold untyped bytes, so the dtype and the number of elements travel
+//! separately. The data of shared containers is handed to Arrow without copying,
+//! since it is immutable and reference counted. All other containers are copied into
+//! a new buffer with Arrow's 64 byte alignment.
+
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use arrow_buffer::{Buffer, MutableBuffer};
+
+use crate::containers::SharedWord;
+use crate::{get_itemsize, ContainerError, RustyDataContainer, DTYPE, OWNERSHIP};
+
+impl RustyDataContainer {
+    /// The elements as an Arrow buffer in row-major order.
+    ///
+    /// Shared containers are converted without copying, and the buffer keeps the shared
+    /// data alive. The data of other containers is copied.
+    pub fn to_arrow_buffer(&self) -> Buffer {
+        let nbytes = self.nitems * self.itemsize;
+        if self.is_owner == OWNERSHIP::Shared && nbytes > 0 {
+            let words = self.shared_words();
+            unsafe { Arc::increment_strong_count(words) };
+            let owner: Arc<[SharedWord]> = unsafe { Arc::from_raw(words) };
+            let data = NonNull::new(self.data as *mut u8).unwrap();
+            return unsafe { Buffer::from_custom_allocation(data, nbytes, Arc::new(owner)) };
+        }
+        let mut buffer = MutableBuffer::with_capacity(nbytes);
+        for index in 0..self.nitems {
+            buffer.extend_from_slice(self.element_bytes(index));
+        }
+        buffer.into()
+    }
+
+    /// Create a new owning container with `nitems` elements of type `dtype` from the
+    /// start of an Arrow buffer.
+    ///
+    /// The data is copied. Returns [ContainerError::InvalidShape] if the buffer holds
+    /// fewer than `nitems` elements, and [ContainerError::InvalidFormat] for Bool data
+    /// with bytes other than 0 and 1. Arrow's bit-packed booleans must be unpacked first,
+    /// see [crate::unpack_bits].
+    pub fn from_arrow_buffer(
+        buffer: &Buffer,
+        dtype: DTYPE,
+        nitems: usize,
+    ) -> Result<RustyDataContainer, ContainerError> {
+        let bytes = nitems
+            .checked_mul(get_itemsize(dtype))
+            .and_then(|nbytes| buffer.get(..nbytes))
+            .ok_or(ContainerError::InvalidShape)?;
+        if dtype == DTYPE::Bool && bytes.iter().any(|&byte| byte > 1) {
+            return Err(ContainerError::InvalidFormat);
+        }
+        let mut container = RustyDataContainer::new_zeroed(nitems, dtype);
+        container.bytes_mut().copy_from_slice(bytes);
+        Ok(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f64_container_to_arrow_and_back() {
+        let container = RustyDataContainer::from_vec(vec![1.0_f64, 2.5, -3.0]);
+        let buffer = container.to_arrow_buffer();
+        assert_eq!(buffer.len(), 3 * 8);
+        assert_eq!(buffer.typed_data::<f64>(), [1.0, 2.5, -3.0]);
+        assert!((buffer.as_ptr() as usize).is_multiple_of(64));
+
+        let restored = RustyDataContainer::from_arrow_buffer(&buffer, DTYPE::Float64, 3).unwrap();
+        assert_eq!(restored.typed::<f64>(), [1.0, 2.5, -3.0]);
+        assert_eq!(
+            RustyDataContainer::from_arrow_buffer(&buffer, DTYPE::Float64, 4).err(),
+            Some(ContainerError::InvalidShape)
+        );
+        assert_eq!(
+            RustyDataContainer::from_arrow_buffer(&buffer, DTYPE::Float32, 6)
+                .unwrap()
+                .nitems,
+            6
+        );
+    }
+
+    #[test]
+    fn shared_containers_are_not_copied() {
+        let shared = RustyDataContainer::from_vec(vec![1_i32, 2, 3, 4]).into_shared();
+        let buffer = shared.to_arrow_buffer();
+        assert_eq!(buffer.as_ptr(), shared.data as *const u8);
+        drop(shared);
+        assert_eq!(buffer.typed_data::<i32>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn strided_views_are_copied_in_row_major_order() {
+        let container = RustyDataContainer::from_vec(vec![1_u8, 2, 3, 4, 5]);
+        let buffer = container.reverse_view().to_arrow_buffer();
+        assert_eq!(buffer.as_slice(), [5, 4, 3, 2, 1]);
+        let bools = Buffer::from_slice_ref([1_u8, 0, 2]);
+        assert_eq!(
+            RustyDataContainer::from_arrow_buffer(&bools, DTYPE::Bool, 3).err(),
+            Some(ContainerError::InvalidFormat)
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Builder for data containers.
+//!
+//! The [ContainerBuilder] collects all properties of a new
+//! owning container and validates them in one place.
+
+use crate::{
+    get_alignment, get_itemsize, ContainerError, RustyDataContainer, DTYPE, MAX_NDIM, MUTABILITY,
+    OWNERSHIP,
+};
+use libc::c_void;
+use std::alloc::Layout;
+
+/// Builder for a new owning and mutable data container.
+#[derive(Debug, Clone)]
+pub struct ContainerBuilder {
+    shape: Vec<usize>,
+    dtype: Option<DTYPE>,
+    alignment: Option<usize>,
+    zero_filled: bool,
+}
+
+impl Default for ContainerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContainerBuilder {
+    /// Create a new builder for a zero filled one dimensional container of length 0.
+    pub fn new() -> Self {
+        Self {
+            shape: vec![0],
+            dtype: None,
+            alignment: None,
+            zero_filled: true,
+        }
+    }
+
+    /// Set the shape of the container.
+    pub fn shape(mut self, shape: &[usize]) -> Self {
+        self.shape = shape.to_vec();
+        self
+    }
+
+    /// Set the dtype of the container.
+    pub fn dtype(mut self, dtype: DTYPE) -> Self {
+        self.dtype = Some(dtype);
+        self
+    }
+
+    /// Set the alignment in bytes of the data. By default the data has
+    /// the natural alignment of the dtype.
+    pub fn aligned(mut self, alignment: usize) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+
+    /// Set whether the data is zero filled. The default is true.
+    ///
+    /// # Safety
+    /// If `zero_filled` is false the data of the built container is uninitialized.
+    /// Every element must be written before it is read.
+    pub unsafe fn zero_filled(mut self, zero_filled: bool) -> Self {
+        self.zero_filled = zero_filled;
+        self
+    }
+
+    /// Validate the properties and allocate the container.
+    pub fn build(self) -> Result<RustyDataContainer, ContainerError> {
+        let dtype = self.dtype.ok_or(ContainerError::MissingDtype)?;
+        if self.shape.is_empty() || self.shape.len() > MAX_NDIM {
+            return Err(ContainerError::InvalidShape);
+        }
+        let nitems = self
+            .shape
+            .iter()
+            .try_fold(1_usize, |acc, &extent| acc.checked_mul(extent))
+            .ok_or(ContainerError::InvalidShape)?;
+        let size = nitems
+            .checked_mul(get_itemsize(dtype))
+            .ok_or(ContainerError::InvalidShape)?;
+        let alignment = self.alignment.unwrap_or(get_alignment(dtype));
+        if !alignment.is_power_of_two() || alignment < get_alignment(dtype) {
+            return Err(ContainerError::InvalidAlignment);
+        }
+        let layout =
+            Layout::from_size_align(size, alignment).map_err(|_| ContainerError::InvalidShape)?;
+        let data = if size == 0 {
+            // A dangling but well aligned pointer, as for an empty Vec.
+            alignment as *mut u8
+        } else if self.zero_filled {
+            unsafe { crate::canary::alloc(layout, true) }
+        } else {
+            unsafe { crate::canary::alloc(layout, false) }
+        };
+        if data.is_null() {
+            return Err(ContainerError::AllocationFailed);
+        }
+        let mut container = RustyDataContainer::new_1d(
+            data as *mut c_void,
+            nitems,
+            nitems,
+            dtype,
+            OWNERSHIP::Owner,
+            MUTABILITY::Mutable,
+        );
+        container.alignment = alignment;
+        container.set_contiguous_shape(&self.shape);
+        Ok(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_aligned_zero_filled_matrix() {
+        let container = ContainerBuilder::new()
+            .shape(&[2, 3])
+            .dtype(DTYPE::Float64)
+            .aligned(32)
+            .build()
+            .unwrap();
+        assert_eq!(&container.shape[..container.ndim], [2, 3]);
+        assert_eq!(container.nitems, 6);
+        assert_eq!(container.alignment, 32);
+        assert!((container.data as usize).is_multiple_of(32));
+        assert_eq!(container.typed::<f64>(), [0.0; 6]);
+    }
+
+    #[test]
+    fn build_rejects_invalid_properties() {
+        let builder = ContainerBuilder::new().shape(&[2, 3]);
+        assert_eq!(
+            builder.clone().build().err(),
+            Some(ContainerError::MissingDtype)
+        );
+        let builder = builder.dtype(DTYPE::Float64);
+        assert_eq!(
+            builder.clone().aligned(24).build().err(),
+            Some(ContainerError::InvalidAlignment)
+        );
+        assert_eq!(
+            builder.clone().aligned(4).build().err(),
+            Some(ContainerError::InvalidAlignment)
+        );
+        assert_eq!(
+            builder.clone().shape(&[]).build().err(),
+            Some(ContainerError::InvalidShape)
+        );
+        assert_eq!(
+            builder.shape(&[usize::MAX, 2]).build().err(),
+            Some(ContainerError::InvalidShape)
+        );
+    }
+}
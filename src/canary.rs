@@ -0,0 +1,170 @@
+//! Canary bytes after owned allocations to detect writes past the end of the data.
+//!
+//! With the `debug-canary` feature every allocation that this crate makes for an
+//! owning container is followed by [CANARY_LEN] bytes with the value [CANARY_BYTE].
+//! The canary is checked by [crate::RustyDataContainer::as_slice] and when the data
+//! is freed. Without the feature the allocation functions forward to [std::alloc]
+//! and no memory is added.
+//!
+//! Memory that was not allocated by this crate, for example a `Vec` passed to
+//! [crate::RustyDataContainer::from_raw_parts], has no canary and is never reported.
+
+use crate::RustyDataContainer;
+use std::alloc::Layout;
+#[cfg(feature = "debug-canary")]
+use std::collections::BTreeMap;
+#[cfg(feature = "debug-canary")]
+use std::sync::Mutex;
+
+/// The number of canary bytes after each allocation.
+#[cfg(feature = "debug-canary")]
+pub const CANARY_LEN: usize = 16;
+/// The number of canary bytes after each allocation.
+#[cfg(not(feature = "debug-canary"))]
+pub const CANARY_LEN: usize = 0;
+
+/// The value of each canary byte.
+pub const CANARY_BYTE: u8 = 0xA5;
+
+/// Sizes without canary of all allocations with a canary, keyed by their address.
+#[cfg(feature = "debug-canary")]
+static PADDED: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+fn padded(layout: Layout) -> Layout {
+    Layout::from_size_align(layout.size() + CANARY_LEN, layout.align()).unwrap()
+}
+
+#[cfg(feature = "debug-canary")]
+unsafe fn register(data: *mut u8, size: usize) {
+    std::ptr::write_bytes(data.add(size), CANARY_BYTE, CANARY_LEN);
+    PADDED.lock().unwrap().insert(data as usize, size);
+}
+
+#[cfg(not(feature = "debug-canary"))]
+unsafe fn register(_data: *mut u8, _size: usize) {}
+
+/// Forget the canary of an allocation and return whether it had one.
+#[cfg(feature = "debug-canary")]
+fn unregister(data: *mut u8) -> bool {
+    let size = PADDED.lock().unwrap().remove(&(data as usize));
+    if let Some(size) = size {
+        if !canary_intact(data, size) {
+            eprintln!("rusty-cffi: canary after the data at {data:p} was overwritten");
+        }
+    }
+    size.is_some()
+}
+
+#[cfg(not(feature = "debug-canary"))]
+fn unregister(_data: *mut u8) -> bool {
+    false
+}
+
+fn canary_intact(data: *const u8, size: usize) -> bool {
+    let canary = unsafe { std::slice::from_raw_parts(data.add(size), CANARY_LEN) };
+    canary.iter().all(|&byte| byte == CANARY_BYTE)
+}
+
+/// Allocate memory for `layout` followed by the canary. `layout` must have nonzero size.
+pub(crate) unsafe fn alloc(layout: Layout, zeroed: bool) -> *mut u8 {
+    let data = if zeroed {
+        std::alloc::alloc_zeroed(padded(layout))
+    } else {
+        std::alloc::alloc(padded(layout))
+    };
+    if !data.is_null() {
+        register(data, layout.size());
+    }
+    data
+}
+
+/// Free memory that was allocated with `layout`, either by [alloc] or as a `Vec`.
+pub(crate) unsafe fn dealloc(data: *mut u8, layout: Layout) {
+    if unregister(data) {
+        std::alloc::dealloc(data, padded(layout));
+    } else {
+        std::alloc::dealloc(data, layout);
+    }
+}
+
+/// Reallocate memory that was allocated with `layout` to `new_size` bytes.
+///
+/// Memory without a canary is reallocated without a canary. On failure null is
+/// returned and the old allocation is unchanged.
+pub(crate) unsafe fn realloc(data: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    if !unregister(data) {
+        return std::alloc::realloc(data, layout, new_size);
+    }
+    let result = std::alloc::realloc(data, padded(layout), new_size + CANARY_LEN);
+    if result.is_null() {
+        register(data, layout.size());
+    } else {
+        register(result, new_size);
+    }
+    result
+}
+
+/// The size without canary of the allocation at `data`, if it has a canary.
+#[cfg(feature = "debug-canary")]
+fn padded_size(data: *const u8) -> Option<usize> {
+    PADDED.lock().unwrap().get(&(data as usize)).copied()
+}
+
+#[cfg(not(feature = "debug-canary"))]
+fn padded_size(_data: *const u8) -> Option<usize> {
+    None
+}
+
+/// Whether `data` is an allocation with a canary.
+pub(crate) fn is_padded(data: *const u8) -> bool {
+    padded_size(data).is_some()
+}
+
+impl RustyDataContainer {
+    /// Whether the canary after the data is intact.
+    ///
+    /// Always true if the data has no canary, in particular without the
+    /// `debug-canary` feature.
+    pub fn check_canary(&self) -> bool {
+        padded_size(self.data as *const u8)
+            .is_none_or(|size| canary_intact(self.data as *const u8, size))
+    }
+}
+
+/// Check that the canary after the data of a container is intact.
+///
+/// Returns true if the data has no canary, which is always the case
+/// without the `debug-canary` feature.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_check_canary(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    RustyDataContainer::leak(ptr).check_canary()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn intact_canary() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]));
+        assert!(rusty_data_container_check_canary(handle(ptr)));
+        destroy(ptr);
+    }
+
+    #[test]
+    #[cfg(feature = "debug-canary")]
+    fn detect_writes_past_the_data() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_u32, 2, 3]));
+        let data = RustyDataContainer::leak(handle(ptr)).data as *mut u8;
+        assert!(is_padded(data));
+        unsafe { *data.add(12) = 0 };
+        assert!(!rusty_data_container_check_canary(handle(ptr)));
+        unsafe { *data.add(12) = CANARY_BYTE };
+        assert!(rusty_data_container_check_canary(handle(ptr)));
+        unsafe { *data.add(12 + CANARY_LEN - 1) = 0 };
+        assert!(!rusty_data_container_check_canary(handle(ptr)));
+        unsafe { *data.add(12 + CANARY_LEN - 1) = CANARY_BYTE };
+        destroy(ptr);
+    }
+}
@@ -0,0 +1,149 @@
+//! Incremental checksums of container data.
+//!
+//! The checksum is the 128 bit FNV-1a hash of the bytes of the elements in
+//! row-major order. It can be computed in bounded memory by feeding the data
+//! to a [ChecksumState] in chunks, which gives the same result as hashing all
+//! bytes at once. It is not a cryptographic hash.
+
+use crate::RustyDataContainer;
+use libc::size_t;
+
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// The state of an incremental 128 bit FNV-1a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumState {
+    hash: u128,
+}
+
+impl ChecksumState {
+    /// Create the state of an empty input.
+    pub fn new() -> Self {
+        Self { hash: FNV_OFFSET }
+    }
+
+    /// Feed the next bytes of the input.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= byte as u128;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// The hash of all bytes fed so far as little endian bytes.
+    pub fn finalize(&self) -> [u8; 16] {
+        self.hash.to_le_bytes()
+    }
+}
+
+impl Default for ChecksumState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RustyDataContainer {
+    /// The checksum of the bytes of all elements in row-major order.
+    pub fn checksum(&self) -> [u8; 16] {
+        let mut state = ChecksumState::new();
+        for index in 0..self.nitems {
+            state.update(self.element_bytes(index));
+        }
+        state.finalize()
+    }
+
+    /// The same checksum as [RustyDataContainer::checksum], computed in chunks of
+    /// `chunk_bytes` bytes.
+    ///
+    /// Contiguous data is hashed directly from memory, so no buffer is needed.
+    /// Strided data is gathered into a buffer of at most `chunk_bytes` bytes, or of
+    /// one element if `chunk_bytes` is smaller than the itemsize.
+    pub fn checksum_streamed(&self, chunk_bytes: usize) -> [u8; 16] {
+        let chunk_bytes = chunk_bytes.max(1);
+        let mut state = ChecksumState::new();
+        if self.is_contiguous() {
+            for chunk in self.bytes().chunks(chunk_bytes) {
+                state.update(chunk);
+            }
+            return state.finalize();
+        }
+        let mut buffer = Vec::with_capacity(chunk_bytes.max(self.itemsize));
+        for index in 0..self.nitems {
+            if buffer.len() + self.itemsize > buffer.capacity() {
+                state.update(&buffer);
+                buffer.clear();
+            }
+            buffer.extend_from_slice(self.element_bytes(index));
+        }
+        state.update(&buffer);
+        state.finalize()
+    }
+}
+
+/// Write the 16 byte checksum of the data of a container to `out`.
+///
+/// # Safety
+/// `out` must be valid for writing 16 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_checksum(
+    ptr: Option<Box<RustyDataContainer>>,
+    out: *mut u8,
+) {
+    let checksum = RustyDataContainer::leak(ptr).checksum();
+    std::ptr::copy_nonoverlapping(checksum.as_ptr(), out, checksum.len());
+}
+
+/// Write the 16 byte checksum of the data of a container to `out`, reading the
+/// data in chunks of `chunk_bytes` bytes.
+///
+/// The result is the same as for [rusty_data_container_checksum].
+///
+/// # Safety
+/// `out` must be valid for writing 16 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_checksum_streamed(
+    ptr: Option<Box<RustyDataContainer>>,
+    chunk_bytes: size_t,
+    out: *mut u8,
+) {
+    let checksum = RustyDataContainer::leak(ptr).checksum_streamed(chunk_bytes);
+    std::ptr::copy_nonoverlapping(checksum.as_ptr(), out, checksum.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn streamed_checksum_matches_the_one_shot_checksum() {
+        let values: Vec<u32> = (0..1000).map(|index| index * 7919).collect();
+        let ptr = boxed(RustyDataContainer::from_vec(values));
+        let mut one_shot = [0_u8; 16];
+        unsafe { rusty_data_container_checksum(handle(ptr), one_shot.as_mut_ptr()) };
+        for chunk_bytes in [0, 1, 3, 64, 4000, 1 << 20] {
+            let mut streamed = [0_u8; 16];
+            unsafe {
+                rusty_data_container_checksum_streamed(
+                    handle(ptr),
+                    chunk_bytes,
+                    streamed.as_mut_ptr(),
+                )
+            };
+            assert_eq!(streamed, one_shot);
+        }
+
+        let container = RustyDataContainer::leak(handle(ptr));
+        let mut state = ChecksumState::new();
+        state.update(&container.bytes()[..100]);
+        state.update(&container.bytes()[100..]);
+        assert_eq!(state.finalize(), one_shot);
+
+        let reversed = container.reverse_view();
+        assert_ne!(reversed.checksum(), one_shot);
+        assert_eq!(reversed.checksum_streamed(10), reversed.checksum());
+        assert_eq!(reversed.checksum(), reversed.to_contiguous().checksum());
+        destroy(ptr);
+    }
+}
@@ -4,260 +4,4000 @@
 //! is a simple C data structure that can be flexibly converted to
 //! various Rust types.
 
-use crate::{assert_dtype, get_itemsize, ConversionType, DTYPE, MUTABILITY, OWNERSHIP};
-use libc::{c_void, size_t};
+use crate::{
+    assert_dtype, dispatch_dtype, get_alignment, get_itemsize, CastFrom, CastFromAny, Complex64,
+    ContainerError, ConversionType, DTYPE, MUTABILITY, OWNERSHIP,
+};
+use libc::{c_double, c_void, size_t};
+use std::alloc::Layout;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Addresses of all boxed containers that are currently alive.
+///
+/// This allows [rusty_data_container_destroy] to detect pointers
+/// that were already destroyed instead of freeing them twice. The detection only
+/// works until the allocator reuses the address for a new container. From then on
+/// the old pointer is indistinguishable from the new container and a second destroy
+/// frees the new container.
+static LIVE_CONTAINERS: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
+#[cfg(test)]
+thread_local! {
+    /// The number of bytes overwritten by [wipe_bytes] on this thread, to test that freed data is wiped.
+    static WIPED_BYTES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Overwrite `size` bytes at `data` with zeros using volatile writes, so that the
+/// writes are not optimized away when the data is freed afterwards.
+///
+/// # Safety
+/// `data` must be valid for writing `size` bytes.
+unsafe fn wipe_bytes(data: *mut u8, size: usize) {
+    for offset in 0..size {
+        std::ptr::write_volatile(data.add(offset), 0);
+    }
+    std::sync::atomic::compiler_fence(Ordering::SeqCst);
+    #[cfg(test)]
+    WIPED_BYTES.with(|wiped| wiped.set(wiped.get() + size));
+}
+
+/// Whether `ptr` is the address of a boxed container that is alive.
+#[cfg(feature = "safe-views")]
+pub(crate) fn is_live(ptr: *const RustyDataContainer) -> bool {
+    LIVE_CONTAINERS.lock().unwrap().contains(&(ptr as usize))
+}
+
+/// The element type of the buffers of shared containers.
+///
+/// Its alignment is at least the alignment of every dtype.
+pub(crate) type SharedWord = u128;
+
+const _: () = assert!(std::mem::align_of::<SharedWord>() >= std::mem::align_of::<Complex64>());
+
+/// The maximum number of dimensions of a container.
+pub const MAX_NDIM: usize = 8;
 
 /// A data container for communication with a C ABI.
 ///
 /// The basic task of this container is to provide a C ABI
 /// compatible type to store arbitrary data arrays, and
 /// to convert them back and forth into corresponding Rust types.
+///
+/// The layout is `repr(C)` so that C code can mirror the struct.
+/// The field offsets are checked at compile time below.
+#[repr(C)]
 pub struct RustyDataContainer {
     /// The number of elements in the Array.
-    nitems: size_t,
+    pub(crate) nitems: size_t,
     /// The size in bytes of each element.
-    itemsize: size_t,
+    pub(crate) itemsize: size_t,
     /// The capacity of the underlying array.
     /// This is only needed if the container is allocated
     /// from a Rust Vec.
-    capacity: size_t,
+    pub(crate) capacity: size_t,
     /// The type of the data.
-    dtype: DTYPE,
+    pub(crate) dtype: DTYPE,
     /// The ownership of the data. It is either
-    /// [OWNERSHIP::Owner] or [OWNERSHIP::NotOwner].
+    /// [OWNERSHIP::Owner], [OWNERSHIP::NotOwner] or [OWNERSHIP::Shared].
     /// The underlying data can only be destroyed if
-    /// [DataContainer] is owner.
-    is_owner: OWNERSHIP,
+    /// [DataContainer] is owner. Shared data is destroyed
+    /// together with the last container referencing it.
+    pub(crate) is_owner: OWNERSHIP,
     /// Mutability of the underlying data. It is either
     /// [MUTABILITY::Mutable] or [MUTABILITY::NotMutable].
-    is_mutable: MUTABILITY,
+    pub(crate) is_mutable: MUTABILITY,
     /// A pointer to the underlying data.
-    data: *mut c_void,
+    pub(crate) data: *mut c_void,
+    /// The number of dimensions.
+    pub(crate) ndim: size_t,
+    /// The shape of the data. Only the first `ndim` entries are used.
+    pub(crate) shape: [size_t; MAX_NDIM],
+    /// The strides of the data in units of elements. Only the first `ndim`
+    /// entries are used. Strides may be negative, in which case `data`
+    /// points to the first element in logical order, not the lowest address.
+    pub(crate) strides: [isize; MAX_NDIM],
+    /// The alignment in bytes of the allocation of owned data.
+    /// Data allocated from a Rust Vec has the natural alignment of the dtype.
+    pub(crate) alignment: size_t,
+    /// Whether a C caller holds exclusive mutable access through
+    /// [rusty_data_container_borrow_mut].
+    pub(crate) borrowed: AtomicBool,
+    /// Whether owned data is overwritten with zeros before it is freed.
+    pub(crate) zeroize: bool,
+    /// The number of times the data was reallocated.
+    pub(crate) generation: u64,
+    /// The container whose data this view references, kept alive by the view.
+    /// Only set with the `safe-views` feature, see [crate::views].
+    pub(crate) parent: *mut RustyDataContainer,
+    /// The state of the reader-writer lock of [rusty_data_container_read_lock] and
+    /// [rusty_data_container_write_lock]: the number of readers, or [WRITE_LOCKED].
+    pub(crate) lock: AtomicUsize,
 }
 
+// Compile-time check of the C layout of the container. With `W` the pointer
+// width in bytes the offsets are, with the values for 64 bit targets in brackets:
+//
+// | field        | offset        |
+// |--------------|---------------|
+// | `nitems`     | 0             |
+// | `itemsize`   | W (8)         |
+// | `capacity`   | 2W (16)       |
+// | `dtype`      | 3W (24)       |
+// | `is_owner`   | 3W + 4 (28)   |
+// | `is_mutable` | 3W + 5 (29)   |
+// | `data`       | 4W (32)       |
+// | `ndim`       | 5W (40)       |
+// | `shape`      | 6W (48)       |
+// | `strides`    | 14W (112)     |
+// | `alignment`  | 22W (176)     |
+// | `borrowed`   | 23W (184)     |
+// | `zeroize`    | 23W + 1 (185) |
+// | `generation` | 24W (192)     |
+// | `parent`     | 24W + 8 (200) |
+// | `lock`       | 25W + 8 (208) |
+//
+// The total size is 26W + 8 (216) bytes, rounded up to the alignment, which is
+// W, or the alignment of a u64 if larger.
+const _: () = {
+    use std::mem::{align_of, offset_of, size_of};
+    const W: usize = size_of::<usize>();
+    const fn max(a: usize, b: usize) -> usize {
+        if a > b {
+            a
+        } else {
+            b
+        }
+    }
+
+    assert!(size_of::<DTYPE>() == 4);
+    assert!(size_of::<OWNERSHIP>() == 1);
+    assert!(size_of::<MUTABILITY>() == 1);
+    assert!(OWNERSHIP::NotOwner as u8 == 0);
+    assert!(OWNERSHIP::Owner as u8 == 1);
+    assert!(OWNERSHIP::Shared as u8 == 2);
+    assert!(MUTABILITY::NotMutable as u8 == 0);
+    assert!(MUTABILITY::Mutable as u8 == 1);
+
+    assert!(offset_of!(RustyDataContainer, nitems) == 0);
+    assert!(offset_of!(RustyDataContainer, itemsize) == W);
+    assert!(offset_of!(RustyDataContainer, capacity) == 2 * W);
+    assert!(offset_of!(RustyDataContainer, dtype) == 3 * W);
+    assert!(offset_of!(RustyDataContainer, is_owner) == 3 * W + 4);
+    assert!(offset_of!(RustyDataContainer, is_mutable) == 3 * W + 5);
+    assert!(offset_of!(RustyDataContainer, data) == 4 * W);
+    assert!(offset_of!(RustyDataContainer, ndim) == 5 * W);
+    assert!(offset_of!(RustyDataContainer, shape) == 6 * W);
+    assert!(offset_of!(RustyDataContainer, strides) == (6 + MAX_NDIM) * W);
+    assert!(offset_of!(RustyDataContainer, alignment) == (6 + 2 * MAX_NDIM) * W);
+    assert!(offset_of!(RustyDataContainer, borrowed) == (7 + 2 * MAX_NDIM) * W);
+    assert!(offset_of!(RustyDataContainer, zeroize) == (7 + 2 * MAX_NDIM) * W + 1);
+    assert!(offset_of!(RustyDataContainer, generation) == (8 + 2 * MAX_NDIM) * W);
+    assert!(size_of::<AtomicBool>() == 1);
+    assert!(offset_of!(RustyDataContainer, parent) == (8 + 2 * MAX_NDIM) * W + 8);
+    assert!(offset_of!(RustyDataContainer, lock) == (9 + 2 * MAX_NDIM) * W + 8);
+    assert!(
+        size_of::<RustyDataContainer>()
+            == ((10 + 2 * MAX_NDIM) * W + 8).next_multiple_of(max(W, align_of::<u64>()))
+    );
+    assert!(align_of::<RustyDataContainer>() == max(W, align_of::<u64>()));
+};
+
 impl RustyDataContainer {
-    /// Create a new non-owning and non-mutable container from a given slice.
-    pub fn from_slice<T: ConversionType>(slice: &[T]) -> Self {
+    /// Create a new one dimensional contiguous container.
+    pub(crate) fn new_1d(
+        data: *mut c_void,
+        nitems: size_t,
+        capacity: size_t,
+        dtype: DTYPE,
+        is_owner: OWNERSHIP,
+        is_mutable: MUTABILITY,
+    ) -> Self {
+        let mut shape = [0; MAX_NDIM];
+        let mut strides = [0; MAX_NDIM];
+        shape[0] = nitems;
+        strides[0] = 1;
         Self {
-            nitems: slice.len(),
-            capacity: slice.len(),
-            itemsize: crate::get_size::<T>(),
-            dtype: crate::get_dtype::<T>(),
-            is_owner: OWNERSHIP::NotOwner,
-            is_mutable: MUTABILITY::NotMutable,
-            data: slice.as_ptr() as *mut c_void,
+            nitems,
+            itemsize: get_itemsize(dtype),
+            capacity,
+            dtype,
+            is_owner,
+            is_mutable,
+            data,
+            ndim: 1,
+            shape,
+            strides,
+            alignment: get_alignment(dtype),
+            borrowed: AtomicBool::new(false),
+            zeroize: false,
+            generation: 0,
+            parent: std::ptr::null_mut(),
+            lock: AtomicUsize::new(0),
         }
     }
+
+    /// Create a new non-owning and non-mutable container from a given slice.
+    pub fn from_slice<T: ConversionType>(slice: &[T]) -> Self {
+        Self::new_1d(
+            slice.as_ptr() as *mut c_void,
+            slice.len(),
+            slice.len(),
+            crate::get_dtype::<T>(),
+            OWNERSHIP::NotOwner,
+            MUTABILITY::NotMutable,
+        )
+    }
     /// Create a new non-owning but mutable container from a given slice.
     pub fn from_slice_mut<T: ConversionType>(slice: &mut [T]) -> Self {
-        Self {
-            nitems: slice.len(),
-            capacity: slice.len(),
-            itemsize: crate::get_size::<T>(),
-            dtype: crate::get_dtype::<T>(),
-            is_owner: OWNERSHIP::NotOwner,
-            is_mutable: MUTABILITY::Mutable,
-            data: slice.as_ptr() as *mut c_void,
-        }
+        Self::new_1d(
+            slice.as_ptr() as *mut c_void,
+            slice.len(),
+            slice.len(),
+            crate::get_dtype::<T>(),
+            OWNERSHIP::NotOwner,
+            MUTABILITY::Mutable,
+        )
+    }
+
+    /// Create a new non-owning and non-mutable container from a fixed-size array.
+    pub fn from_array<T: ConversionType, const N: usize>(array: &[T; N]) -> Self {
+        Self::from_slice(array)
     }
 
     // To boxed pointer.
     pub fn to_box(self) -> Box<RustyDataContainer> {
-        Box::new(self)
+        let boxed = Box::new(self);
+        LIVE_CONTAINERS
+            .lock()
+            .unwrap()
+            .insert(&*boxed as *const RustyDataContainer as usize);
+        boxed
+    }
+
+    /// Move a container out of its box.
+    // The box is needed to remove its address from the live containers.
+    #[allow(clippy::boxed_local)]
+    pub fn from_box(boxed: Box<RustyDataContainer>) -> Self {
+        LIVE_CONTAINERS
+            .lock()
+            .unwrap()
+            .remove(&(&*boxed as *const RustyDataContainer as usize));
+        *boxed
     }
 
+    /// Convert an owning container into a Rust Vec.
+    ///
+    /// # Safety
+    /// The container must own its data, and the data must have been
+    /// allocated as a `Vec<T>` with the stored capacity.
     pub unsafe fn to_vec<T: ConversionType>(mut self) -> Vec<T> {
         assert_eq!(self.is_owner, OWNERSHIP::Owner);
         assert_dtype::<T>(self.dtype);
+        assert_eq!(self.alignment, std::mem::align_of::<T>());
+        if crate::canary::is_padded(self.data as *const u8) {
+            // Allocations with a canary are not `Vec` allocations and must be copied.
+            let mut vec = Vec::<T>::with_capacity(self.nitems);
+            std::ptr::copy_nonoverlapping(self.data as *const T, vec.as_mut_ptr(), self.nitems);
+            vec.set_len(self.nitems);
+            return vec;
+        }
         // Have to remove ownership as the Vec takes ownership of the
         // contained data.
         self.is_owner = OWNERSHIP::NotOwner;
         Vec::<T>::from_raw_parts(self.data as *mut T, self.nitems, self.capacity)
     }
 
+    /// Convert an owning container into its shape and a Vec with the data in row-major order.
+    ///
+    /// The data is not copied. With the `ndarray` feature
+    /// [RustyDataContainer::into_ndarray] turns the result into an owned array.
+    pub fn into_shape_vec<T: ConversionType>(self) -> Result<(Vec<usize>, Vec<T>), ContainerError> {
+        crate::ensure_dtype::<T>(self.dtype)?;
+        if self.is_owner != OWNERSHIP::Owner || self.alignment != std::mem::align_of::<T>() {
+            return Err(ContainerError::NotOwner);
+        }
+        if !self.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        let shape = self.shape[..self.ndim].to_vec();
+        Ok((shape, unsafe { self.to_vec::<T>() }))
+    }
+
+    /// Convert an owning container into an owned `ndarray` array of the same shape.
+    ///
+    /// The data is not copied. Returns the errors of [RustyDataContainer::into_shape_vec].
+    #[cfg(feature = "ndarray")]
+    pub fn into_ndarray<T: ConversionType>(self) -> Result<ndarray::ArrayD<T>, ContainerError> {
+        let (shape, vec) = self.into_shape_vec::<T>()?;
+        ndarray::ArrayD::from_shape_vec(shape, vec).map_err(|_| ContainerError::InvalidShape)
+    }
+
+    /// Decompose an owning container into `(data, nitems, capacity, itemsize, alignment, dtype)`.
+    ///
+    /// The data is not freed. The caller becomes responsible for the memory, which
+    /// was allocated with the given alignment, for example by [crate::ContainerBuilder::aligned],
+    /// and can be handed back with [RustyDataContainer::from_raw_parts]. Fails with
+    /// [ContainerError::NotOwner] if the container does not own its data.
+    pub fn into_raw_parts(
+        self,
+    ) -> Result<(*mut c_void, size_t, size_t, size_t, size_t, DTYPE), ContainerError> {
+        if self.is_owner != OWNERSHIP::Owner {
+            return Err(ContainerError::NotOwner);
+        }
+        let parts = (
+            self.data,
+            self.nitems,
+            self.capacity,
+            self.itemsize,
+            self.alignment,
+            self.dtype,
+        );
+        std::mem::forget(self);
+        Ok(parts)
+    }
+
+    /// Release the data of an owning contiguous container to the caller.
+    ///
+    /// The data ends up in an allocation of exactly `nitems * itemsize` bytes that is
+    /// aligned to `itemsize`, which is copied from the buffer of the container if its
+    /// capacity or alignment differ. The result must be freed with [rusty_free].
+    /// Returns null for an empty container. Panics if the container is not owning
+    /// and contiguous.
+    pub fn into_data(mut self) -> *mut c_void {
+        assert_eq!(self.is_owner, OWNERSHIP::Owner);
+        assert!(self.is_contiguous());
+        let size = self.nitems * self.itemsize;
+        if size == 0 {
+            return std::ptr::null_mut();
+        }
+        if self.capacity == self.nitems && self.alignment == self.itemsize {
+            // The buffer has the final layout, so hand it over without freeing it.
+            self.is_owner = OWNERSHIP::NotOwner;
+            return self.data;
+        }
+        let layout = Layout::from_size_align(size, self.itemsize).unwrap();
+        let data = unsafe { crate::canary::alloc(layout, false) };
+        if data.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        unsafe { std::ptr::copy_nonoverlapping(self.data as *const u8, data, size) };
+        data as *mut c_void
+    }
+
+    /// Create a new owning and mutable one dimensional container from its raw parts.
+    ///
+    /// The data is freed with the layout of `capacity` elements and `alignment`.
+    ///
+    /// # Safety
+    /// The parts must have been obtained from [RustyDataContainer::into_raw_parts],
+    /// or `data` must otherwise have been allocated as a `Vec` of the Rust type
+    /// corresponding to `dtype` with length `nitems` and capacity `capacity`, in
+    /// which case `alignment` is [crate::get_alignment] of `dtype`.
+    pub unsafe fn from_raw_parts(
+        data: *mut c_void,
+        nitems: size_t,
+        capacity: size_t,
+        alignment: size_t,
+        dtype: DTYPE,
+    ) -> Self {
+        let mut container = Self::new_1d(
+            data,
+            nitems,
+            capacity,
+            dtype,
+            OWNERSHIP::Owner,
+            MUTABILITY::Mutable,
+        );
+        container.alignment = alignment;
+        container
+    }
+
+    /// Copy the data into a new Vec, converting each element to `T` like an `as` cast.
+    /// Returns [ContainerError::DtypeMismatch] for the non-numeric [DTYPE::Bool].
+    /// This method does not take ownership of the container associated with `ptr`.
+    pub fn to_vec_as<T: ConversionType + CastFromAny>(
+        ptr: Option<Box<RustyDataContainer>>,
+    ) -> Result<Vec<T>, ContainerError> {
+        let container = RustyDataContainer::leak(ptr);
+        if container.dtype == DTYPE::Bool {
+            return Err(ContainerError::DtypeMismatch);
+        }
+        Ok(dispatch_dtype!(container.dtype, S => (0..container.nitems)
+            .map(|index| T::cast_from(container.element::<S>(index)))
+            .collect()))
+    }
+
     /// Get a mutable reference to a RustyDataContainer from a ptr.
     /// Ensures that the destructor of the data container is not run.
+    /// Panics if the container is not mutable or is borrowed by a C caller.
     pub fn leak_mut(ptr: Option<Box<RustyDataContainer>>) -> &'static mut RustyDataContainer {
         let ptr_ref = Box::leak(ptr.unwrap());
         assert_eq!(ptr_ref.is_mutable, MUTABILITY::Mutable);
+        assert!(
+            !ptr_ref.is_borrowed(),
+            "container is mutably borrowed by a C caller"
+        );
         ptr_ref
     }
 
+    /// Get a mutable reference to a RustyDataContainer from a ptr for a C function that changes it.
+    /// Ensures that the destructor of the data container is not run.
+    ///
+    /// Returns `None` and sets the last error if `ptr` is null, if the container is borrowed
+    /// by a C caller, or if `writes_data` is true and the container is not mutable. Changes
+    /// of only the metadata, such as the shape, do not require a mutable container.
+    pub fn try_leak_mut(
+        ptr: Option<Box<RustyDataContainer>>,
+        writes_data: bool,
+    ) -> Option<&'static mut RustyDataContainer> {
+        let Some(ptr) = ptr else {
+            crate::set_last_error(ContainerError::NullPointer);
+            return None;
+        };
+        let container = Box::leak(ptr);
+        let error = if container.is_borrowed() {
+            ContainerError::Borrowed
+        } else if writes_data && container.is_mutable != MUTABILITY::Mutable {
+            ContainerError::NotMutable
+        } else {
+            return Some(container);
+        };
+        crate::set_last_error(error);
+        None
+    }
+
     /// Get a reference to a RustyDataContainer from a ptr.
     /// Ensures that the destructor of the data container is not run.
+    /// The `'static` lifetime is not checked against the lifetime of the container,
+    /// prefer [RustyDataContainer::borrow] where the borrow is local.
     pub fn leak(ptr: Option<Box<RustyDataContainer>>) -> &'static RustyDataContainer {
         Box::leak(ptr.unwrap())
     }
 
+    /// Borrow the container behind a handle received from C.
+    ///
+    /// Unlike [RustyDataContainer::leak] the borrow cannot outlive the handle. Wrap
+    /// the handle in [ManuallyDrop] so that the container is not destroyed when the
+    /// handle goes out of scope.
+    pub fn borrow(ptr: &Option<Box<RustyDataContainer>>) -> ContainerRef<'_> {
+        ContainerRef {
+            container: ptr.as_deref().unwrap(),
+        }
+    }
+
+    /// Mutably borrow the container behind a handle received from C.
+    ///
+    /// Panics if the container is not mutable or is borrowed by a C caller.
+    /// See [RustyDataContainer::borrow].
+    pub fn borrow_mut(ptr: &mut Option<Box<RustyDataContainer>>) -> ContainerMut<'_> {
+        let container = ptr.as_deref_mut().unwrap();
+        assert_eq!(container.is_mutable, MUTABILITY::Mutable);
+        assert!(
+            !container.is_borrowed(),
+            "container is mutably borrowed by a C caller"
+        );
+        ContainerMut { container }
+    }
+
     /// Create a new owning and mutable container from a vector.
     /// The vector is consumed by this method.
     pub fn from_vec<T: ConversionType>(vec: Vec<T>) -> Self {
+        #[cfg(feature = "debug-canary")]
+        if !vec.is_empty() {
+            return Self::from_slice_padded(&vec);
+        }
         let nitems = vec.len();
         let capacity = vec.capacity();
         let data = vec.as_ptr() as *mut c_void;
         std::mem::forget(vec);
-        Self {
+        Self::new_1d(
+            data,
             nitems,
             capacity,
-            itemsize: crate::get_size::<T>(),
-            dtype: crate::get_dtype::<T>(),
-            is_owner: OWNERSHIP::Owner,
-            is_mutable: MUTABILITY::Mutable,
-            data,
+            crate::get_dtype::<T>(),
+            OWNERSHIP::Owner,
+            MUTABILITY::Mutable,
+        )
+    }
+
+    /// Copy a nonempty slice into a new allocation with a canary, see [crate::canary].
+    #[cfg(feature = "debug-canary")]
+    fn from_slice_padded<T: ConversionType>(values: &[T]) -> Self {
+        let layout = Layout::array::<T>(values.len()).unwrap();
+        let data = unsafe { crate::canary::alloc(layout, false) };
+        if data.is_null() {
+            std::alloc::handle_alloc_error(layout);
         }
+        unsafe { std::ptr::copy_nonoverlapping(values.as_ptr(), data as *mut T, values.len()) };
+        Self::new_1d(
+            data as *mut c_void,
+            values.len(),
+            values.len(),
+            crate::get_dtype::<T>(),
+            OWNERSHIP::Owner,
+            MUTABILITY::Mutable,
+        )
     }
 
-    /// Get a representation of the data as slice.
-    /// This method does not take ownership of the container associated with `ptr`.
-    pub unsafe fn as_slice<T: ConversionType>(
-        ptr: Option<Box<RustyDataContainer>>,
-    ) -> &'static [T] {
-        let container = RustyDataContainer::leak(ptr);
-        assert_dtype::<T>(container.dtype);
-        std::slice::from_raw_parts::<'static, T>(container.data as *const T, container.nitems)
+    /// Create a new owning and mutable container whose element `index` is `f(index)`.
+    ///
+    /// This is analogous to [std::array::from_fn].
+    pub fn from_fn<T: ConversionType>(nitems: usize, f: impl FnMut(usize) -> T) -> Self {
+        RustyDataContainer::from_vec((0..nitems).map(f).collect())
     }
 
-    /// Get a representation of the data as mutable slice.
-    /// This method does not take ownership of the container associated with `ptr`.
-    pub unsafe fn as_slice_mut<T: ConversionType>(
-        ptr: Option<Box<RustyDataContainer>>,
-    ) -> &'static mut [T] {
-        let container = RustyDataContainer::leak_mut(ptr);
-        assert_eq!(container.is_mutable, MUTABILITY::Mutable);
-        std::slice::from_raw_parts_mut::<'static, T>(container.data as *mut T, container.nitems)
+    /// Create a float container with the values `start, start + step, ...` excluding `stop`.
+    ///
+    /// The values are computed in f64 and converted to `dtype`, which must be a float
+    /// dtype. Returns [ContainerError::InvalidShape] if `step` is zero or the number of
+    /// values is not finite or too large.
+    pub fn arange(start: f64, stop: f64, step: f64, dtype: DTYPE) -> Result<Self, ContainerError> {
+        let count = ((stop - start) / step).ceil().max(0.0);
+        if step == 0.0 || !count.is_finite() || count >= (isize::MAX as usize / 16) as f64 {
+            return Err(ContainerError::InvalidShape);
+        }
+        Self::from_f64_fn(count as usize, dtype, |index| start + index as f64 * step)
     }
-}
 
-impl Drop for RustyDataContainer {
-    /// Destroy a data container. If the container owns the
-    /// data the corresponding memory is also deallocated.
-    fn drop(&mut self) {
-        if let OWNERSHIP::Owner = self.is_owner {
-            let len = self.nitems * self.itemsize;
-            let cap = self.capacity * self.itemsize;
-            let vec = unsafe { Vec::<u8>::from_raw_parts(self.data as *mut u8, len, cap) };
-            drop(vec);
+    /// Create a float container with `num` evenly spaced values from `start` to `stop`.
+    ///
+    /// Both endpoints are included, a single value is `start`. The values are computed
+    /// in f64 and converted to `dtype`, which must be a float dtype. Returns
+    /// [ContainerError::InvalidShape] if `num` is zero.
+    pub fn linspace(
+        start: f64,
+        stop: f64,
+        num: usize,
+        dtype: DTYPE,
+    ) -> Result<Self, ContainerError> {
+        if num == 0 {
+            return Err(ContainerError::InvalidShape);
         }
+        let delta = if num == 1 {
+            0.0
+        } else {
+            (stop - start) / (num - 1) as f64
+        };
+        Self::from_f64_fn(num, dtype, |index| {
+            if num > 1 && index == num - 1 {
+                stop
+            } else {
+                start + index as f64 * delta
+            }
+        })
     }
-}
 
-/// Destroy a data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_destroy(_: Option<Box<RustyDataContainer>>) {}
+    /// Create a container of the float dtype `dtype` whose element `index` is `f(index)`.
+    fn from_f64_fn(
+        nitems: usize,
+        dtype: DTYPE,
+        f: impl Fn(usize) -> f64,
+    ) -> Result<Self, ContainerError> {
+        match dtype {
+            DTYPE::Float32 => Ok(Self::from_fn(nitems, |index| f(index) as f32)),
+            DTYPE::Float64 => Ok(Self::from_fn(nitems, f)),
+            _ => Err(ContainerError::DtypeMismatch),
+        }
+    }
 
-/// Create a new f32 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_f32(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as f32; nitems]).to_box()
-}
+    /// Create a new owning and mutable zero initialized container of type `dtype`.
+    pub(crate) fn new_zeroed(nitems: usize, dtype: DTYPE) -> Self {
+        dispatch_dtype!(dtype, T => RustyDataContainer::from_vec(vec![T::default(); nitems]))
+    }
 
-/// Create a new f64 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_f64(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as f64; nitems]).to_box()
-}
+    /// Like [RustyDataContainer::new_zeroed], but fails with [ContainerError::AllocationFailed]
+    /// instead of aborting if the data cannot be allocated.
+    pub(crate) fn try_new_zeroed(nitems: usize, dtype: DTYPE) -> Result<Self, ContainerError> {
+        dispatch_dtype!(dtype, T => {
+            let mut data = Vec::new();
+            data.try_reserve_exact(nitems)
+                .map_err(|_| ContainerError::AllocationFailed)?;
+            data.resize(nitems, T::default());
+            Ok(RustyDataContainer::from_vec(data))
+        })
+    }
 
-/// Create a new u8 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_u8(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as u8; nitems]).to_box()
-}
+    /// Create a new owning and mutable container of type `dtype` filled with ones.
+    pub(crate) fn new_ones(nitems: usize, dtype: DTYPE) -> Self {
+        dispatch_dtype!(dtype, T => RustyDataContainer::from_vec(vec![T::cast_from(1_u8); nitems]))
+    }
 
-/// Create a new u32 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_u32(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as u32; nitems]).to_box()
-}
+    /// Create a new owning and mutable `n x n` identity matrix of type `dtype`.
+    pub(crate) fn new_eye(n: usize, dtype: DTYPE) -> Self {
+        let mut container = dispatch_dtype!(dtype, T => {
+            let mut data = vec![T::default(); n * n];
+            for index in 0..n {
+                data[index * (n + 1)] = T::cast_from(1_u8);
+            }
+            RustyDataContainer::from_vec(data)
+        });
+        container.set_contiguous_shape(&[n, n]);
+        container
+    }
 
-/// Create a new u64 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_u64(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as u64; nitems]).to_box()
-}
+    /// Convert the container into a shared container.
+    ///
+    /// The elements are copied in row-major order into an `Arc<[SharedWord]>` whose raw
+    /// pointer is stored as `data`, so that [RustyDataContainer::clone_ref] only needs to
+    /// increment the reference count. The shared data is contiguous with the shape of
+    /// the container and aligned for every dtype. Shared containers are not mutable.
+    pub fn into_shared(self) -> Self {
+        let nbytes = self.nitems * self.itemsize;
+        let mut words: Arc<[SharedWord]> =
+            Arc::from(vec![0; nbytes.div_ceil(std::mem::size_of::<SharedWord>())]);
+        let dst = Arc::get_mut(&mut words).unwrap().as_mut_ptr() as *mut u8;
+        for index in 0..self.nitems {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.element_ptr(index),
+                    dst.add(index * self.itemsize),
+                    self.itemsize,
+                )
+            };
+        }
+        let mut result = Self::new_1d(
+            Arc::into_raw(words) as *mut c_void,
+            self.nitems,
+            self.nitems,
+            self.dtype,
+            OWNERSHIP::Shared,
+            MUTABILITY::NotMutable,
+        );
+        result.set_contiguous_shape(&self.shape[..self.ndim]);
+        result.alignment = std::mem::align_of::<SharedWord>();
+        result.zeroize = self.zeroize;
+        result
+    }
 
-/// Create a new i8 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_i8(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as i8; nitems]).to_box()
-}
+    /// Create a new container referencing the same shared data.
+    ///
+    /// Panics if the container is not shared.
+    pub fn clone_ref(&self) -> Self {
+        assert_eq!(self.is_owner, OWNERSHIP::Shared);
+        unsafe { Arc::increment_strong_count(self.shared_words()) };
+        let mut result = self.view();
+        result.is_owner = OWNERSHIP::Shared;
+        result.zeroize = self.zeroize;
+        result
+    }
 
-/// Create a new i32 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_i32(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as i32; nitems]).to_box()
-}
+    /// The raw `Arc<[SharedWord]>` representation of shared data.
+    pub(crate) fn shared_words(&self) -> *const [SharedWord] {
+        let nbytes = self.capacity * self.itemsize;
+        std::ptr::slice_from_raw_parts(
+            self.data as *const SharedWord,
+            nbytes.div_ceil(std::mem::size_of::<SharedWord>()),
+        )
+    }
 
-/// Create a new i64 data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_i64(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as i64; nitems]).to_box()
-}
+    /// Create a new non-owning container referencing the same data.
+    ///
+    /// With the `safe-views` feature the view keeps its parent alive, see [crate::views].
+    pub(crate) fn view(&self) -> Self {
+        Self {
+            nitems: self.nitems,
+            itemsize: self.itemsize,
+            capacity: self.nitems,
+            dtype: self.dtype,
+            is_owner: OWNERSHIP::NotOwner,
+            is_mutable: self.is_mutable,
+            data: self.data,
+            ndim: self.ndim,
+            shape: self.shape,
+            strides: self.strides,
+            alignment: self.alignment,
+            borrowed: AtomicBool::new(false),
+            zeroize: false,
+            generation: 0,
+            parent: crate::views::retain(self),
+            lock: AtomicUsize::new(0),
+        }
+    }
 
-/// Create a new usize data container.
-#[no_mangle]
-pub extern "C" fn rusty_data_container_new_usize(nitems: size_t) -> Box<RustyDataContainer> {
-    RustyDataContainer::from_vec(vec![0 as usize; nitems]).to_box()
-}
+    /// Overwrite the whole allocation of an owning container with zeros.
+    ///
+    /// The writes are volatile, so that they are not optimized away when the
+    /// data is freed afterwards. Containers that do not own their data are unchanged.
+    pub fn wipe(&mut self) {
+        if self.is_owner != OWNERSHIP::Owner {
+            return;
+        }
+        unsafe { wipe_bytes(self.data as *mut u8, self.capacity * self.itemsize) };
+    }
 
-/// Get nitems
-#[no_mangle]
-pub extern "C" fn rusty_data_container_get_nitems(ptr: Option<Box<RustyDataContainer>>) -> size_t {
-    RustyDataContainer::leak(ptr).nitems
-}
+    /// The number of bytes of memory held by the container.
+    ///
+    /// This is the size of the struct, which includes the shape and stride arrays,
+    /// plus the full allocated capacity of owned data. Views hold no data. The data of
+    /// shared containers is counted for every reference to it.
+    pub fn memory_usage(&self) -> usize {
+        let data = match self.is_owner {
+            OWNERSHIP::Owner => self.capacity * self.itemsize,
+            OWNERSHIP::Shared => self.nitems * self.itemsize,
+            OWNERSHIP::NotOwner => 0,
+        };
+        std::mem::size_of::<Self>() + data
+    }
 
-/// Get itemsize
-#[no_mangle]
-pub extern "C" fn rusty_data_container_get_itemsize(
-    ptr: Option<Box<RustyDataContainer>>,
-) -> size_t {
-    RustyDataContainer::leak(ptr).itemsize
-}
+    /// The number of times the data was reallocated, e.g. by [RustyDataContainer::resize].
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
 
-/// Get dtype
-#[no_mangle]
-pub extern "C" fn rusty_data_container_get_dtype(ptr: Option<Box<RustyDataContainer>>) -> DTYPE {
-    RustyDataContainer::leak(ptr).dtype
-}
+    /// Whether a C caller currently holds exclusive mutable access.
+    pub fn is_borrowed(&self) -> bool {
+        self.borrowed.load(Ordering::Acquire)
+    }
 
-/// Get is_owner
-#[no_mangle]
-pub extern "C" fn rusty_data_container_get_is_owner(
-    ptr: Option<Box<RustyDataContainer>>,
-) -> OWNERSHIP {
-    RustyDataContainer::leak(ptr).is_owner
-}
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.nitems
+    }
 
-/// Get is_mutable
-#[no_mangle]
-pub extern "C" fn rusty_data_container_get_is_mutable(
-    ptr: Option<Box<RustyDataContainer>>,
-) -> MUTABILITY {
-    RustyDataContainer::leak(ptr).is_mutable
-}
+    /// Return true if the container has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.nitems == 0
+    }
 
-/// Get data
-#[no_mangle]
-pub extern "C" fn rusty_data_container_get_data(
-    ptr: Option<Box<RustyDataContainer>>,
-) -> *mut c_void {
-    RustyDataContainer::leak(ptr).data
-}
+    /// Return true if the data is stored contiguously in row-major order.
+    pub fn is_contiguous(&self) -> bool {
+        let mut expected = 1;
+        for axis in (0..self.ndim).rev() {
+            if self.shape[axis] != 1 && self.strides[axis] != expected {
+                return false;
+            }
+            expected *= self.shape[axis] as isize;
+        }
+        true
+    }
 
-#[no_mangle]
-pub extern "C" fn new_from_pointer(
-    ptr: *mut c_void,
-    nitems: size_t,
-    dtype: DTYPE,
-    is_mutable: MUTABILITY,
-) -> Box<RustyDataContainer> {
-    RustyDataContainer {
-        nitems,
-        capacity: nitems,
-        itemsize: get_itemsize(dtype) as size_t,
-        dtype,
-        is_owner: OWNERSHIP::NotOwner,
-        is_mutable,
-        data: ptr,
+    /// The offset in elements from `data` of the element with row-major index `index`.
+    pub(crate) fn element_offset(&self, mut index: usize) -> isize {
+        let mut offset = 0;
+        for axis in (0..self.ndim).rev() {
+            offset += (index % self.shape[axis]) as isize * self.strides[axis];
+            index /= self.shape[axis];
+        }
+        offset
+    }
+
+    /// The address range `[start, end)` of the memory spanned by the elements.
+    pub(crate) fn address_range(&self) -> (usize, usize) {
+        let start = self.data as usize;
+        if self.nitems == 0 {
+            return (start, start);
+        }
+        let (mut low, mut high) = (0, 0);
+        for axis in 0..self.ndim {
+            let extent = (self.shape[axis] as isize - 1) * self.strides[axis];
+            if extent < 0 {
+                low += extent;
+            } else {
+                high += extent;
+            }
+        }
+        let itemsize = self.itemsize as isize;
+        (
+            (start as isize + low * itemsize) as usize,
+            (start as isize + (high + 1) * itemsize) as usize,
+        )
+    }
+
+    /// Return true if the memory spanned by the two containers overlaps in at least one byte.
+    pub fn aliases(&self, other: &RustyDataContainer) -> bool {
+        let (start, end) = self.address_range();
+        let (other_start, other_end) = other.address_range();
+        start < end && other_start < other_end && start < other_end && other_start < end
+    }
+
+    /// Create a non-owning view with the order of the elements reversed along every axis.
+    ///
+    /// The strides of the view are negated and its data pointer is moved to the
+    /// last element of this container.
+    pub fn reverse_view(&self) -> Self {
+        let mut view = self.view();
+        if self.nitems == 0 {
+            return view;
+        }
+        let offset = self.element_offset(self.nitems - 1);
+        view.data = unsafe { (self.data as *mut u8).offset(offset * self.itemsize as isize) }
+            as *mut c_void;
+        for axis in 0..self.ndim {
+            view.strides[axis] = -self.strides[axis];
+        }
+        view
+    }
+
+    /// Create a non-owning strided view of the elements `start, start + step, ...` before `stop`.
+    ///
+    /// This corresponds to `a[start:stop:step]` in Python. Returns `None` if the
+    /// container is not one-dimensional, if `step` is zero or if `start <= stop <= len`
+    /// does not hold.
+    pub fn slice_step(&self, start: usize, stop: usize, step: usize) -> Option<Self> {
+        if self.ndim != 1 || step == 0 || start > stop || stop > self.nitems {
+            return None;
+        }
+        let mut view = self.view();
+        let nitems = (stop - start).div_ceil(step);
+        if nitems > 0 {
+            view.data = self.element_ptr(start) as *mut c_void;
+        }
+        view.nitems = nitems;
+        view.capacity = nitems;
+        view.shape[0] = nitems;
+        view.strides[0] = self.strides[0] * step as isize;
+        Some(view)
+    }
+
+    /// Change the shape of a contiguous container in place without moving the data.
+    ///
+    /// The data is interpreted in row-major order with the new shape, like numpy's
+    /// `reshape` of a contiguous array. Returns [ContainerError::InvalidShape] if the
+    /// shape is empty, has more than [MAX_NDIM] dimensions or does not preserve the
+    /// number of items, and [ContainerError::NotContiguous] if the container is not
+    /// contiguous. The container is left unchanged on error.
+    pub fn reshape(&mut self, shape: &[usize]) -> Result<(), ContainerError> {
+        if shape.is_empty()
+            || shape.len() > MAX_NDIM
+            || shape
+                .iter()
+                .try_fold(1_usize, |acc, &extent| acc.checked_mul(extent))
+                != Some(self.nitems)
+        {
+            return Err(ContainerError::InvalidShape);
+        }
+        if !self.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        self.set_contiguous_shape(shape);
+        Ok(())
+    }
+
+    /// Create a non-owning one-dimensional view of row `index` of a two-dimensional container.
+    ///
+    /// The view uses the column stride of the container, so that rows of column-major
+    /// and transposed containers work as well. Returns `None` if the container is not
+    /// two-dimensional or if `index` is out of range.
+    pub fn row(&self, index: usize) -> Option<Self> {
+        if self.ndim != 2 || index >= self.shape[0] {
+            return None;
+        }
+        let mut view = self.view();
+        let offset = index as isize * self.strides[0] * self.itemsize as isize;
+        view.data = (self.data as *mut u8).wrapping_offset(offset) as *mut c_void;
+        view.nitems = self.shape[1];
+        view.capacity = self.shape[1];
+        view.ndim = 1;
+        view.shape = [0; MAX_NDIM];
+        view.strides = [0; MAX_NDIM];
+        view.shape[0] = self.shape[1];
+        view.strides[0] = self.strides[1];
+        Some(view)
+    }
+
+    /// Iterate over the rows of a two-dimensional container as non-owning views.
+    ///
+    /// Panics if the container is not two-dimensional. See [RustyDataContainer::row].
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = RustyDataContainer> + '_ {
+        assert_eq!(self.ndim, 2, "rows requires a two-dimensional container");
+        (0..self.shape[0]).map(|index| self.row(index).unwrap())
+    }
+
+    /// Move the start of a one-dimensional non-owning container forward by `n` elements.
+    ///
+    /// Returns false without changing the container if it owns its data,
+    /// is not one-dimensional or has fewer than `n` elements.
+    pub fn advance(&mut self, n: usize) -> bool {
+        if self.is_owner != OWNERSHIP::NotOwner || self.ndim != 1 || n > self.nitems {
+            return false;
+        }
+        // Advancing past the last element of a reversed view points before the buffer,
+        // so the pointer arithmetic must not assume that the result stays in bounds.
+        let offset = n as isize * self.strides[0] * self.itemsize as isize;
+        self.data = (self.data as *mut u8).wrapping_offset(offset) as *mut c_void;
+        self.nitems -= n;
+        self.shape[0] -= n;
+        self.capacity = self.capacity.saturating_sub(n);
+        true
+    }
+
+    /// Whether the data is contiguous and starts at a multiple of `alignment` bytes.
+    ///
+    /// For example, Apache Arrow buffers should be aligned to 64 bytes for zero-copy
+    /// sharing.
+    pub fn is_aligned_to(&self, alignment: usize) -> bool {
+        alignment.is_power_of_two()
+            && self.is_contiguous()
+            && (self.data as usize).is_multiple_of(alignment)
+    }
+
+    /// Create a new owning contiguous copy of the data whose allocation is aligned to
+    /// `alignment` bytes.
+    ///
+    /// Returns [ContainerError::InvalidAlignment] if `alignment` is not a power of two
+    /// or smaller than the natural alignment of the dtype.
+    pub fn to_aligned(&self, alignment: usize) -> Result<Self, ContainerError> {
+        let shape = &self.shape[..self.ndim];
+        let result = crate::ContainerBuilder::new()
+            .shape(if shape.is_empty() { &[0] } else { shape })
+            .dtype(self.dtype)
+            .aligned(alignment)
+            .build()?;
+        let dst = result.data as *mut u8;
+        for index in 0..self.nitems {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.element_ptr(index),
+                    dst.add(index * self.itemsize),
+                    self.itemsize,
+                );
+            }
+        }
+        Ok(result)
+    }
+
+    /// Create a new owning contiguous copy of the data in row-major order.
+    pub fn to_contiguous(&self) -> Self {
+        let mut result = RustyDataContainer::new_zeroed(self.nitems, self.dtype);
+        let src = self.data as *const u8;
+        let dst = result.data as *mut u8;
+        for index in 0..self.nitems {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    src.offset(self.element_offset(index) * self.itemsize as isize),
+                    dst.add(index * self.itemsize),
+                    self.itemsize,
+                );
+            }
+        }
+        result.set_contiguous_shape(&self.shape[..self.ndim]);
+        result
+    }
+
+    /// Move the data into a new container and leave an empty owning container behind.
+    ///
+    /// The returned container has the data, shape and ownership of `self`. Afterwards
+    /// `self` is an empty, mutable, owning container of the same dtype whose data pointer
+    /// is dangling but well aligned, as for an empty Vec, and whose generation is bumped.
+    pub fn take(&mut self) -> Self {
+        let mut taken = Self::new_1d(
+            self.alignment as *mut c_void,
+            0,
+            0,
+            self.dtype,
+            OWNERSHIP::Owner,
+            MUTABILITY::Mutable,
+        );
+        taken.alignment = self.alignment;
+        taken.zeroize = self.zeroize;
+        taken.generation = self.generation + 1;
+        std::mem::swap(self, &mut taken);
+        taken
+    }
+
+    /// Reinterpret the data of an owning contiguous container as elements of `dtype` without copying.
+    ///
+    /// The container becomes one-dimensional. Both the number of bytes of the data and
+    /// of the allocated capacity must be divisible by the itemsize of `dtype`, and the
+    /// allocation must be aligned for `dtype`. Bool is rejected since not every byte is a
+    /// valid bool. The container is left unchanged on error.
+    pub fn reinterpret_as(&mut self, dtype: DTYPE) -> Result<(), ContainerError> {
+        if self.is_owner != OWNERSHIP::Owner {
+            return Err(ContainerError::NotOwner);
+        }
+        if !self.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        if dtype == DTYPE::Bool && self.dtype != DTYPE::Bool {
+            return Err(ContainerError::DtypeMismatch);
+        }
+        let itemsize = get_itemsize(dtype);
+        let nbytes = self.nitems * self.itemsize;
+        let capacity_bytes = self.capacity * self.itemsize;
+        if !nbytes.is_multiple_of(itemsize) || !capacity_bytes.is_multiple_of(itemsize) {
+            return Err(ContainerError::InvalidShape);
+        }
+        if self.alignment < get_alignment(dtype) {
+            return Err(ContainerError::InvalidAlignment);
+        }
+        self.dtype = dtype;
+        self.itemsize = itemsize;
+        self.nitems = nbytes / itemsize;
+        self.capacity = capacity_bytes / itemsize;
+        self.set_contiguous_shape(&[self.nitems]);
+        Ok(())
+    }
+
+    /// Change the number of elements of an owning one-dimensional container.
+    ///
+    /// The data is always moved into a new allocation with the same alignment, see
+    /// [RustyDataContainer::grow_inplace] to keep the pointer if possible. Existing
+    /// elements are kept and new elements are set to [DTYPE::zero_bytes]. Fails with
+    /// [ContainerError::HasViews] while views of the container are alive.
+    pub fn resize(&mut self, nitems: usize) -> Result<(), ContainerError> {
+        if self.is_owner != OWNERSHIP::Owner {
+            return Err(ContainerError::NotOwner);
+        }
+        crate::views::ensure_no_views(self)?;
+        if self.ndim != 1 {
+            return Err(ContainerError::InvalidShape);
+        }
+        if !self.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        let size = nitems
+            .checked_mul(self.itemsize)
+            .ok_or(ContainerError::InvalidShape)?;
+        let layout = Layout::from_size_align(size, self.alignment)
+            .map_err(|_| ContainerError::InvalidShape)?;
+        let data = if size == 0 {
+            // A dangling but well aligned pointer, as for an empty Vec.
+            self.alignment as *mut u8
+        } else {
+            unsafe { crate::canary::alloc(layout, false) }
+        };
+        if data.is_null() {
+            return Err(ContainerError::AllocationFailed);
+        }
+        let kept = self.nitems.min(nitems) * self.itemsize;
+        unsafe { std::ptr::copy_nonoverlapping(self.data as *const u8, data, kept) };
+        let zero = self.dtype.zero_bytes();
+        for offset in (kept..size).step_by(self.itemsize) {
+            unsafe {
+                std::ptr::copy_nonoverlapping(zero.as_ptr(), data.add(offset), self.itemsize)
+            };
+        }
+        let old_size = self.capacity * self.itemsize;
+        if self.zeroize {
+            self.wipe();
+        }
+        if old_size > 0 {
+            let old_layout = Layout::from_size_align(old_size, self.alignment).unwrap();
+            unsafe { crate::canary::dealloc(self.data as *mut u8, old_layout) };
+        }
+        self.data = data as *mut c_void;
+        self.nitems = nitems;
+        self.capacity = nitems;
+        self.shape[0] = nitems;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Grow an owning one-dimensional container to `nitems` elements, keeping the data
+    /// pointer if possible.
+    ///
+    /// If the capacity suffices the data is not moved. Otherwise the allocation is grown
+    /// with `realloc`, which often extends it in place, but the allocator gives no
+    /// guarantee. This applies equally to containers backed by a Vec and to containers with
+    /// a custom alignment from [crate::ContainerBuilder]. New elements are set to
+    /// [DTYPE::zero_bytes]. Returns whether the data pointer changed. If the container wipes
+    /// its data, see [RustyDataContainer::wipe], a grown allocation is always new and the
+    /// old one is wiped before it is freed.
+    pub fn grow_inplace(&mut self, nitems: usize) -> Result<bool, ContainerError> {
+        if self.is_owner != OWNERSHIP::Owner {
+            return Err(ContainerError::NotOwner);
+        }
+        crate::views::ensure_no_views(self)?;
+        if self.ndim != 1 || nitems < self.nitems {
+            return Err(ContainerError::InvalidShape);
+        }
+        if !self.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        let old_data = self.data;
+        if nitems > self.capacity {
+            let size = nitems
+                .checked_mul(self.itemsize)
+                .ok_or(ContainerError::InvalidShape)?;
+            let layout = Layout::from_size_align(size, self.alignment)
+                .map_err(|_| ContainerError::InvalidShape)?;
+            let old_size = self.capacity * self.itemsize;
+            let data = if old_size == 0 {
+                unsafe { crate::canary::alloc(layout, false) }
+            } else if self.zeroize {
+                // realloc could free the old block unwiped, so move the data explicitly.
+                let data = unsafe { crate::canary::alloc(layout, false) };
+                if !data.is_null() {
+                    let old_layout = Layout::from_size_align(old_size, self.alignment).unwrap();
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(
+                            self.data as *const u8,
+                            data,
+                            self.nitems * self.itemsize,
+                        );
+                        self.wipe();
+                        crate::canary::dealloc(self.data as *mut u8, old_layout);
+                    }
+                }
+                data
+            } else {
+                let old_layout = Layout::from_size_align(old_size, self.alignment).unwrap();
+                unsafe { crate::canary::realloc(self.data as *mut u8, old_layout, size) }
+            };
+            if data.is_null() {
+                return Err(ContainerError::AllocationFailed);
+            }
+            self.data = data as *mut c_void;
+            self.capacity = nitems;
+            self.generation += 1;
+        }
+        let zero = self.dtype.zero_bytes();
+        let data = self.data as *mut u8;
+        for index in self.nitems..nitems {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    zero.as_ptr(),
+                    data.add(index * self.itemsize),
+                    self.itemsize,
+                )
+            };
+        }
+        self.nitems = nitems;
+        self.shape[0] = nitems;
+        Ok(self.data != old_data)
+    }
+
+    /// Set the shape and the matching row-major strides of the data.
+    /// Panics if the shape does not match the number of items.
+    pub(crate) fn set_contiguous_shape(&mut self, shape: &[usize]) {
+        assert!(!shape.is_empty() && shape.len() <= MAX_NDIM);
+        assert_eq!(shape.iter().product::<usize>(), self.nitems);
+        self.ndim = shape.len();
+        self.shape = [0; MAX_NDIM];
+        self.strides = [0; MAX_NDIM];
+        self.shape[..self.ndim].copy_from_slice(shape);
+        let mut stride = 1;
+        for axis in (0..self.ndim).rev() {
+            self.strides[axis] = stride;
+            stride *= shape[axis] as isize;
+        }
+    }
+
+    /// Pointer to the first byte of the element with row-major index `index`.
+    pub(crate) fn element_ptr(&self, index: usize) -> *mut u8 {
+        assert!(index < self.nitems);
+        unsafe {
+            (self.data as *mut u8).offset(self.element_offset(index) * self.itemsize as isize)
+        }
+    }
+
+    /// The bytes of the element with row-major index `index`.
+    pub(crate) fn element_bytes(&self, index: usize) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.element_ptr(index), self.itemsize) }
+    }
+
+    /// A stable 128 bit fingerprint of dtype, shape and data.
+    ///
+    /// The fingerprint is a hash of the dtype code, the number of dimensions and the
+    /// shape (each as little endian integers), followed by the bytes of all elements in
+    /// row-major order. With the `blake3` feature it consists of the first 16 bytes of
+    /// the BLAKE3 hash, which is collision resistant. Otherwise it is the 128 bit
+    /// FNV-1a hash, which is suitable as a cache key for trusted data but is not a
+    /// cryptographic hash. The two variants give different fingerprints.
+    pub fn fingerprint(&self) -> [u8; 16] {
+        #[cfg(feature = "blake3")]
+        {
+            let mut hasher = blake3::Hasher::new();
+            self.fingerprint_input(|bytes| {
+                hasher.update(bytes);
+            });
+            hasher.finalize().as_bytes()[..16].try_into().unwrap()
+        }
+        #[cfg(not(feature = "blake3"))]
+        {
+            let mut state = crate::ChecksumState::new();
+            self.fingerprint_input(|bytes| state.update(bytes));
+            state.finalize()
+        }
+    }
+
+    /// Pass the canonical serialization of metadata and data hashed by
+    /// [RustyDataContainer::fingerprint] to `update`.
+    fn fingerprint_input(&self, mut update: impl FnMut(&[u8])) {
+        update(&self.dtype.discriminant().to_le_bytes());
+        update(&(self.ndim as u64).to_le_bytes());
+        for &extent in &self.shape[..self.ndim] {
+            update(&(extent as u64).to_le_bytes());
+        }
+        for index in 0..self.nitems {
+            update(self.element_bytes(index));
+        }
+    }
+
+    /// The element with row-major index `index`. Panics if `T` does not match the dtype.
+    pub(crate) fn element<T: ConversionType + Copy>(&self, index: usize) -> T {
+        assert_dtype::<T>(self.dtype);
+        assert!(index < self.nitems);
+        unsafe { *(self.data as *const T).offset(self.element_offset(index)) }
+    }
+
+    /// The element with row-major index `index`, or `None` if `T` does not match
+    /// the dtype or the index is out of range.
+    pub fn get<T: ConversionType + Copy>(&self, index: usize) -> Option<T> {
+        if crate::ensure_dtype::<T>(self.dtype).is_err() || index >= self.nitems {
+            return None;
+        }
+        Some(self.element(index))
+    }
+
+    /// Iterate over the elements in row-major order. Panics if `T` does not match the dtype.
+    pub(crate) fn values<T: ConversionType + Copy>(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + '_ {
+        assert_dtype::<T>(self.dtype);
+        (0..self.nitems).map(|index| self.element(index))
+    }
+
+    /// The data as raw bytes.
+    pub(crate) fn bytes(&self) -> &[u8] {
+        assert!(self.is_contiguous());
+        if self.nitems == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.data as *const u8, self.nitems * self.itemsize) }
+    }
+
+    /// The data as mutable raw bytes. Panics if the container is not mutable.
+    pub(crate) fn bytes_mut(&mut self) -> &mut [u8] {
+        assert_eq!(self.is_mutable, MUTABILITY::Mutable);
+        assert!(self.is_contiguous());
+        if self.nitems == 0 {
+            return &mut [];
+        }
+        unsafe { std::slice::from_raw_parts_mut(self.data as *mut u8, self.nitems * self.itemsize) }
+    }
+
+    /// The bytes of all elements in row-major order, borrowed if the data is contiguous.
+    pub(crate) fn logical_bytes(&self) -> Cow<'_, [u8]> {
+        if self.is_contiguous() {
+            Cow::Borrowed(self.bytes())
+        } else {
+            Cow::Owned(
+                (0..self.nitems)
+                    .flat_map(|index| self.element_bytes(index))
+                    .copied()
+                    .collect(),
+            )
+        }
+    }
+
+    /// Compute the byte runs in which `new` differs from `old`.
+    ///
+    /// The bytes are compared in row-major order of the elements, so that strided
+    /// views can be compared as well. The patch is a sequence of runs, each consisting
+    /// of the byte offset and the run length as little endian `u64`, followed by the new
+    /// bytes of the run. Returns [ContainerError::DtypeMismatch] or
+    /// [ContainerError::InvalidShape] if the dtypes or the numbers of items differ.
+    pub fn diff(
+        old: &RustyDataContainer,
+        new: &RustyDataContainer,
+    ) -> Result<Vec<u8>, ContainerError> {
+        if old.dtype != new.dtype {
+            return Err(ContainerError::DtypeMismatch);
+        }
+        if old.nitems != new.nitems {
+            return Err(ContainerError::InvalidShape);
+        }
+        let old_bytes = old.logical_bytes();
+        let new_bytes = new.logical_bytes();
+        let mut patch = Vec::new();
+        let mut pos = 0;
+        while pos < new_bytes.len() {
+            if old_bytes[pos] == new_bytes[pos] {
+                pos += 1;
+                continue;
+            }
+            let start = pos;
+            while pos < new_bytes.len() && old_bytes[pos] != new_bytes[pos] {
+                pos += 1;
+            }
+            patch.extend_from_slice(&(start as u64).to_le_bytes());
+            patch.extend_from_slice(&((pos - start) as u64).to_le_bytes());
+            patch.extend_from_slice(&new_bytes[start..pos]);
+        }
+        Ok(patch)
+    }
+
+    /// Apply a patch created by [RustyDataContainer::diff] to a mutable container.
+    ///
+    /// Returns [ContainerError::NotMutable] if the container is not mutable and
+    /// [ContainerError::InvalidFormat] if the patch is malformed or does not fit the
+    /// container, in which case the container is unchanged.
+    pub fn apply_patch(&mut self, patch: &[u8]) -> Result<(), ContainerError> {
+        if self.is_mutable != MUTABILITY::Mutable {
+            return Err(ContainerError::NotMutable);
+        }
+        let runs = Self::patch_runs(patch, self.nitems * self.itemsize)
+            .ok_or(ContainerError::InvalidFormat)?;
+        for (start, bytes) in runs {
+            for (pos, &byte) in (start..).zip(bytes) {
+                let element = self.element_ptr(pos / self.itemsize);
+                unsafe { *element.add(pos % self.itemsize) = byte };
+            }
+        }
+        Ok(())
+    }
+
+    /// Split a patch into its runs of start offset and new bytes, or `None` if it is
+    /// malformed or a run does not fit into `nbytes` bytes.
+    fn patch_runs(mut patch: &[u8], nbytes: usize) -> Option<Vec<(usize, &[u8])>> {
+        let mut runs = Vec::new();
+        while !patch.is_empty() {
+            let (header, rest) = patch.split_at_checked(16)?;
+            let start =
+                usize::try_from(u64::from_le_bytes(header[..8].try_into().unwrap())).ok()?;
+            let len = usize::try_from(u64::from_le_bytes(header[8..].try_into().unwrap())).ok()?;
+            if start.checked_add(len)? > nbytes {
+                return None;
+            }
+            let (bytes, rest) = rest.split_at_checked(len)?;
+            runs.push((start, bytes));
+            patch = rest;
+        }
+        Some(runs)
+    }
+
+    /// Typed view of the data. Panics if `T` does not match the dtype.
+    pub(crate) fn typed<T: ConversionType>(&self) -> &[T] {
+        assert_dtype::<T>(self.dtype);
+        assert!(self.is_contiguous());
+        if self.nitems == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.data as *const T, self.nitems) }
+    }
+
+    /// Typed mutable view of the data. Panics if `T` does not match the dtype
+    /// or if the container is not mutable.
+    pub(crate) fn typed_mut<T: ConversionType>(&mut self) -> &mut [T] {
+        assert_dtype::<T>(self.dtype);
+        assert_eq!(self.is_mutable, MUTABILITY::Mutable);
+        assert!(self.is_contiguous());
+        if self.nitems == 0 {
+            return &mut [];
+        }
+        unsafe { std::slice::from_raw_parts_mut(self.data as *mut T, self.nitems) }
+    }
+
+    /// Get a representation of the data as slice.
+    /// This method does not take ownership of the container associated with `ptr`.
+    ///
+    /// # Safety
+    /// The data pointer must be valid for `nitems` elements of type `T` and
+    /// the returned slice must not outlive the container.
+    pub unsafe fn as_slice<T: ConversionType>(
+        ptr: Option<Box<RustyDataContainer>>,
+    ) -> &'static [T] {
+        let container = RustyDataContainer::leak(ptr);
+        assert_dtype::<T>(container.dtype);
+        assert!(container.is_contiguous());
+        assert!(
+            container.check_canary(),
+            "canary after the container data was overwritten"
+        );
+        std::slice::from_raw_parts::<'static, T>(container.data as *const T, container.nitems)
+    }
+
+    /// Call `f` with a representation of the data as slice.
+    ///
+    /// Unlike [RustyDataContainer::as_slice] the borrow of the data is
+    /// scoped to the closure and cannot outlive the call.
+    /// This method does not take ownership of the container associated with `ptr`.
+    /// Returns [ContainerError::DtypeMismatch] if `T` does not match the dtype and
+    /// [ContainerError::NotContiguous] for strided views, without calling `f`.
+    pub fn with_slice<T: ConversionType, R>(
+        ptr: Option<Box<RustyDataContainer>>,
+        f: impl FnOnce(&[T]) -> R,
+    ) -> Result<R, ContainerError> {
+        let container = RustyDataContainer::leak(ptr);
+        crate::ensure_dtype::<T>(container.dtype)?;
+        if !container.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        Ok(f(container.typed::<T>()))
+    }
+
+    /// Iterate over the elements in row-major order.
+    /// This method does not take ownership of the container associated with `ptr`.
+    pub fn iter<T: ConversionType + Copy>(
+        ptr: Option<Box<RustyDataContainer>>,
+    ) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator {
+        RustyDataContainer::leak(ptr).values()
+    }
+
+    /// Iterate over the elements in reverse row-major order.
+    /// This method does not take ownership of the container associated with `ptr`.
+    pub fn iter_rev<T: ConversionType + Copy>(
+        ptr: Option<Box<RustyDataContainer>>,
+    ) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator {
+        RustyDataContainer::leak(ptr).values().rev()
+    }
+
+    /// Get a representation of the data as mutable slice.
+    /// This method does not take ownership of the container associated with `ptr`.
+    ///
+    /// # Safety
+    /// The data pointer must be valid for `nitems` elements of type `T`, no other
+    /// reference to the data may exist, and the returned slice must not outlive the container.
+    pub unsafe fn as_slice_mut<T: ConversionType>(
+        ptr: Option<Box<RustyDataContainer>>,
+    ) -> &'static mut [T] {
+        let container = RustyDataContainer::leak_mut(ptr);
+        assert_eq!(container.is_mutable, MUTABILITY::Mutable);
+        assert!(container.is_contiguous());
+        std::slice::from_raw_parts_mut::<'static, T>(container.data as *mut T, container.nitems)
+    }
+}
+
+impl Clone for RustyDataContainer {
+    /// Clone a container.
+    ///
+    /// An owner is cloned into a new owner with an independent copy of the data.
+    /// A shared container is cloned into a new reference to the same data, and a
+    /// non-owning container is cloned into a new non-owning container of the same data.
+    fn clone(&self) -> Self {
+        match self.is_owner {
+            OWNERSHIP::Owner => self.to_contiguous(),
+            OWNERSHIP::Shared => self.clone_ref(),
+            OWNERSHIP::NotOwner => self.view(),
+        }
+    }
+}
+
+impl PartialEq for RustyDataContainer {
+    /// Two containers are equal if they have the same dtype, the same number
+    /// of items and bitwise identical elements. In particular, floating point
+    /// NaNs with identical bit patterns compare equal, whereas `0.0` and `-0.0` do not.
+    fn eq(&self, other: &Self) -> bool {
+        self.dtype == other.dtype
+            && self.nitems == other.nitems
+            && (0..self.nitems).all(|index| self.element_bytes(index) == other.element_bytes(index))
+    }
+}
+
+impl Eq for RustyDataContainer {}
+
+impl Hash for RustyDataContainer {
+    /// Hash dtype, number of items and the bytes of all elements,
+    /// consistent with the bitwise equality of [PartialEq].
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dtype.hash(state);
+        self.nitems.hash(state);
+        for index in 0..self.nitems {
+            state.write(self.element_bytes(index));
+        }
+    }
+}
+
+impl Drop for RustyDataContainer {
+    /// Destroy a data container. If the container owns the
+    /// data the corresponding memory is also deallocated.
+    fn drop(&mut self) {
+        LIVE_CONTAINERS
+            .lock()
+            .unwrap()
+            .remove(&(self as *const RustyDataContainer as usize));
+        match self.is_owner {
+            OWNERSHIP::Owner => {
+                let size = self.capacity * self.itemsize;
+                if self.zeroize {
+                    self.wipe();
+                }
+                if size > 0 {
+                    let layout = Layout::from_size_align(size, self.alignment).unwrap();
+                    unsafe { crate::canary::dealloc(self.data as *mut u8, layout) };
+                }
+            }
+            OWNERSHIP::Shared => {
+                let mut words = unsafe { Arc::from_raw(self.shared_words()) };
+                // Only the last reference wipes the shared data.
+                if let Some(words) = Arc::get_mut(&mut words).filter(|_| self.zeroize) {
+                    unsafe {
+                        wipe_bytes(words.as_mut_ptr() as *mut u8, std::mem::size_of_val(words))
+                    };
+                }
+            }
+            OWNERSHIP::NotOwner => (),
+        }
+        crate::views::release(self.parent);
+    }
+}
+
+/// A shared borrow of a container that is tied to the lifetime of its handle.
+///
+/// Created by [RustyDataContainer::borrow]. The borrow cannot outlive the handle:
+///
+/// ```compile_fail
+/// use rusty_cffi::{ContainerRef, RustyDataContainer};
+///
+/// let borrowed: ContainerRef;
+/// {
+///     let handle = Some(RustyDataContainer::from_vec(vec![1.0_f64]).to_box());
+///     borrowed = RustyDataContainer::borrow(&handle);
+/// }
+/// assert_eq!(borrowed.len(), 1);
+/// ```
+pub struct ContainerRef<'a> {
+    container: &'a RustyDataContainer,
+}
+
+impl Deref for ContainerRef<'_> {
+    type Target = RustyDataContainer;
+
+    fn deref(&self) -> &RustyDataContainer {
+        self.container
+    }
+}
+
+/// A mutable borrow of a container that is tied to the lifetime of its handle.
+///
+/// Created by [RustyDataContainer::borrow_mut]. The borrow cannot outlive the handle:
+///
+/// ```compile_fail
+/// use rusty_cffi::{ContainerMut, RustyDataContainer};
+///
+/// let borrowed: ContainerMut;
+/// {
+///     let mut handle = Some(RustyDataContainer::from_vec(vec![1.0_f64]).to_box());
+///     borrowed = RustyDataContainer::borrow_mut(&mut handle);
+/// }
+/// assert_eq!(borrowed.len(), 1);
+/// ```
+pub struct ContainerMut<'a> {
+    container: &'a mut RustyDataContainer,
+}
+
+impl Deref for ContainerMut<'_> {
+    type Target = RustyDataContainer;
+
+    fn deref(&self) -> &RustyDataContainer {
+        self.container
+    }
+}
+
+impl DerefMut for ContainerMut<'_> {
+    fn deref_mut(&mut self) -> &mut RustyDataContainer {
+        self.container
+    }
+}
+
+/// Destroy a data container.
+///
+/// Destroying a null pointer, or a container that was already destroyed,
+/// is a no-op. In the latter case a warning is printed to stderr. With the
+/// `safe-views` feature a container with live views is freed with its last view.
+///
+/// The double destroy is only detected as long as no new container was allocated
+/// at the same address, so it is a debugging aid and not a guarantee.
+///
+/// # Safety
+/// `ptr` must be null or a container pointer obtained from this library that was
+/// not destroyed before.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_destroy(ptr: *mut RustyDataContainer) {
+    if ptr.is_null() {
+        return;
+    }
+    if !LIVE_CONTAINERS.lock().unwrap().remove(&(ptr as usize)) {
+        eprintln!("rusty-cffi: ignoring destroy of already destroyed container {ptr:p}");
+        return;
+    }
+    if crate::views::defer_destroy(ptr) {
+        return;
+    }
+    drop(Box::from_raw(ptr));
+}
+
+/// Destroy a container and transfer its data to the caller.
+///
+/// The data is an allocation of `nitems * itemsize` bytes, see
+/// [RustyDataContainer::into_data], and must be freed with [rusty_free]. Returns null
+/// and sets the last error if the container is not owning and contiguous, is borrowed
+/// by a C caller or has live views, in which case `ptr` stays valid. Also returns null for an empty
+/// container, which is destroyed.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_into_data(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *mut c_void {
+    // Leak first so that the container stays alive if it cannot be released.
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, false) else {
+        return std::ptr::null_mut();
+    };
+    let error = if container.is_owner != OWNERSHIP::Owner {
+        Some(ContainerError::NotOwner)
+    } else if !container.is_contiguous() {
+        Some(ContainerError::NotContiguous)
+    } else {
+        crate::views::ensure_no_views(container).err()
+    };
+    if let Some(error) = error {
+        crate::set_last_error(error);
+        return std::ptr::null_mut();
+    }
+    RustyDataContainer::from_box(unsafe { Box::from_raw(container) }).into_data()
+}
+
+/// Free data obtained from [rusty_data_container_into_data].
+///
+/// `nitems` and `itemsize` must be the number of items and the itemsize of the
+/// container the data was obtained from. Freeing null is a no-op.
+///
+/// # Safety
+/// `data` must be null or obtained from [rusty_data_container_into_data] with the
+/// given `nitems` and `itemsize`, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_free(data: *mut c_void, nitems: size_t, itemsize: size_t) {
+    let size = nitems * itemsize;
+    if data.is_null() || size == 0 {
+        return;
+    }
+    let layout = Layout::from_size_align(size, itemsize).unwrap();
+    crate::canary::dealloc(data as *mut u8, layout);
+}
+
+/// Create a new zero filled data container of the dtype with code `dtype`.
+///
+/// All numeric dtypes, including the complex dtypes, can be constructed this way.
+/// Returns null for Bool, whose zero value must be requested explicitly with
+/// [rusty_data_container_new_bool], for invalid dtype codes, if the size in bytes
+/// overflows, or if the allocation fails.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new(
+    nitems: size_t,
+    dtype: u32,
+) -> Option<Box<RustyDataContainer>> {
+    let dtype = DTYPE::from_discriminant(dtype).filter(|&dtype| dtype != DTYPE::Bool)?;
+    crate::ContainerBuilder::new()
+        .shape(&[nitems])
+        .dtype(dtype)
+        .build()
+        .ok()
+        .map(RustyDataContainer::to_box)
+}
+
+/// Create a new f32 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_f32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_f32; nitems]).to_box()
+}
+
+/// Create a new f64 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_f64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_f64; nitems]).to_box()
+}
+
+/// Create a new u8 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_u8(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_u8; nitems]).to_box()
+}
+
+/// Create a new u32 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_u32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_u32; nitems]).to_box()
+}
+
+/// Create a new u64 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_u64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_u64; nitems]).to_box()
+}
+
+/// Create a new i8 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_i8(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_i8; nitems]).to_box()
+}
+
+/// Create a new i32 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_i32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_i32; nitems]).to_box()
+}
+
+/// Create a new i64 data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_i64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_i64; nitems]).to_box()
+}
+
+/// Create a new usize data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_usize(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![0_usize; nitems]).to_box()
+}
+
+/// Create a new f32 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_f32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Float32).to_box()
+}
+
+/// Create a new f64 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_f64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Float64).to_box()
+}
+
+/// Create a new u8 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_u8(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Unsigned8).to_box()
+}
+
+/// Create a new u32 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_u32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Unsigned32).to_box()
+}
+
+/// Create a new u64 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_u64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Unsigned64).to_box()
+}
+
+/// Create a new i8 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_i8(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Int8).to_box()
+}
+
+/// Create a new i32 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_i32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Int32).to_box()
+}
+
+/// Create a new i64 data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_i64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Int64).to_box()
+}
+
+/// Create a new usize data container filled with ones.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ones_usize(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_ones(nitems, DTYPE::Usize).to_box()
+}
+
+/// Create a new f32 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_f32(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Float32).to_box()
+}
+
+/// Create a new f64 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_f64(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Float64).to_box()
+}
+
+/// Create a new u8 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_u8(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Unsigned8).to_box()
+}
+
+/// Create a new u32 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_u32(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Unsigned32).to_box()
+}
+
+/// Create a new u64 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_u64(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Unsigned64).to_box()
+}
+
+/// Create a new i8 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_i8(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Int8).to_box()
+}
+
+/// Create a new i32 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_i32(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Int32).to_box()
+}
+
+/// Create a new i64 `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_i64(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Int64).to_box()
+}
+
+/// Create a new usize `n x n` identity matrix.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_eye_usize(n: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_eye(n, DTYPE::Usize).to_box()
+}
+
+/// Create a new bool data container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_bool(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::from_vec(vec![false; nitems]).to_box()
+}
+
+/// Create a new complex data container with 32 bit float components.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_c32(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_zeroed(nitems, DTYPE::Complex32).to_box()
+}
+
+/// Create a new complex data container with 64 bit float components.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_c64(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_zeroed(nitems, DTYPE::Complex64).to_box()
+}
+
+/// Create a new f64 data container with the values `start, start + step, ...` excluding `stop`.
+///
+/// Returns null if `step` is zero or the number of values is not finite.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_arange_f64(
+    start: f64,
+    stop: f64,
+    step: f64,
+) -> Option<Box<RustyDataContainer>> {
+    RustyDataContainer::arange(start, stop, step, DTYPE::Float64)
+        .ok()
+        .map(RustyDataContainer::to_box)
+}
+
+/// Create a new f64 data container with `num` evenly spaced values from `start` to `stop`.
+///
+/// Both endpoints are included. Returns null if `num` is zero.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_linspace_f64(
+    start: f64,
+    stop: f64,
+    num: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    RustyDataContainer::linspace(start, stop, num, DTYPE::Float64)
+        .ok()
+        .map(RustyDataContainer::to_box)
+}
+
+/// Create a new 128 bit signed integer data container.
+///
+/// Each element is stored in native byte order, on little endian targets as the
+/// low 64 bit word followed by the high 64 bit word.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_i128(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_zeroed(nitems, DTYPE::Int128).to_box()
+}
+
+/// Create a new 128 bit unsigned integer data container.
+///
+/// Each element is stored in native byte order, on little endian targets as the
+/// low 64 bit word followed by the high 64 bit word.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_u128(nitems: size_t) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_zeroed(nitems, DTYPE::Unsigned128).to_box()
+}
+
+/// Acquire exclusive mutable access to a mutable container.
+///
+/// Returns false if the container is not mutable or is already borrowed.
+/// Until [rusty_data_container_release_mut] is called further borrows fail and
+/// all operations of this library that mutate the container fail and set the last
+/// error to [ContainerError::Borrowed], as do [rusty_data_container_get_data] and
+/// [rusty_data_container_as_f64_ptr_mut]. The borrower therefore fetches the data
+/// pointer before borrowing and writes through it until the release.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_borrow_mut(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    let container = RustyDataContainer::leak(ptr);
+    container.is_mutable == MUTABILITY::Mutable
+        && container
+            .borrowed
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+}
+
+/// Release exclusive mutable access acquired with [rusty_data_container_borrow_mut].
+///
+/// Returns false if the container was not borrowed.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_release_mut(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    RustyDataContainer::leak(ptr)
+        .borrowed
+        .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
+        .is_ok()
+}
+
+/// The state of the lock of a container that is locked for writing.
+pub const WRITE_LOCKED: usize = usize::MAX;
+
+/// Lock a container for reading, blocking while it is locked for writing.
+///
+/// Any number of threads can hold the read lock at the same time. The lock only
+/// coordinates C callers with each other, the operations of this library do not
+/// acquire it. Views have a lock of their own. Release it with
+/// [rusty_data_container_read_unlock].
+#[no_mangle]
+pub extern "C" fn rusty_data_container_read_lock(ptr: Option<Box<RustyDataContainer>>) {
+    let lock = &RustyDataContainer::leak(ptr).lock;
+    let mut state = lock.load(Ordering::Relaxed);
+    loop {
+        if state == WRITE_LOCKED {
+            std::thread::yield_now();
+            state = lock.load(Ordering::Relaxed);
+            continue;
+        }
+        match lock.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(current) => state = current,
+        }
+    }
+}
+
+/// Release a read lock acquired with [rusty_data_container_read_lock].
+///
+/// Returns false if the container was not locked for reading.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_read_unlock(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    RustyDataContainer::leak(ptr)
+        .lock
+        .fetch_update(Ordering::Release, Ordering::Relaxed, |state| {
+            (state != 0 && state != WRITE_LOCKED).then(|| state - 1)
+        })
+        .is_ok()
+}
+
+/// Lock a container for writing, blocking while it is locked for reading or writing.
+///
+/// The write lock is exclusive. As for [rusty_data_container_read_lock] only C
+/// callers that use the lock are coordinated. Release it with
+/// [rusty_data_container_write_unlock].
+#[no_mangle]
+pub extern "C" fn rusty_data_container_write_lock(ptr: Option<Box<RustyDataContainer>>) {
+    let lock = &RustyDataContainer::leak(ptr).lock;
+    while lock
+        .compare_exchange_weak(0, WRITE_LOCKED, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        std::thread::yield_now();
+    }
+}
+
+/// Release a write lock acquired with [rusty_data_container_write_lock].
+///
+/// Returns false if the container was not locked for writing.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_write_unlock(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    RustyDataContainer::leak(ptr)
+        .lock
+        .compare_exchange(WRITE_LOCKED, 0, Ordering::Release, Ordering::Relaxed)
+        .is_ok()
+}
+
+/// Change the number of elements of an owning one-dimensional container.
+///
+/// New elements are zero. The data pointer changes. Returns false if the
+/// container does not own its data, is not one-dimensional and contiguous,
+/// or if the allocation fails. Returns false and sets the last error if the
+/// container is not mutable, is borrowed by a C caller or has live views.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_resize(
+    ptr: Option<Box<RustyDataContainer>>,
+    nitems: size_t,
+) -> bool {
+    RustyDataContainer::try_leak_mut(ptr, true).is_some_and(|container| {
+        match container.resize(nitems) {
+            Ok(()) => true,
+            Err(ContainerError::HasViews) => {
+                crate::set_last_error(ContainerError::HasViews);
+                false
+            }
+            Err(_) => false,
+        }
+    })
+}
+
+/// Enable or disable overwriting the data with zeros when the container is destroyed.
+///
+/// See [RustyDataContainer::wipe]. This affects containers that own their data, whose
+/// old buffers are also wiped when [rusty_data_container_resize] or
+/// [rusty_data_container_grow_inplace] move the data, and shared containers, whose
+/// data is wiped when the last reference is destroyed. Views never wipe the data.
+/// Returns false and sets the last error if the container is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_set_zeroize(
+    ptr: Option<Box<RustyDataContainer>>,
+    enabled: bool,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, false) else {
+        return false;
+    };
+    container.zeroize = enabled;
+    true
+}
+
+/// Grow an owning one-dimensional container to `nitems` elements with `realloc`.
+///
+/// New elements are zero. Returns true if the data pointer changed, in which case
+/// previously obtained data pointers are invalid. On failure, for example if
+/// `nitems` is smaller than the current number of items, the container is unchanged
+/// and false is returned. Also returns false and sets the last error if the container
+/// is not mutable, is borrowed by a C caller or has live views.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_grow_inplace(
+    ptr: Option<Box<RustyDataContainer>>,
+    nitems: size_t,
+) -> bool {
+    RustyDataContainer::try_leak_mut(ptr, true).is_some_and(|container| {
+        match container.grow_inplace(nitems) {
+            Ok(moved) => moved,
+            Err(ContainerError::HasViews) => {
+                crate::set_last_error(ContainerError::HasViews);
+                false
+            }
+            Err(_) => false,
+        }
+    })
+}
+
+fn reinterpret_boxed(
+    ptr: Option<Box<RustyDataContainer>>,
+    source: Option<DTYPE>,
+    dtype: Option<DTYPE>,
+) -> Option<Box<RustyDataContainer>> {
+    // Leak first so that the container stays alive if the conversion fails.
+    let container = RustyDataContainer::try_leak_mut(ptr, false)?;
+    let dtype = dtype?;
+    let result = match source {
+        Some(source) if source != container.dtype => Err(ContainerError::DtypeMismatch),
+        _ => container.reinterpret_as(dtype),
+    };
+    result.map_err(crate::set_last_error).ok()?;
+    Some(unsafe { Box::from_raw(container) })
+}
+
+/// Reinterpret an owning contiguous container as a u8 container over the same allocation.
+///
+/// The returned handle replaces `ptr`. Returns null and sets the last error if the
+/// container is not owning and contiguous or is borrowed by a C caller, in which case
+/// `ptr` stays valid.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_take_bytes(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    reinterpret_boxed(ptr, None, Some(DTYPE::Unsigned8))
+}
+
+/// Reinterpret an owning contiguous u8 container as a container of `dtype` over the same allocation.
+///
+/// The returned handle replaces `ptr`. Returns null and sets the last error if the
+/// container is not an owning and contiguous u8 container, if the number of bytes is
+/// not divisible by the itemsize of `dtype`, if the data is not aligned for `dtype` or if
+/// the container is borrowed by a C caller. In that case `ptr` stays valid. Also returns
+/// null if `dtype` is not a valid dtype code.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_from_bytes_as(
+    ptr: Option<Box<RustyDataContainer>>,
+    dtype: u32,
+) -> Option<Box<RustyDataContainer>> {
+    reinterpret_boxed(ptr, Some(DTYPE::Unsigned8), DTYPE::from_discriminant(dtype))
+}
+
+/// Convert a container into a shared container.
+///
+/// `ptr` is consumed and must not be used afterwards. Returns null and sets the last
+/// error if the container is null, is borrowed by a C caller or has live views, in
+/// which case `ptr` stays valid.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_into_shared(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    // Leak first so that the container stays alive if it cannot be consumed.
+    let container = RustyDataContainer::try_leak_mut(ptr, false)?;
+    if let Err(error) = crate::views::ensure_no_views(container) {
+        crate::set_last_error(error);
+        return None;
+    }
+    Some(
+        RustyDataContainer::from_box(unsafe { Box::from_raw(container) })
+            .into_shared()
+            .to_box(),
+    )
+}
+
+/// Create a new reference to the data of a shared container.
+///
+/// Returns null and sets the last error if the container is not shared.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_clone_ref(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    let container = RustyDataContainer::leak(ptr);
+    if container.is_owner != OWNERSHIP::Shared {
+        crate::set_last_error(ContainerError::NotOwner);
+        return None;
+    }
+    Some(container.clone_ref().to_box())
+}
+
+/// Create a non-owning view with the order of the elements reversed.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_reverse_view(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Box<RustyDataContainer> {
+    RustyDataContainer::leak(ptr).reverse_view().to_box()
+}
+
+/// Create a non-owning strided view of the elements `start, start + step, ...` before `stop`.
+///
+/// Returns null if the container is not one-dimensional, if `step` is zero
+/// or if `start <= stop <= nitems` does not hold.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_slice_step(
+    ptr: Option<Box<RustyDataContainer>>,
+    start: size_t,
+    stop: size_t,
+    step: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    RustyDataContainer::leak(ptr)
+        .slice_step(start, stop, step)
+        .map(RustyDataContainer::to_box)
+}
+
+/// Change the shape of a contiguous container in place to the `new_ndim` extents at `new_shape`.
+///
+/// Returns false without changing the container if it is not contiguous or if the
+/// new shape does not have the same number of items. Returns false and sets the last
+/// error if the container is borrowed by a C caller.
+///
+/// # Safety
+/// `new_shape` must be valid for reading `new_ndim` values.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_reshape(
+    ptr: Option<Box<RustyDataContainer>>,
+    new_shape: *const size_t,
+    new_ndim: size_t,
+) -> bool {
+    let shape = if new_ndim == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(new_shape, new_ndim)
+    };
+    RustyDataContainer::try_leak_mut(ptr, false)
+        .is_some_and(|container| container.reshape(shape).is_ok())
+}
+
+/// Create a non-owning one-dimensional view of row `index` of a two-dimensional container.
+///
+/// Returns null if the container is not two-dimensional or if `index` is out of range.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_row(
+    ptr: Option<Box<RustyDataContainer>>,
+    index: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    RustyDataContainer::leak(ptr)
+        .row(index)
+        .map(RustyDataContainer::to_box)
+}
+
+/// Move the start of a one-dimensional non-owning container forward by `n` elements in place.
+///
+/// Returns false without changing the container if it owns its data,
+/// is not one-dimensional or has fewer than `n` elements. Returns false and
+/// sets the last error if the container is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_advance(
+    ptr: Option<Box<RustyDataContainer>>,
+    n: size_t,
+) -> bool {
+    RustyDataContainer::try_leak_mut(ptr, false).is_some_and(|container| container.advance(n))
+}
+
+/// Create a new owning contiguous copy of a container aligned to `alignment` bytes.
+///
+/// Use an alignment of 64 bytes for buffers shared with Apache Arrow. Returns null if
+/// `alignment` is not a power of two or smaller than the natural alignment of the dtype.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_to_aligned(
+    ptr: Option<Box<RustyDataContainer>>,
+    alignment: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    RustyDataContainer::leak(ptr)
+        .to_aligned(alignment)
+        .ok()
+        .map(RustyDataContainer::to_box)
+}
+
+/// Move the data of a container into a new container.
+///
+/// The original handle stays valid and becomes an empty owning container
+/// of the same dtype. Returns null and sets the last error if the container
+/// is not mutable, is borrowed by a C caller or has live views.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_take(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    let container = RustyDataContainer::try_leak_mut(ptr, true)?;
+    if let Err(error) = crate::views::ensure_no_views(container) {
+        crate::set_last_error(error);
+        return None;
+    }
+    Some(container.take().to_box())
+}
+
+/// Create a new owning contiguous copy of a container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_to_contiguous(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Box<RustyDataContainer> {
+    RustyDataContainer::leak(ptr).to_contiguous().to_box()
+}
+
+/// Get nitems
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_nitems(ptr: Option<Box<RustyDataContainer>>) -> size_t {
+    let ptr = ManuallyDrop::new(ptr);
+    RustyDataContainer::borrow(&ptr).nitems
+}
+
+/// Get itemsize
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_itemsize(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> size_t {
+    let ptr = ManuallyDrop::new(ptr);
+    RustyDataContainer::borrow(&ptr).itemsize
+}
+
+/// Get dtype
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_dtype(ptr: Option<Box<RustyDataContainer>>) -> DTYPE {
+    let ptr = ManuallyDrop::new(ptr);
+    RustyDataContainer::borrow(&ptr).dtype
+}
+
+/// Get is_owner
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_is_owner(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> OWNERSHIP {
+    let ptr = ManuallyDrop::new(ptr);
+    RustyDataContainer::borrow(&ptr).is_owner
+}
+
+/// Get is_mutable
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_is_mutable(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> MUTABILITY {
+    let ptr = ManuallyDrop::new(ptr);
+    RustyDataContainer::borrow(&ptr).is_mutable
+}
+
+/// Get data. Returns null if the container is not mutable. Returns null and sets the
+/// last error if the container is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_data(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *mut c_void {
+    let ptr = ManuallyDrop::new(ptr);
+    let container = RustyDataContainer::borrow(&ptr);
+    if container.is_borrowed() {
+        crate::set_last_error(ContainerError::Borrowed);
+        return std::ptr::null_mut();
+    }
+    if container.is_mutable != MUTABILITY::Mutable {
+        return std::ptr::null_mut();
+    }
+    container.data
+}
+
+/// Get data for read-only access
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_const_data(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *const c_void {
+    let ptr = ManuallyDrop::new(ptr);
+    RustyDataContainer::borrow(&ptr).data
+}
+
+/// Get data as `double` pointer. Returns null if the dtype is not [DTYPE::Float64].
+#[no_mangle]
+pub extern "C" fn rusty_data_container_as_f64_ptr(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *const c_double {
+    let ptr = ManuallyDrop::new(ptr);
+    let container = RustyDataContainer::borrow(&ptr);
+    if container.dtype != DTYPE::Float64 {
+        return std::ptr::null();
+    }
+    container.data as *const c_double
+}
+
+/// Get data as mutable `double` pointer. Returns null if the dtype is not
+/// [DTYPE::Float64] or if the container is not mutable. Returns null and sets the
+/// last error if the container is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_as_f64_ptr_mut(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *mut c_double {
+    let ptr = ManuallyDrop::new(ptr);
+    let container = RustyDataContainer::borrow(&ptr);
+    if container.is_borrowed() {
+        crate::set_last_error(ContainerError::Borrowed);
+        return std::ptr::null_mut();
+    }
+    if container.dtype != DTYPE::Float64 || container.is_mutable != MUTABILITY::Mutable {
+        return std::ptr::null_mut();
+    }
+    container.data as *mut c_double
+}
+
+/// Write the 16 byte fingerprint of a container to `out`.
+///
+/// # Safety
+/// `out` must be valid for writing 16 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_fingerprint(
+    ptr: Option<Box<RustyDataContainer>>,
+    out: *mut u8,
+) {
+    let fingerprint = RustyDataContainer::leak(ptr).fingerprint();
+    std::ptr::copy_nonoverlapping(fingerprint.as_ptr(), out, fingerprint.len());
+}
+
+/// Return true if the data of two containers overlaps in memory.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_aliases(
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> bool {
+    RustyDataContainer::leak(a).aliases(RustyDataContainer::leak(b))
+}
+
+/// Copy the data of a container in row-major order into the buffer `dst`.
+///
+/// Copies `min(dst_nbytes, nitems * itemsize)` bytes and returns the number of bytes copied.
+///
+/// # Safety
+/// `dst` must be valid for writing `dst_nbytes` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_copy_out(
+    ptr: Option<Box<RustyDataContainer>>,
+    dst: *mut c_void,
+    dst_nbytes: size_t,
+) -> size_t {
+    let container = RustyDataContainer::leak(ptr);
+    let nbytes = dst_nbytes.min(container.nitems * container.itemsize);
+    let dst = dst as *mut u8;
+    if nbytes == 0 {
+        return 0;
+    }
+    if container.is_contiguous() {
+        std::ptr::copy_nonoverlapping(container.data as *const u8, dst, nbytes);
+        return nbytes;
+    }
+    let mut copied = 0;
+    let mut index = 0;
+    while copied < nbytes {
+        let count = container.itemsize.min(nbytes - copied);
+        std::ptr::copy_nonoverlapping(container.element_ptr(index), dst.add(copied), count);
+        copied += count;
+        index += 1;
+    }
+    copied
+}
+
+/// Copy the elements of a container in row-major order to `dst + i * stride_bytes`.
+///
+/// This scatters the elements into an array of interleaved C structs, the bytes
+/// between the elements are left unchanged. Returns false without copying if
+/// `stride_bytes` is less than the itemsize.
+///
+/// # Safety
+/// `dst` must be valid for writing `itemsize` bytes at each of the `nitems` offsets.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_copy_out_strided(
+    ptr: Option<Box<RustyDataContainer>>,
+    dst: *mut c_void,
+    stride_bytes: size_t,
+) -> bool {
+    let container = RustyDataContainer::leak(ptr);
+    if stride_bytes < container.itemsize {
+        return false;
+    }
+    let dst = dst as *mut u8;
+    for index in 0..container.nitems {
+        std::ptr::copy_nonoverlapping(
+            container.element_ptr(index),
+            dst.add(index * stride_bytes),
+            container.itemsize,
+        );
+    }
+    true
+}
+
+/// The cache line size assumed for prefetching.
+const CACHE_LINE: usize = 64;
+
+/// Hint the processor to load the cache line containing `address` for reading.
+#[cfg(target_arch = "x86_64")]
+fn prefetch_line(address: *const u8) {
+    use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+    unsafe { _mm_prefetch::<_MM_HINT_T0>(address as *const i8) };
+}
+
+/// Hint the processor to load the cache line containing `address` for reading.
+#[cfg(target_arch = "aarch64")]
+fn prefetch_line(address: *const u8) {
+    unsafe {
+        std::arch::asm!("prfm pldl1keep, [{0}]", in(reg) address, options(nostack, readonly, preserves_flags))
+    };
+}
+
+/// Hint the processor to load the cache line containing `address` for reading.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn prefetch_line(_address: *const u8) {}
+
+/// Hint the processor to load the elements `offset..offset + len` in row-major order into the cache.
+///
+/// Prefetching does not change the data and cannot fault. It is a no-op on targets
+/// other than x86_64 and aarch64. Returns false if the range is out of bounds.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_prefetch(
+    ptr: Option<Box<RustyDataContainer>>,
+    offset: size_t,
+    len: size_t,
+) -> bool {
+    let container = RustyDataContainer::leak(ptr);
+    if offset
+        .checked_add(len)
+        .is_none_or(|end| end > container.nitems)
+    {
+        return false;
+    }
+    if len == 0 {
+        return true;
+    }
+    if container.is_contiguous() {
+        let start = container.element_ptr(offset);
+        for byte in (0..len * container.itemsize).step_by(CACHE_LINE) {
+            prefetch_line(start.wrapping_add(byte));
+        }
+        prefetch_line(start.wrapping_add(len * container.itemsize - 1));
+    } else {
+        let mut last_line = None;
+        for index in offset..offset + len {
+            let address = container.element_ptr(index);
+            let line = address as usize / CACHE_LINE;
+            if last_line != Some(line) {
+                prefetch_line(address);
+                last_line = Some(line);
+            }
+        }
+    }
+    true
+}
+
+/// Bit of [rusty_data_container_validate] for a null pointer.
+pub const RUSTY_INVALID_NULL: u32 = 1;
+/// Bit of [rusty_data_container_validate] for an unknown dtype code.
+pub const RUSTY_INVALID_DTYPE: u32 = 2;
+/// Bit of [rusty_data_container_validate] for more items than the capacity.
+pub const RUSTY_INVALID_CAPACITY: u32 = 4;
+/// Bit of [rusty_data_container_validate] for an itemsize that does not match the dtype.
+pub const RUSTY_INVALID_ITEMSIZE: u32 = 8;
+/// Bit of [rusty_data_container_validate] for null data with a nonzero number of items.
+pub const RUSTY_INVALID_DATA: u32 = 16;
+
+/// Check the invariants of a container and return a bitmask of the detected problems.
+///
+/// The result is a combination of the `RUSTY_INVALID_*` bits, and 0 if no problem
+/// was found. The checks only read the fields of the struct, so they can be used on
+/// handles that are suspected to be corrupted. A null pointer gives only
+/// [RUSTY_INVALID_NULL], and the itemsize is not checked for an unknown dtype.
+///
+/// # Safety
+/// `ptr` must be null or point to readable memory of the size of a container.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_validate(ptr: *const RustyDataContainer) -> u32 {
+    if ptr.is_null() {
+        return RUSTY_INVALID_NULL;
+    }
+    let mut problems = 0;
+    // Read the dtype as an integer, since an unknown code is not a valid DTYPE.
+    let code = std::ptr::addr_of!((*ptr).dtype).cast::<u32>().read();
+    let nitems = (*ptr).nitems;
+    match DTYPE::from_discriminant(code) {
+        Some(dtype) if (*ptr).itemsize != get_itemsize(dtype) => problems |= RUSTY_INVALID_ITEMSIZE,
+        Some(_) => {}
+        None => problems |= RUSTY_INVALID_DTYPE,
+    }
+    if nitems > (*ptr).capacity {
+        problems |= RUSTY_INVALID_CAPACITY;
+    }
+    if nitems > 0 && (*ptr).data.is_null() {
+        problems |= RUSTY_INVALID_DATA;
+    }
+    problems
+}
+
+/// Write the address of the first element to `begin` and the address one past the
+/// last element to `end`, so that C code can iterate with `p += itemsize`.
+///
+/// Both are set to null if the container is null or not contiguous.
+///
+/// # Safety
+/// `begin` and `end` must be valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_ptr_range(
+    ptr: Option<Box<RustyDataContainer>>,
+    begin: *mut *mut c_void,
+    end: *mut *mut c_void,
+) {
+    let range = ptr
+        .map(|ptr| RustyDataContainer::leak(Some(ptr)))
+        .filter(|container| container.is_contiguous())
+        .map(|container| {
+            let nbytes = container.nitems * container.itemsize;
+            (
+                container.data,
+                (container.data as *mut u8).add(nbytes) as *mut c_void,
+            )
+        });
+    let (first, last) = range.unwrap_or((std::ptr::null_mut(), std::ptr::null_mut()));
+    *begin = first;
+    *end = last;
+}
+
+/// Get the element with row-major index `index` of a f64 container.
+///
+/// Returns `default` if the container is null, is not a f64 container
+/// or if the index is out of range.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_f64_or(
+    ptr: Option<Box<RustyDataContainer>>,
+    index: size_t,
+    default: f64,
+) -> f64 {
+    ptr.map(|ptr| RustyDataContainer::leak(Some(ptr)))
+        .and_then(|container| container.get::<f64>(index))
+        .unwrap_or(default)
+}
+
+/// Get the element with row-major index `index` of a i64 container.
+///
+/// Returns `default` if the container is null, is not a i64 container
+/// or if the index is out of range.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_i64_or(
+    ptr: Option<Box<RustyDataContainer>>,
+    index: size_t,
+    default: i64,
+) -> i64 {
+    ptr.map(|ptr| RustyDataContainer::leak(Some(ptr)))
+        .and_then(|container| container.get::<i64>(index))
+        .unwrap_or(default)
+}
+
+/// Get the element with row-major index `index` of a u64 container.
+///
+/// Returns `default` if the container is null, is not a u64 container
+/// or if the index is out of range.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_u64_or(
+    ptr: Option<Box<RustyDataContainer>>,
+    index: size_t,
+    default: u64,
+) -> u64 {
+    ptr.map(|ptr| RustyDataContainer::leak(Some(ptr)))
+        .and_then(|container| container.get::<u64>(index))
+        .unwrap_or(default)
+}
+
+/// Get the number of bytes of memory held by a container, including unused capacity.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_memory_usage(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> size_t {
+    RustyDataContainer::leak(ptr).memory_usage()
+}
+
+/// Get the number of times the data of a container was reallocated.
+///
+/// C callers can cache the generation together with the data pointer and
+/// fetch the pointer again when the generation changes.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_generation(ptr: Option<Box<RustyDataContainer>>) -> u64 {
+    RustyDataContainer::leak(ptr).generation
+}
+
+/// Get ndim
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_ndim(ptr: Option<Box<RustyDataContainer>>) -> size_t {
+    RustyDataContainer::leak(ptr).ndim
+}
+
+/// Get shape
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_shape(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *const size_t {
+    RustyDataContainer::leak(ptr).shape.as_ptr()
+}
+
+/// Get strides (in units of elements)
+#[no_mangle]
+pub extern "C" fn rusty_data_container_get_strides(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> *const isize {
+    RustyDataContainer::leak(ptr).strides.as_ptr()
+}
+
+/// Create a new non-owning container of the dtype with code `dtype` from memory.
+///
+/// Returns null if `dtype` is not a valid dtype code.
+#[no_mangle]
+pub extern "C" fn new_from_pointer(
+    ptr: *mut c_void,
+    nitems: size_t,
+    dtype: u32,
+    is_mutable: MUTABILITY,
+) -> Option<Box<RustyDataContainer>> {
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    Some(
+        RustyDataContainer::new_1d(ptr, nitems, nitems, dtype, OWNERSHIP::NotOwner, is_mutable)
+            .to_box(),
+    )
+}
+
+/// Like [new_from_pointer], but checks the size of the foreign buffer.
+///
+/// `buffer_capacity_bytes` is the size of the buffer at `ptr` in bytes. Returns null if
+/// `nitems * itemsize` exceeds it or overflows, if `ptr` is null for a nonempty
+/// container, if `ptr` is not aligned for `dtype`, or if `dtype` is not a valid dtype code.
+#[no_mangle]
+pub extern "C" fn new_from_pointer_checked(
+    ptr: *mut c_void,
+    nitems: size_t,
+    dtype: u32,
+    is_mutable: MUTABILITY,
+    buffer_capacity_bytes: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    let nbytes = nitems.checked_mul(get_itemsize(dtype))?;
+    if nbytes > buffer_capacity_bytes
+        || (ptr.is_null() && nitems > 0)
+        || !(ptr as usize).is_multiple_of(get_alignment(dtype))
+    {
+        return None;
+    }
+    new_from_pointer(ptr, nitems, dtype.discriminant(), is_mutable)
+}
+
+/// Create a new non-owning two dimensional container from a matrix in memory.
+///
+/// The container has shape `[rows, cols]`. If `col_major` is true the data is
+/// interpreted in column-major (Fortran) order with strides `[1, rows]`,
+/// otherwise in row-major (C) order with strides `[cols, 1]`. Returns null if
+/// `dtype` is not a valid dtype code or if the size of the matrix in bytes overflows.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_from_matrix(
+    ptr: *mut c_void,
+    rows: size_t,
+    cols: size_t,
+    dtype: u32,
+    col_major: bool,
+    is_mutable: MUTABILITY,
+) -> Option<Box<RustyDataContainer>> {
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    let nitems = rows.checked_mul(cols)?;
+    if nitems > isize::MAX as usize / get_itemsize(dtype) {
+        return None;
+    }
+    let mut container =
+        RustyDataContainer::new_1d(ptr, nitems, nitems, dtype, OWNERSHIP::NotOwner, is_mutable);
+    container.ndim = 2;
+    container.shape[..2].copy_from_slice(&[rows, cols]);
+    if col_major {
+        container.strides[..2].copy_from_slice(&[1, rows as isize]);
+    } else {
+        container.strides[..2].copy_from_slice(&[cols as isize, 1]);
+    }
+    Some(container.to_box())
+}
+
+/// Create a new non-owning f64 container from the `data`, `size` and `stride` of a GSL vector.
+///
+/// Element `i` is `data[i * stride]`, as in `gsl_vector`. Returns null if `stride`
+/// is 0 or the strided extent of the vector overflows.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_from_gsl_vector(
+    data: *mut c_double,
+    size: size_t,
+    stride: size_t,
+    is_mutable: MUTABILITY,
+) -> Option<Box<RustyDataContainer>> {
+    let stride = isize::try_from(stride).ok().filter(|&stride| stride >= 1)?;
+    let extent = size.checked_mul(stride as usize)?;
+    if extent > isize::MAX as usize / get_itemsize(DTYPE::Float64) {
+        return None;
+    }
+    let mut container = RustyDataContainer::new_1d(
+        data as *mut c_void,
+        size,
+        size,
+        DTYPE::Float64,
+        OWNERSHIP::NotOwner,
+        is_mutable,
+    );
+    container.strides[0] = stride;
+    Some(container.to_box())
+}
+
+/// Extract one field of an array of C structs into a new owning container.
+///
+/// Element `i` of the new container is copied from `base + i * struct_size + field_offset`.
+/// Returns null if `dtype` is not a valid dtype code or if a field of type `dtype` at
+/// `field_offset` does not fit into the struct.
+///
+/// # Safety
+/// `base` must point to `count` consecutive structs of `struct_size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_from_aos(
+    base: *const c_void,
+    count: size_t,
+    struct_size: size_t,
+    field_offset: size_t,
+    dtype: u32,
+) -> Option<Box<RustyDataContainer>> {
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    let itemsize = get_itemsize(dtype);
+    if itemsize > struct_size || field_offset > struct_size - itemsize {
+        return None;
+    }
+    let container = RustyDataContainer::new_zeroed(count, dtype);
+    let src = base as *const u8;
+    let dst = container.data as *mut u8;
+    for index in 0..count {
+        std::ptr::copy_nonoverlapping(
+            src.add(index * struct_size + field_offset),
+            dst.add(index * itemsize),
+            itemsize,
+        );
+    }
+    Some(container.to_box())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    /// The number of containers referencing the data of a shared container.
+    fn shared_count(container: &RustyDataContainer) -> usize {
+        let words = unsafe { Arc::from_raw(container.shared_words()) };
+        let count = Arc::strong_count(&words);
+        let _ = Arc::into_raw(words);
+        count
+    }
+
+    #[test]
+    fn reverse_view_reads_backwards() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_i64, 2, 3]));
+        let view = rusty_data_container_reverse_view(handle(ptr));
+        assert_eq!(view.strides[0], -1);
+        assert_eq!(view.values::<i64>().collect::<Vec<_>>(), [3, 2, 1]);
+        assert_eq!(
+            view.reverse_view().values::<i64>().collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        drop(view);
+        destroy(ptr);
+
+        let mut matrix = RustyDataContainer::from_vec(vec![1_u8, 2, 3, 4, 5, 6]);
+        matrix.reshape(&[2, 3]).unwrap();
+        let reversed = matrix.reverse_view();
+        assert_eq!(
+            reversed.values::<u8>().collect::<Vec<_>>(),
+            [6, 5, 4, 3, 2, 1]
+        );
+        assert_eq!(reversed.to_contiguous().typed::<u8>(), [6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn clone_copies_owners_and_shares_views() {
+        let mut owner = RustyDataContainer::from_vec(vec![1_i32, 2, 3]);
+        let copy = owner.clone();
+        assert_eq!(copy.is_owner, OWNERSHIP::Owner);
+        assert_ne!(copy.data, owner.data);
+        owner.typed_mut::<i32>()[0] = 10;
+        assert_eq!(copy.typed::<i32>(), [1, 2, 3]);
+
+        let mut data = [1_i32, 2, 3];
+        let view = RustyDataContainer::from_slice_mut(&mut data);
+        let view_clone = view.clone();
+        assert_eq!(view_clone.is_owner, OWNERSHIP::NotOwner);
+        assert_eq!(view_clone.data, view.data);
+        drop(view);
+        drop(view_clone);
+        assert_eq!(data, [1, 2, 3]);
+    }
+
+    #[test]
+    // The atomic borrow and lock flags are not part of the hash.
+    #[allow(clippy::mutable_key_type)]
+    fn equal_containers_hash_equally() {
+        let mut set = std::collections::HashSet::new();
+        set.insert(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        set.insert(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        assert_eq!(set.len(), 1);
+        set.insert(RustyDataContainer::from_vec(vec![1_u32, 2, 3]));
+        assert_eq!(set.len(), 2);
+
+        let nan = RustyDataContainer::from_vec(vec![f64::NAN]);
+        assert!(nan == nan.clone());
+        assert!(
+            RustyDataContainer::from_vec(vec![0.0_f64])
+                != RustyDataContainer::from_vec(vec![-0.0_f64])
+        );
+    }
+
+    #[test]
+    fn column_major_matrix_view() {
+        // The 2 x 3 matrix [[1, 2, 3], [4, 5, 6]] in column-major order.
+        let mut data = [1.0_f64, 4.0, 2.0, 5.0, 3.0, 6.0];
+        let matrix = rusty_data_container_from_matrix(
+            data.as_mut_ptr() as *mut c_void,
+            2,
+            3,
+            DTYPE::Float64 as u32,
+            true,
+            MUTABILITY::Mutable,
+        )
+        .unwrap();
+        assert_eq!(&matrix.shape[..2], [2, 3]);
+        assert_eq!(&matrix.strides[..2], [1, 2]);
+        let offset = matrix.strides[0] + 2 * matrix.strides[1];
+        assert_eq!(unsafe { *(matrix.data as *const f64).offset(offset) }, 6.0);
+        assert_eq!(matrix.element::<f64>(5), 6.0);
+        assert_eq!(
+            matrix.row(1).unwrap().values::<f64>().collect::<Vec<_>>(),
+            [4.0, 5.0, 6.0]
+        );
+
+        let overflow = rusty_data_container_from_matrix(
+            data.as_mut_ptr() as *mut c_void,
+            usize::MAX,
+            2,
+            DTYPE::Float64 as u32,
+            false,
+            MUTABILITY::Mutable,
+        );
+        assert!(overflow.is_none());
+    }
+
+    #[test]
+    fn fingerprint_depends_on_data_and_shape() {
+        let a = RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 4.0]);
+        let b = RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 4.0]);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        let mut reshaped = b.clone();
+        reshaped.reshape(&[2, 2]).unwrap();
+        assert_ne!(a.fingerprint(), reshaped.fingerprint());
+        let changed = RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 5.0]);
+        assert_ne!(a.fingerprint(), changed.fingerprint());
+
+        let ptr = boxed(a);
+        let mut out = [0_u8; 16];
+        unsafe { rusty_data_container_fingerprint(handle(ptr), out.as_mut_ptr()) };
+        assert_eq!(out, b.fingerprint());
+        destroy(ptr);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn fingerprint_is_a_truncated_blake3_hash() {
+        let container = RustyDataContainer::from_vec(vec![7_u8]);
+        let mut input = Vec::new();
+        input.extend_from_slice(&DTYPE::Unsigned8.discriminant().to_le_bytes());
+        input.extend_from_slice(&1_u64.to_le_bytes());
+        input.extend_from_slice(&1_u64.to_le_bytes());
+        input.push(7);
+        assert_eq!(
+            container.fingerprint(),
+            blake3::hash(&input).as_bytes()[..16]
+        );
+    }
+
+    #[test]
+    fn with_slice_scopes_the_borrow() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_u32, 2, 3, 4]));
+        let sum = RustyDataContainer::with_slice(handle(ptr), |values: &[u32]| values.iter().sum());
+        assert_eq!(sum, Ok(10_u32));
+        let mismatch = RustyDataContainer::with_slice(handle(ptr), |values: &[i32]| values.len());
+        assert_eq!(mismatch, Err(ContainerError::DtypeMismatch));
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).reverse_view());
+        let strided = RustyDataContainer::with_slice(handle(view), |values: &[u32]| values.len());
+        assert_eq!(strided, Err(ContainerError::NotContiguous));
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn double_destroy_is_ignored() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_u8; 16]));
+        assert!(LIVE_CONTAINERS.lock().unwrap().contains(&(ptr as usize)));
+        unsafe {
+            rusty_data_container_destroy(ptr);
+            rusty_data_container_destroy(ptr);
+            rusty_data_container_destroy(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn f64_pointers_check_the_dtype() {
+        let float = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0]));
+        let int = boxed(RustyDataContainer::from_vec(vec![1_i32, 2]));
+        let data = [1.0_f64];
+        let immutable = boxed(RustyDataContainer::from_slice(&data));
+        assert_eq!(
+            rusty_data_container_as_f64_ptr(handle(float)),
+            unsafe { (*float).data } as *const c_double
+        );
+        assert!(!rusty_data_container_as_f64_ptr_mut(handle(float)).is_null());
+        assert!(rusty_data_container_as_f64_ptr(handle(int)).is_null());
+        assert!(rusty_data_container_as_f64_ptr_mut(handle(int)).is_null());
+        assert!(!rusty_data_container_as_f64_ptr(handle(immutable)).is_null());
+        assert!(rusty_data_container_as_f64_ptr_mut(handle(immutable)).is_null());
+        destroy(float);
+        destroy(int);
+        destroy(immutable);
+    }
+
+    #[test]
+    fn diff_and_patch_round_trip() {
+        let old: Vec<f64> = (0..100).map(|index| index as f64).collect();
+        let mut new = old.clone();
+        new[3] = -1.0;
+        new[70] = 0.5;
+        let old = RustyDataContainer::from_vec(old);
+        let new = RustyDataContainer::from_vec(new);
+        let patch = RustyDataContainer::diff(&old, &new).unwrap();
+        assert!(patch.len() < 2 * (16 + 8));
+        let mut patched = old.clone();
+        patched.apply_patch(&patch).unwrap();
+        assert!(patched == new);
+        assert!(RustyDataContainer::diff(&old, &old).unwrap().is_empty());
+
+        assert_eq!(
+            patched.apply_patch(&patch[..patch.len() - 1]),
+            Err(ContainerError::InvalidFormat)
+        );
+        let mut out_of_range = patch.clone();
+        out_of_range[..8].copy_from_slice(&(800_u64).to_le_bytes());
+        assert_eq!(
+            patched.apply_patch(&out_of_range),
+            Err(ContainerError::InvalidFormat)
+        );
+        assert!(patched == new);
+        let short = RustyDataContainer::from_vec(vec![0.0_f64; 3]);
+        assert_eq!(
+            RustyDataContainer::diff(&old, &short),
+            Err(ContainerError::InvalidShape)
+        );
+    }
+
+    #[test]
+    fn patch_strided_view() {
+        let old = [1_u32, 2, 3];
+        let new = [1_u32, 5, 3];
+        let mut data = [0_u32, 1, 0, 2, 0, 3];
+        let patch = RustyDataContainer::diff(
+            &RustyDataContainer::from_slice(&old),
+            &RustyDataContainer::from_slice(&new),
+        );
+        let mut view = RustyDataContainer::from_slice_mut(&mut data)
+            .slice_step(1, 6, 2)
+            .unwrap();
+        view.apply_patch(&patch.unwrap()).unwrap();
+        drop(view);
+        assert_eq!(data, [0, 1, 0, 5, 0, 3]);
+    }
+
+    #[test]
+    fn shared_clones_are_released_across_threads() {
+        struct Handle(RustyDataContainer);
+        // Shared containers are immutable, so referencing them from several threads is fine.
+        unsafe impl Sync for Handle {}
+
+        let shared = Handle(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]).into_shared());
+        let shared = &shared;
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(move || {
+                    let clones: Vec<_> = (0..100).map(|_| shared.0.clone_ref()).collect();
+                    for clone in &clones {
+                        assert_eq!(clone.get::<f64>(2), Some(3.0));
+                    }
+                });
+            }
+        });
+        assert_eq!(shared_count(&shared.0), 1);
+        let clone = shared.0.clone_ref();
+        assert_eq!(shared_count(&shared.0), 2);
+        drop(clone);
+        assert_eq!(shared_count(&shared.0), 1);
+    }
+
+    #[test]
+    fn into_shared_makes_views_contiguous() {
+        let data = [1_u8, 2, 3];
+        let shared = RustyDataContainer::from_slice(&data)
+            .reverse_view()
+            .into_shared();
+        assert!(shared.is_contiguous());
+        assert_eq!(shared.bytes(), [3, 2, 1]);
+        assert!((shared.data as usize).is_multiple_of(16));
+
+        let ptr = boxed(RustyDataContainer::from_slice(&data));
+        assert!(rusty_data_container_clone_ref(handle(ptr)).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::NotOwner));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn from_aos_extracts_a_field() {
+        #[repr(C)]
+        struct Pair {
+            first: f32,
+            second: f32,
+        }
+        let pairs: Vec<Pair> = (0..4)
+            .map(|index| Pair {
+                first: index as f32,
+                second: 10.0 * index as f32,
+            })
+            .collect();
+        let container = unsafe {
+            rusty_data_container_from_aos(
+                pairs.as_ptr() as *const c_void,
+                pairs.len(),
+                std::mem::size_of::<Pair>(),
+                std::mem::offset_of!(Pair, second),
+                DTYPE::Float32 as u32,
+            )
+        }
+        .unwrap();
+        assert_eq!(
+            container.values::<f32>().collect::<Vec<_>>(),
+            [0.0, 10.0, 20.0, 30.0]
+        );
+        assert_eq!(pairs[1].first, 1.0);
+
+        let misplaced = unsafe {
+            rusty_data_container_from_aos(
+                pairs.as_ptr() as *const c_void,
+                pairs.len(),
+                std::mem::size_of::<Pair>(),
+                std::mem::size_of::<Pair>() - 2,
+                DTYPE::Float32 as u32,
+            )
+        };
+        assert!(misplaced.is_none());
+    }
+
+    #[test]
+    fn raw_parts_round_trip() {
+        let container = RustyDataContainer::from_vec(vec![1.5_f64, 2.5, 3.5]);
+        let (data, nitems, capacity, itemsize, alignment, dtype) =
+            container.into_raw_parts().unwrap();
+        assert_eq!(
+            (nitems, itemsize, alignment, dtype),
+            (3, 8, 8, DTYPE::Float64)
+        );
+        assert!(capacity >= nitems);
+        assert_eq!(unsafe { *(data as *const f64).add(2) }, 3.5);
+        let container =
+            unsafe { RustyDataContainer::from_raw_parts(data, nitems, capacity, alignment, dtype) };
+        assert_eq!(container.is_owner, OWNERSHIP::Owner);
+        assert_eq!(container.typed::<f64>(), [1.5, 2.5, 3.5]);
+
+        let values = [1_u8, 2];
+        let view = RustyDataContainer::from_slice(&values);
+        assert_eq!(view.into_raw_parts().err(), Some(ContainerError::NotOwner));
+    }
+
+    #[test]
+    fn raw_parts_keep_the_alignment_of_a_builder() {
+        let container = crate::ContainerBuilder::new()
+            .dtype(DTYPE::Float32)
+            .shape(&[5])
+            .aligned(64)
+            .build()
+            .unwrap();
+        let (data, nitems, capacity, _, alignment, dtype) = container.into_raw_parts().unwrap();
+        assert_eq!(alignment, 64);
+        assert_eq!(data as usize % 64, 0);
+        // Dropping the rebuilt container frees the data with the original layout.
+        let container =
+            unsafe { RustyDataContainer::from_raw_parts(data, nitems, capacity, alignment, dtype) };
+        assert_eq!(container.alignment, 64);
+        assert_eq!(container.typed::<f32>(), [0.0; 5]);
+    }
+
+    #[test]
+    fn ones_and_eye() {
+        let ones = rusty_data_container_ones_f64(7);
+        assert_eq!(ones.values::<f64>().sum::<f64>(), 7.0);
+        let ones = rusty_data_container_ones_i32(5);
+        assert_eq!(ones.values::<i32>().sum::<i32>(), 5);
+
+        let eye = rusty_data_container_eye_f64(3);
+        assert_eq!(&eye.shape[..eye.ndim], [3, 3]);
+        for row in 0..3 {
+            for col in 0..3 {
+                let expected = if row == col { 1.0 } else { 0.0 };
+                assert_eq!(eye.element::<f64>(3 * row + col), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn immutable_containers_hide_the_mutable_data_pointer() {
+        let data = [1_u8, 2, 3];
+        let immutable = boxed(RustyDataContainer::from_slice(&data));
+        assert!(rusty_data_container_get_data(handle(immutable)).is_null());
+        assert_eq!(
+            rusty_data_container_get_const_data(handle(immutable)),
+            data.as_ptr() as *const c_void
+        );
+        let mutable = boxed(RustyDataContainer::from_vec(vec![1_u8, 2, 3]));
+        assert!(!rusty_data_container_get_data(handle(mutable)).is_null());
+        destroy(immutable);
+        destroy(mutable);
+    }
+
+    #[test]
+    fn to_vec_as_coerces_numeric_dtypes() {
+        let ints = boxed(RustyDataContainer::from_vec(vec![-1_i32, 0, 7]));
+        assert_eq!(
+            RustyDataContainer::to_vec_as::<f64>(handle(ints)),
+            Ok(vec![-1.0, 0.0, 7.0])
+        );
+        let view = boxed(RustyDataContainer::leak(handle(ints)).reverse_view());
+        assert_eq!(
+            RustyDataContainer::to_vec_as::<f64>(handle(view)),
+            Ok(vec![7.0, 0.0, -1.0])
+        );
+        let bools = boxed(RustyDataContainer::from_vec(vec![true, false]));
+        assert_eq!(
+            RustyDataContainer::to_vec_as::<f64>(handle(bools)),
+            Err(ContainerError::DtypeMismatch)
+        );
+        destroy(view);
+        destroy(ints);
+        destroy(bools);
+    }
+
+    #[test]
+    fn views_alias_their_parent() {
+        let parent = boxed(RustyDataContainer::from_vec(vec![0_u32; 8]));
+        let slice = boxed(
+            RustyDataContainer::leak(handle(parent))
+                .slice_step(6, 8, 1)
+                .unwrap(),
+        );
+        let other = boxed(RustyDataContainer::from_vec(vec![0_u32; 8]));
+        assert!(rusty_data_container_aliases(handle(parent), handle(slice)));
+        assert!(rusty_data_container_aliases(handle(slice), handle(parent)));
+        assert!(!rusty_data_container_aliases(handle(parent), handle(other)));
+
+        let reversed = boxed(RustyDataContainer::leak(handle(parent)).reverse_view());
+        let head = boxed(
+            RustyDataContainer::leak(handle(parent))
+                .slice_step(0, 1, 1)
+                .unwrap(),
+        );
+        assert!(rusty_data_container_aliases(handle(reversed), handle(head)));
+        let tail = boxed(
+            RustyDataContainer::leak(handle(parent))
+                .slice_step(6, 8, 1)
+                .unwrap(),
+        );
+        assert!(!rusty_data_container_aliases(handle(head), handle(tail)));
+        for ptr in [tail, head, reversed, slice, other, parent] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn iter_rev_traverses_from_the_end() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_i8, 2, 3]));
+        let reversed = RustyDataContainer::iter_rev::<i8>(handle(ptr));
+        assert_eq!(reversed.len(), 3);
+        assert_eq!(reversed.collect::<Vec<_>>(), [3, 2, 1]);
+        let mut iter = RustyDataContainer::iter_rev::<i8>(handle(ptr));
+        assert_eq!((iter.next(), iter.next_back()), (Some(3), Some(1)));
+        assert_eq!(iter.len(), 1);
+        destroy(ptr);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn into_ndarray_keeps_the_shape() {
+        let mut container = RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        container.reshape(&[2, 3]).unwrap();
+        let data = container.data as *const f64;
+        let array = container.into_ndarray::<f64>().unwrap();
+        let array = array.into_dimensionality::<ndarray::Ix2>().unwrap();
+        assert_eq!(array, ndarray::array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        if !cfg!(feature = "debug-canary") {
+            assert_eq!(array.as_ptr(), data);
+        }
+
+        let container = RustyDataContainer::from_vec(vec![1_i32, 2]);
+        assert_eq!(
+            container.into_ndarray::<f64>().err(),
+            Some(ContainerError::DtypeMismatch)
+        );
+        let data = [1.0_f64, 2.0];
+        assert_eq!(
+            RustyDataContainer::from_slice(&data)
+                .into_ndarray::<f64>()
+                .err(),
+            Some(ContainerError::NotOwner)
+        );
+    }
+
+    #[test]
+    fn copy_out_full_and_truncated() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]));
+        let mut full = [0.0_f64; 4];
+        let copied = unsafe {
+            rusty_data_container_copy_out(handle(ptr), full.as_mut_ptr() as *mut c_void, 32)
+        };
+        assert_eq!(copied, 24);
+        assert_eq!(full, [1.0, 2.0, 3.0, 0.0]);
+
+        let mut short = [0_u8; 12];
+        let copied = unsafe {
+            rusty_data_container_copy_out(handle(ptr), short.as_mut_ptr() as *mut c_void, 12)
+        };
+        assert_eq!(copied, 12);
+        assert_eq!(short[..8], 1.0_f64.to_ne_bytes());
+        assert_eq!(short[8..], 2.0_f64.to_ne_bytes()[..4]);
+
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).reverse_view());
+        let copied = unsafe {
+            rusty_data_container_copy_out(handle(view), full.as_mut_ptr() as *mut c_void, 32)
+        };
+        assert_eq!(copied, 24);
+        assert_eq!(full[..3], [3.0, 2.0, 1.0]);
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn advance_moves_the_start_of_a_view() {
+        let data: Vec<u32> = (0..10).collect();
+        let view = boxed(RustyDataContainer::from_slice(&data));
+        assert!(rusty_data_container_advance(handle(view), 3));
+        let advanced = RustyDataContainer::leak(handle(view));
+        assert_eq!((advanced.nitems, advanced.shape[0]), (7, 7));
+        assert_eq!(advanced.element::<u32>(0), 3);
+        assert!(!rusty_data_container_advance(handle(view), 8));
+        assert!(rusty_data_container_advance(handle(view), 7));
+        assert_eq!(RustyDataContainer::leak(handle(view)).nitems, 0);
+
+        let owner = boxed(RustyDataContainer::from_vec(data.clone()));
+        assert!(!rusty_data_container_advance(handle(owner), 1));
+        assert_eq!(RustyDataContainer::leak(handle(owner)).nitems, 10);
+        destroy(view);
+        destroy(owner);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn layout_matches_the_documented_c_struct() {
+        use std::mem::{align_of, offset_of, size_of};
+        let offsets = [
+            (offset_of!(RustyDataContainer, nitems), 0),
+            (offset_of!(RustyDataContainer, itemsize), 8),
+            (offset_of!(RustyDataContainer, capacity), 16),
+            (offset_of!(RustyDataContainer, dtype), 24),
+            (offset_of!(RustyDataContainer, is_owner), 28),
+            (offset_of!(RustyDataContainer, is_mutable), 29),
+            (offset_of!(RustyDataContainer, data), 32),
+            (offset_of!(RustyDataContainer, ndim), 40),
+            (offset_of!(RustyDataContainer, shape), 48),
+            (offset_of!(RustyDataContainer, strides), 112),
+            (offset_of!(RustyDataContainer, alignment), 176),
+            (offset_of!(RustyDataContainer, borrowed), 184),
+            (offset_of!(RustyDataContainer, zeroize), 185),
+            (offset_of!(RustyDataContainer, generation), 192),
+            (offset_of!(RustyDataContainer, parent), 200),
+            (offset_of!(RustyDataContainer, lock), 208),
+        ];
+        for (offset, documented) in offsets {
+            assert_eq!(offset, documented);
+        }
+        assert_eq!(size_of::<RustyDataContainer>(), 216);
+        assert_eq!(align_of::<RustyDataContainer>(), 8);
+        assert_eq!(
+            [
+                OWNERSHIP::NotOwner as u8,
+                OWNERSHIP::Owner as u8,
+                OWNERSHIP::Shared as u8
+            ],
+            [0, 1, 2]
+        );
+        assert_eq!(
+            [MUTABILITY::NotMutable as u8, MUTABILITY::Mutable as u8],
+            [0, 1]
+        );
+    }
+
+    #[test]
+    fn slice_step_takes_every_other_element() {
+        let ptr = boxed(RustyDataContainer::from_vec((0..10).collect::<Vec<i64>>()));
+        let every_other = rusty_data_container_slice_step(handle(ptr), 0, 10, 2).unwrap();
+        assert_eq!(every_other.strides[0], 2);
+        assert_eq!(
+            every_other.values::<i64>().collect::<Vec<_>>(),
+            [0, 2, 4, 6, 8]
+        );
+        let odd = rusty_data_container_slice_step(handle(ptr), 1, 10, 3).unwrap();
+        assert_eq!(odd.values::<i64>().collect::<Vec<_>>(), [1, 4, 7]);
+        assert!(rusty_data_container_slice_step(handle(ptr), 0, 10, 0).is_none());
+        assert!(rusty_data_container_slice_step(handle(ptr), 0, 11, 1).is_none());
+        assert!(rusty_data_container_slice_step(handle(ptr), 5, 4, 1).is_none());
+        drop((every_other, odd));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn borrowed_containers_reject_mutation() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 4.0]));
+        assert!(rusty_data_container_borrow_mut(handle(ptr)));
+        assert!(!rusty_data_container_borrow_mut(handle(ptr)));
+
+        let shape = [2_usize, 2];
+        assert!(!unsafe { rusty_data_container_reshape(handle(ptr), shape.as_ptr(), 2) });
+        assert_eq!(crate::last_error(), Some(ContainerError::Borrowed));
+        assert!(!rusty_data_container_set_zeroize(handle(ptr), true));
+        assert!(!rusty_data_container_advance(handle(ptr), 1));
+        assert!(!rusty_data_container_resize(handle(ptr), 8));
+        assert!(!crate::rusty_data_container_cast_inplace(
+            handle(ptr),
+            DTYPE::Float32 as u32
+        ));
+        assert!(rusty_data_container_take(handle(ptr)).is_none());
+        assert!(rusty_data_container_take_bytes(handle(ptr)).is_none());
+        assert!(rusty_data_container_into_data(handle(ptr)).is_null());
+        assert!(rusty_data_container_into_shared(handle(ptr)).is_none());
+        assert!(rusty_data_container_get_data(handle(ptr)).is_null());
+        assert!(rusty_data_container_as_f64_ptr_mut(handle(ptr)).is_null());
+        assert_eq!(crate::last_error(), Some(ContainerError::Borrowed));
+        let other = boxed(RustyDataContainer::from_vec(vec![0_u8]));
+        assert!(!crate::rusty_data_container_swap(
+            handle(ptr),
+            handle(other)
+        ));
+        assert_eq!(crate::last_error(), Some(ContainerError::Borrowed));
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(
+            (container.dtype, container.nitems, container.ndim),
+            (DTYPE::Float64, 4, 1)
+        );
+        assert!(!container.zeroize);
+
+        assert!(rusty_data_container_release_mut(handle(ptr)));
+        assert!(!rusty_data_container_release_mut(handle(ptr)));
+        assert!(!rusty_data_container_get_data(handle(ptr)).is_null());
+        assert!(!rusty_data_container_as_f64_ptr_mut(handle(ptr)).is_null());
+        assert!(unsafe { rusty_data_container_reshape(handle(ptr), shape.as_ptr(), 2) });
+        assert!(rusty_data_container_borrow_mut(handle(ptr)));
+        assert!(rusty_data_container_release_mut(handle(ptr)));
+        assert!(!crate::rusty_data_container_swap(None, handle(other)));
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+        destroy(other);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn ptr_range_spans_the_data() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_i32, 2, 3, 4, 5]));
+        let mut begin = std::ptr::null_mut();
+        let mut end = std::ptr::null_mut();
+        unsafe { rusty_data_container_ptr_range(handle(ptr), &mut begin, &mut end) };
+        assert_eq!(begin, RustyDataContainer::leak(handle(ptr)).data);
+        assert_eq!(end as usize - begin as usize, 5 * 4);
+
+        unsafe { rusty_data_container_ptr_range(None, &mut begin, &mut end) };
+        assert!(begin.is_null() && end.is_null());
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).reverse_view());
+        unsafe { rusty_data_container_ptr_range(handle(view), &mut begin, &mut end) };
+        assert!(begin.is_null() && end.is_null());
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn zeroize_flag_and_wipe() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0xAB_u8; 16]));
+        assert!(!RustyDataContainer::leak(handle(ptr)).zeroize);
+        assert!(rusty_data_container_set_zeroize(handle(ptr), true));
+        let container = RustyDataContainer::leak_mut(handle(ptr));
+        assert!(container.zeroize);
+        container.wipe();
+        assert_eq!(container.typed::<u8>(), [0; 16]);
+        assert!(rusty_data_container_set_zeroize(handle(ptr), false));
+        assert!(!RustyDataContainer::leak(handle(ptr)).zeroize);
+        assert!(!rusty_data_container_set_zeroize(None, true));
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+
+        // Views never wipe the data they reference.
+        let mut view = RustyDataContainer::leak(handle(ptr))
+            .slice_step(0, 4, 1)
+            .unwrap();
+        RustyDataContainer::leak_mut(handle(ptr)).typed_mut::<u8>()[0] = 7;
+        view.wipe();
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).typed::<u8>()[0], 7);
+        drop(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn grow_inplace_reports_moves_and_keeps_the_values() {
+        let container = crate::ContainerBuilder::new()
+            .shape(&[4])
+            .dtype(DTYPE::Float64)
+            .aligned(64)
+            .build()
+            .unwrap();
+        let ptr = boxed(container);
+        RustyDataContainer::leak_mut(handle(ptr))
+            .typed_mut::<f64>()
+            .copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        let old_data = RustyDataContainer::leak(handle(ptr)).data;
+        let moved = rusty_data_container_grow_inplace(handle(ptr), 1000);
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(moved, container.data != old_data);
+        assert_eq!(container.nitems, 1000);
+        assert!(container.is_aligned_to(64));
+        assert_eq!(container.typed::<f64>()[..4], [1.0, 2.0, 3.0, 4.0]);
+        assert!(container.typed::<f64>()[4..]
+            .iter()
+            .all(|&value| value == 0.0));
+
+        // Shrinking is rejected and leaves the container unchanged.
+        assert!(!rusty_data_container_grow_inplace(handle(ptr), 2));
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).nitems, 1000);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn get_or_returns_the_default_on_misses() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.5_f64, -2.0, 4.25]));
+        assert_eq!(rusty_data_container_get_f64_or(handle(ptr), 1, 0.0), -2.0);
+        assert_eq!(rusty_data_container_get_f64_or(handle(ptr), 3, 9.0), 9.0);
+        assert_eq!(rusty_data_container_get_i64_or(handle(ptr), 0, -1), -1);
+        assert_eq!(rusty_data_container_get_f64_or(None, 0, 7.0), 7.0);
+        destroy(ptr);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![10_i64, 20]));
+        assert_eq!(rusty_data_container_get_i64_or(handle(ptr), 1, 0), 20);
+        assert_eq!(rusty_data_container_get_u64_or(handle(ptr), 1, 5), 5);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn memory_usage_counts_the_capacity() {
+        let ptr = boxed(RustyDataContainer::from_vec(Vec::<f64>::with_capacity(100)));
+        let usage = rusty_data_container_memory_usage(handle(ptr));
+        assert!(usage >= 800 + std::mem::size_of::<RustyDataContainer>());
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).view());
+        assert_eq!(
+            rusty_data_container_memory_usage(handle(view)),
+            std::mem::size_of::<RustyDataContainer>()
+        );
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn generation_increments_on_reallocation() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_u32, 2, 3]));
+        assert_eq!(rusty_data_container_generation(handle(ptr)), 0);
+        assert!(rusty_data_container_resize(handle(ptr), 10));
+        assert_eq!(rusty_data_container_generation(handle(ptr)), 1);
+        assert!(rusty_data_container_resize(handle(ptr), 2));
+        assert_eq!(rusty_data_container_generation(handle(ptr)), 2);
+        // A failed resize leaves the generation unchanged.
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).view());
+        assert!(!rusty_data_container_resize(handle(view), 4));
+        assert_eq!(rusty_data_container_generation(handle(view)), 0);
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn from_fn_builds_from_the_index() {
+        let container = RustyDataContainer::from_fn(5, |i| i as f64 * 2.0);
+        assert_eq!((container.dtype, container.nitems), (DTYPE::Float64, 5));
+        assert_eq!(container.element::<f64>(3), 6.0);
+        assert!(RustyDataContainer::from_fn(0, |i| i as u8).is_empty());
+    }
+
+    #[test]
+    fn new_constructs_every_numeric_dtype() {
+        for dtype in DTYPE::ALL {
+            let container = rusty_data_container_new(3, dtype.discriminant());
+            if dtype == DTYPE::Bool {
+                assert!(container.is_none());
+                continue;
+            }
+            let container = container.unwrap();
+            assert_eq!((container.dtype, container.nitems), (dtype, 3));
+            assert!(container.bytes().iter().all(|&byte| byte == 0));
+        }
+        assert!(rusty_data_container_new(3, DTYPE::ALL.len() as u32).is_none());
+        assert!(rusty_data_container_new(usize::MAX, DTYPE::Float64.discriminant()).is_none());
+    }
+
+    #[test]
+    fn take_leaves_an_empty_owner() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_i64, 2, 3, 4, 5]));
+        let data = RustyDataContainer::leak(handle(ptr)).data;
+        let taken = rusty_data_container_take(handle(ptr)).unwrap();
+        assert_eq!(taken.nitems, 5);
+        assert_eq!(taken.data, data);
+        assert_eq!(taken.typed::<i64>(), [1, 2, 3, 4, 5]);
+
+        let original = RustyDataContainer::leak(handle(ptr));
+        assert_eq!((original.nitems, original.capacity), (0, 0));
+        assert_eq!(original.dtype, DTYPE::Int64);
+        assert_eq!(original.is_owner, OWNERSHIP::Owner);
+        assert_eq!(original.generation(), 1);
+        assert!(rusty_data_container_resize(handle(ptr), 2));
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).typed::<i64>(), [0, 0]);
+        drop(taken);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn take_bytes_and_back_round_trip() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.5_f64, -2.0, 3.25]));
+        let data = RustyDataContainer::leak(handle(ptr)).data;
+        let bytes = Box::into_raw(rusty_data_container_take_bytes(handle(ptr)).unwrap());
+        let container = RustyDataContainer::leak(handle(bytes));
+        assert_eq!((container.dtype, container.nitems), (DTYPE::Unsigned8, 24));
+        assert_eq!(container.data, data);
+        assert_eq!(container.typed::<u8>()[..8], 1.5_f64.to_ne_bytes());
+
+        assert!(rusty_data_container_from_bytes_as(handle(bytes), 14).is_none());
+        assert!(
+            rusty_data_container_from_bytes_as(handle(bytes), DTYPE::Complex64 as u32).is_none()
+        );
+        assert_eq!(crate::last_error(), Some(ContainerError::InvalidShape));
+        let restored =
+            rusty_data_container_from_bytes_as(handle(bytes), DTYPE::Float64 as u32).unwrap();
+        assert_eq!(restored.data, data);
+        assert_eq!(restored.typed::<f64>(), [1.5, -2.0, 3.25]);
+        drop(restored);
+    }
+
+    #[test]
+    fn u128_values_beyond_64_bits() {
+        let value = (1_u128 << 100) + 5;
+        let ptr = Box::into_raw(rusty_data_container_new_u128(2));
+        RustyDataContainer::leak_mut(handle(ptr)).typed_mut::<u128>()[1] = value;
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(container.itemsize, 16);
+        assert_eq!(container.typed::<u128>(), [0, value]);
+        #[cfg(target_endian = "little")]
+        {
+            let words = unsafe { std::slice::from_raw_parts(container.data as *const u64, 4) };
+            assert_eq!(words[2..], [5, 1 << 36]);
+        }
+        let copy = RustyDataContainer::deserialize(&container.serialize()).unwrap();
+        assert_eq!(copy.typed::<u128>(), [0, value]);
+        destroy(ptr);
+
+        let ptr = Box::into_raw(rusty_data_container_new_i128(1));
+        RustyDataContainer::leak_mut(handle(ptr)).typed_mut::<i128>()[0] = -(value as i128);
+        assert_eq!(
+            RustyDataContainer::leak(handle(ptr)).element::<i128>(0),
+            -(value as i128)
+        );
+        destroy(ptr);
+    }
+
+    #[test]
+    fn linspace_and_arange_grids() {
+        let grid = rusty_data_container_linspace_f64(0.0, 1.0, 5).unwrap();
+        let values = grid.typed::<f64>();
+        assert_eq!(values.len(), 5);
+        assert_eq!((values[0], values[4]), (0.0, 1.0));
+        for pair in values.windows(2) {
+            assert!((pair[1] - pair[0] - 0.25).abs() < 1e-15);
+        }
+        assert!(rusty_data_container_linspace_f64(0.0, 1.0, 0).is_none());
+        assert_eq!(
+            rusty_data_container_linspace_f64(2.0, 3.0, 1)
+                .unwrap()
+                .typed::<f64>(),
+            [2.0]
+        );
+
+        let range = rusty_data_container_arange_f64(0.0, 1.0, 0.25).unwrap();
+        assert_eq!(range.typed::<f64>(), [0.0, 0.25, 0.5, 0.75]);
+        assert!(rusty_data_container_arange_f64(0.0, 1.0, 0.0).is_none());
+        assert!(rusty_data_container_arange_f64(1.0, 0.0, 0.5)
+            .unwrap()
+            .is_empty());
+        let single = RustyDataContainer::linspace(-1.0, 1.0, 3, DTYPE::Float32).unwrap();
+        assert_eq!(single.typed::<f32>(), [-1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn copy_out_into_every_other_slot() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]));
+        let mut dst = [-1.0_f64; 6];
+        assert!(unsafe {
+            rusty_data_container_copy_out_strided(handle(ptr), dst.as_mut_ptr() as *mut c_void, 16)
+        });
+        assert_eq!(dst, [1.0, -1.0, 2.0, -1.0, 3.0, -1.0]);
+        assert!(!unsafe {
+            rusty_data_container_copy_out_strided(handle(ptr), dst.as_mut_ptr() as *mut c_void, 4)
+        });
+        destroy(ptr);
+    }
+
+    #[test]
+    fn new_from_pointer_checked_validates_the_buffer() {
+        let mut values = [1.0_f64, 2.0, 3.0, 4.0];
+        let data = values.as_mut_ptr() as *mut c_void;
+        let code = DTYPE::Float64.discriminant();
+        assert!(new_from_pointer_checked(data, 4, code, MUTABILITY::Mutable, 31).is_none());
+        assert!(
+            new_from_pointer_checked(data, usize::MAX, code, MUTABILITY::Mutable, 32).is_none()
+        );
+        assert!(
+            new_from_pointer_checked(std::ptr::null_mut(), 1, code, MUTABILITY::Mutable, 8)
+                .is_none()
+        );
+        assert!(new_from_pointer_checked(data, 4, 14, MUTABILITY::Mutable, 32).is_none());
+        let unaligned = unsafe { (data as *mut u8).add(1) } as *mut c_void;
+        assert!(new_from_pointer_checked(unaligned, 2, code, MUTABILITY::Mutable, 24).is_none());
+
+        let container =
+            new_from_pointer_checked(data, 4, code, MUTABILITY::NotMutable, 32).unwrap();
+        assert_eq!(container.is_owner, OWNERSHIP::NotOwner);
+        assert_eq!(container.is_mutable, MUTABILITY::NotMutable);
+        assert_eq!(container.typed::<f64>(), values);
+        drop(container);
+        assert!(new_from_pointer(data, 4, 14, MUTABILITY::Mutable).is_none());
+    }
+
+    #[test]
+    fn rows_of_a_matrix() {
+        let mut container = RustyDataContainer::from_vec((0..12).collect::<Vec<i32>>());
+        container.reshape(&[3, 4]).unwrap();
+        let rows: Vec<_> = container.rows().collect();
+        assert_eq!(rows.len(), 3);
+        for (index, row) in rows.iter().enumerate() {
+            assert_eq!((row.ndim, row.nitems), (1, 4));
+            assert_eq!(row.is_owner, OWNERSHIP::NotOwner);
+            let start = 4 * index as i32;
+            assert_eq!(row.typed::<i32>(), [start, start + 1, start + 2, start + 3]);
+        }
+        assert!(container.row(3).is_none());
+
+        // Rows of a column-major view are strided.
+        let ptr = boxed(container);
+        let transposed = rusty_data_container_from_matrix(
+            RustyDataContainer::leak(handle(ptr)).data,
+            4,
+            3,
+            DTYPE::Int32 as u32,
+            true,
+            MUTABILITY::NotMutable,
+        )
+        .unwrap();
+        let row = transposed.row(1).unwrap();
+        assert_eq!(row.values::<i32>().collect::<Vec<_>>(), [1, 5, 9]);
+        drop(row);
+        drop(transposed);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn into_data_transfers_the_buffer() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]));
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).view());
+        assert!(rusty_data_container_into_data(handle(view)).is_null());
+        assert_eq!(crate::last_error(), Some(ContainerError::NotOwner));
+        destroy(view);
+
+        let data = rusty_data_container_into_data(handle(ptr));
+        let values = unsafe { std::slice::from_raw_parts(data as *const f64, 3) };
+        assert_eq!(values, [1.0, 2.0, 3.0]);
+        unsafe { rusty_free(data, 3, 8) };
+
+        // Buffers with spare capacity or a different alignment are copied.
+        let mut vec = Vec::with_capacity(8);
+        vec.extend([
+            crate::Complex64::new(1.0, -1.0),
+            crate::Complex64::new(0.5, 2.0),
+        ]);
+        let data = rusty_data_container_into_data(handle(boxed(RustyDataContainer::from_vec(vec))));
+        assert!((data as usize).is_multiple_of(16));
+        let values = unsafe { std::slice::from_raw_parts(data as *const crate::Complex64, 2) };
+        assert_eq!(values[1], crate::Complex64::new(0.5, 2.0));
+        unsafe { rusty_free(data, 2, 16) };
+        unsafe { rusty_free(std::ptr::null_mut(), 0, 8) };
+    }
+
+    #[test]
+    fn reshape_keeps_the_data() {
+        let ptr = boxed(RustyDataContainer::from_vec((0..6).collect::<Vec<i64>>()));
+        let data = RustyDataContainer::leak(handle(ptr)).data;
+        let dims = |ptr| {
+            let container = RustyDataContainer::leak(handle(ptr));
+            let ndim = container.ndim;
+            (
+                container.shape[..ndim].to_vec(),
+                container.strides[..ndim].to_vec(),
+            )
+        };
+        assert!(unsafe { rusty_data_container_reshape(handle(ptr), [2, 3].as_ptr(), 2) });
+        assert_eq!(dims(ptr), (vec![2, 3], vec![3, 1]));
+        assert!(unsafe { rusty_data_container_reshape(handle(ptr), [3, 2].as_ptr(), 2) });
+        assert_eq!(dims(ptr), (vec![3, 2], vec![2, 1]));
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).data, data);
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).element::<i64>(5), 5);
+
+        assert!(!unsafe { rusty_data_container_reshape(handle(ptr), [4, 2].as_ptr(), 2) });
+        assert!(!unsafe { rusty_data_container_reshape(handle(ptr), std::ptr::null(), 0) });
+        assert_eq!(dims(ptr), (vec![3, 2], vec![2, 1]));
+        let view = boxed(
+            RustyDataContainer::leak(handle(ptr))
+                .row(0)
+                .unwrap()
+                .reverse_view(),
+        );
+        assert!(!unsafe { rusty_data_container_reshape(handle(view), [2, 1].as_ptr(), 2) });
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[cfg(feature = "safe-views")]
+    #[test]
+    fn live_views_block_moving_the_data_and_outlive_the_owner() {
+        let owner = boxed(RustyDataContainer::from_vec(vec![1.0f64, 2.0, 3.0, 4.0]));
+        let other = boxed(RustyDataContainer::from_vec(vec![5.0f64]));
+        let view = rusty_data_container_slice_step(handle(owner), 1, 4, 2).unwrap();
+        let view = Box::into_raw(view);
+
+        assert!(!crate::rusty_data_container_swap(
+            handle(owner),
+            handle(other)
+        ));
+        assert_eq!(crate::last_error(), Some(ContainerError::HasViews));
+        assert!(!rusty_data_container_resize(handle(owner), 8));
+        assert_eq!(crate::last_error(), Some(ContainerError::HasViews));
+        assert!(!rusty_data_container_grow_inplace(handle(owner), 8));
+        assert_eq!(crate::last_error(), Some(ContainerError::HasViews));
+        assert!(rusty_data_container_take(handle(owner)).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::HasViews));
+
+        destroy(owner);
+        destroy(other);
+        assert_eq!(rusty_data_container_get_f64_or(handle(view), 0, 0.0), 2.0);
+        assert_eq!(rusty_data_container_get_f64_or(handle(view), 1, 0.0), 4.0);
+        destroy(view);
+    }
+
+    #[test]
+    fn write_lock_excludes_concurrent_readers() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0u8; 4])) as usize;
+        let readers = AtomicUsize::new(0);
+        let writing = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        rusty_data_container_read_lock(handle(ptr as *mut _));
+                        readers.fetch_add(1, Ordering::SeqCst);
+                        assert!(!writing.load(Ordering::SeqCst));
+                        readers.fetch_sub(1, Ordering::SeqCst);
+                        assert!(rusty_data_container_read_unlock(handle(ptr as *mut _)));
+                    }
+                });
+            }
+            scope.spawn(|| {
+                for _ in 0..1000 {
+                    rusty_data_container_write_lock(handle(ptr as *mut _));
+                    writing.store(true, Ordering::SeqCst);
+                    assert_eq!(readers.load(Ordering::SeqCst), 0);
+                    std::thread::yield_now();
+                    writing.store(false, Ordering::SeqCst);
+                    assert!(rusty_data_container_write_unlock(handle(ptr as *mut _)));
+                }
+            });
+        });
+
+        assert!(!rusty_data_container_read_unlock(handle(ptr as *mut _)));
+        assert!(!rusty_data_container_write_unlock(handle(ptr as *mut _)));
+        destroy(ptr as *mut _);
+    }
+
+    #[test]
+    fn from_array_borrows_the_array() {
+        let array = [1u32, 2, 3];
+        let container = RustyDataContainer::from_array(&array);
+        assert_eq!(container.nitems, 3);
+        assert_eq!(container.dtype, DTYPE::Unsigned32);
+        assert_eq!(container.is_owner, OWNERSHIP::NotOwner);
+        assert_eq!(container.is_mutable, MUTABILITY::NotMutable);
+        assert_eq!(container.data as *const u32, array.as_ptr());
+        assert_eq!(container.get::<u32>(2), Some(3));
+    }
+
+    #[test]
+    fn prefetch_accepts_in_bounds_ranges() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0.0f64; 1000]));
+        assert!(rusty_data_container_prefetch(handle(ptr), 0, 1000));
+        assert!(rusty_data_container_prefetch(handle(ptr), 999, 1));
+        assert!(rusty_data_container_prefetch(handle(ptr), 10, 0));
+        let every_other = rusty_data_container_slice_step(handle(ptr), 0, 1000, 2).unwrap();
+        let every_other = Box::into_raw(every_other);
+        assert!(rusty_data_container_prefetch(handle(every_other), 0, 500));
+        assert!(!rusty_data_container_prefetch(handle(ptr), 999, 2));
+        assert!(!rusty_data_container_prefetch(handle(ptr), usize::MAX, 2));
+        destroy(every_other);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn validate_reports_inconsistent_fields() {
+        let values = [1.0f64, 2.0, 3.0];
+        let mut container = RustyDataContainer::from_slice(&values);
+        assert_eq!(
+            unsafe { rusty_data_container_validate(std::ptr::addr_of!(container)) },
+            0
+        );
+        assert_eq!(
+            unsafe { rusty_data_container_validate(std::ptr::null()) },
+            RUSTY_INVALID_NULL
+        );
+
+        container.nitems = 10;
+        container.itemsize = 4;
+        container.data = std::ptr::null_mut();
+        assert_eq!(
+            unsafe { rusty_data_container_validate(std::ptr::addr_of!(container)) },
+            RUSTY_INVALID_CAPACITY | RUSTY_INVALID_ITEMSIZE | RUSTY_INVALID_DATA
+        );
+
+        // An unknown dtype code is written as a raw integer and skips the itemsize check.
+        let code = std::ptr::addr_of_mut!(container.dtype).cast::<u32>();
+        unsafe { code.write(99) };
+        assert_eq!(
+            unsafe { rusty_data_container_validate(std::ptr::addr_of!(container)) },
+            RUSTY_INVALID_DTYPE | RUSTY_INVALID_CAPACITY | RUSTY_INVALID_DATA
+        );
+        unsafe { code.write(DTYPE::Float64 as u32) };
+    }
+
+    #[test]
+    fn gsl_vector_is_a_strided_view() {
+        let mut data = [0.0, 10.0, 1.0, 11.0, 2.0, 12.0];
+        let ptr =
+            rusty_data_container_from_gsl_vector(data.as_mut_ptr(), 3, 2, MUTABILITY::Mutable)
+                .map(Box::into_raw)
+                .unwrap();
+        assert_eq!(rusty_data_container_get_nitems(handle(ptr)), 3);
+        for index in 0..3 {
+            assert_eq!(
+                rusty_data_container_get_f64_or(handle(ptr), index, -1.0),
+                index as f64
+            );
+        }
+        assert!(
+            rusty_data_container_from_gsl_vector(data.as_mut_ptr(), 3, 0, MUTABILITY::Mutable)
+                .is_none()
+        );
+        destroy(ptr);
+    }
+
+    #[test]
+    fn into_shared_consumes_the_container() {
+        assert!(rusty_data_container_into_shared(None).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        let shared = Box::into_raw(rusty_data_container_into_shared(handle(ptr)).unwrap());
+        let container = RustyDataContainer::leak(handle(shared));
+        assert_eq!(container.is_owner, OWNERSHIP::Shared);
+        assert_eq!(container.get::<i32>(2), Some(3));
+        destroy(shared);
+    }
+
+    #[cfg(feature = "safe-views")]
+    #[test]
+    fn into_shared_refuses_containers_with_views() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        let view = Box::into_raw(rusty_data_container_slice_step(handle(ptr), 0, 3, 2).unwrap());
+        assert!(rusty_data_container_into_shared(handle(ptr)).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::HasViews));
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).get::<i32>(1), Some(2));
+        destroy(view);
+        destroy(ptr);
+    }
+
+    fn wiped_bytes() -> usize {
+        WIPED_BYTES.with(|wiped| wiped.get())
+    }
+
+    #[test]
+    fn zeroize_wipes_buffers_released_by_resize_and_grow() {
+        let mut container = RustyDataContainer::from_vec(vec![0xAB_u8; 16]);
+        container.zeroize = true;
+        let before = wiped_bytes();
+        container.resize(32).unwrap();
+        assert_eq!(wiped_bytes() - before, 16);
+        assert_eq!(container.typed::<u8>()[..16], [0xAB; 16]);
+
+        let before = wiped_bytes();
+        assert_eq!(container.grow_inplace(64), Ok(true));
+        assert_eq!(wiped_bytes() - before, 32);
+        assert_eq!(container.typed::<u8>()[..16], [0xAB; 16]);
+        assert_eq!(container.typed::<u8>()[16..], [0; 48]);
+
+        let before = wiped_bytes();
+        drop(container);
+        assert_eq!(wiped_bytes() - before, 64);
+    }
+
+    #[test]
+    fn zeroize_wipes_shared_data_with_the_last_reference() {
+        let mut container = RustyDataContainer::from_vec(vec![0xAB_u8; 16]);
+        container.zeroize = true;
+        let shared = container.into_shared();
+        let other = shared.clone_ref();
+        assert!(other.zeroize);
+        let before = wiped_bytes();
+        drop(shared);
+        assert_eq!(wiped_bytes(), before);
+        drop(other);
+        assert_eq!(wiped_bytes() - before, 16);
+    }
+
+    #[cfg(feature = "safe-views")]
+    #[test]
+    fn into_data_refuses_containers_with_views() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]));
+        let view = Box::into_raw(rusty_data_container_slice_step(handle(ptr), 1, 3, 1).unwrap());
+        assert!(rusty_data_container_into_data(handle(ptr)).is_null());
+        assert_eq!(crate::last_error(), Some(ContainerError::HasViews));
+        assert_eq!(rusty_data_container_get_f64_or(handle(ptr), 2, 0.0), 3.0);
+
+        destroy(view);
+        let data = rusty_data_container_into_data(handle(ptr));
+        assert_eq!(unsafe { *(data as *const f64).add(1) }, 2.0);
+        unsafe { rusty_free(data, 3, 8) };
     }
-    .to_box()
 }
@@ -0,0 +1,71 @@
+//! Export of containers as comma-separated values for inspection.
+//!
+//! Each row of the last dimension becomes one line, so a one-dimensional container
+//! is a single line and a two-dimensional container has one line per row. Higher
+//! dimensional containers are flattened in row-major order into lines of the last
+//! dimension. Every line, including the last one, ends with a newline.
+//!
+//! Values are formatted with the Rust `Display` implementation of their type. Floats
+//! use the shortest digits that parse back to the same value, without an exponent,
+//! for example `0.1`, `0.0000001`, `NaN` and `inf`. Complex values are written like
+//! `1+2j` and booleans as `true` and `false`.
+
+use crate::{dispatch_dtype, RustyDataContainer};
+use libc::{c_char, size_t};
+use std::fmt::Write;
+
+impl RustyDataContainer {
+    /// Format the elements as comma-separated values, one line per row of the last dimension.
+    pub fn to_csv(&self) -> String {
+        let ncols = self.shape[self.ndim - 1];
+        let mut csv = String::new();
+        dispatch_dtype!(self.dtype, T => {
+            for (index, value) in self.values::<T>().enumerate() {
+                write!(csv, "{value}").unwrap();
+                csv.push(if (index + 1) % ncols == 0 { '\n' } else { ',' });
+            }
+        });
+        csv
+    }
+}
+
+/// Write the elements of a container as comma-separated values into `buf`.
+///
+/// The text is null-terminated and truncated to `len` bytes including the terminator.
+/// Returns the length of the full text without terminator, so that the caller can
+/// call this function with `len = 0` first to size the buffer. See [crate::csv] for
+/// the format.
+///
+/// # Safety
+/// `buf` must be valid for writing `len` bytes, or `len` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_to_csv(
+    ptr: Option<Box<RustyDataContainer>>,
+    buf: *mut c_char,
+    len: size_t,
+) -> size_t {
+    crate::write_c_string(&RustyDataContainer::leak(ptr).to_csv(), buf, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn csv_of_a_matrix() {
+        let mut container = RustyDataContainer::from_vec(vec![1_i32, 2, 3, 4]);
+        container.reshape(&[2, 2]).unwrap();
+        let ptr = boxed(container);
+        let len = unsafe { rusty_data_container_to_csv(handle(ptr), std::ptr::null_mut(), 0) };
+        assert_eq!(len, 8);
+        let mut buf = vec![0 as c_char; len + 1];
+        unsafe { rusty_data_container_to_csv(handle(ptr), buf.as_mut_ptr(), buf.len()) };
+        let text = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) };
+        assert_eq!(text.to_str().unwrap(), "1,2\n3,4\n");
+        destroy(ptr);
+
+        let floats = RustyDataContainer::from_vec(vec![0.1_f64, f64::NAN, 1e-7]);
+        assert_eq!(floats.to_csv(), "0.1,NaN,0.0000001\n");
+    }
+}
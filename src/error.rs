@@ -0,0 +1,128 @@
+//! Error types.
+//!
+//! C functions that cannot report an error through their return value
+//! store it as the last error of the calling thread, which can be queried
+//! with [rusty_last_error] and [rusty_last_error_message].
+
+use libc::{c_char, size_t};
+use std::cell::Cell;
+use std::fmt;
+
+/// Errors that can occur when creating or converting data containers.
+///
+/// The discriminants are the error codes returned by [rusty_last_error].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[repr(u32)]
+pub enum ContainerError {
+    /// No dtype was specified.
+    MissingDtype = 1,
+    /// The shape is empty, has too many dimensions or its size overflows.
+    InvalidShape = 2,
+    /// The alignment is not a power of two or smaller than the natural alignment of the dtype.
+    InvalidAlignment = 3,
+    /// The memory allocation failed.
+    AllocationFailed = 4,
+    /// The dtype of the container does not match the requested type.
+    DtypeMismatch = 5,
+    /// The operation requires a container that owns its data.
+    NotOwner = 6,
+    /// The operation requires contiguous data.
+    NotContiguous = 7,
+    /// A serialized container is truncated or malformed.
+    InvalidFormat = 8,
+    /// The operation requires a container with at least one element.
+    Empty = 9,
+    /// Bytes that should be UTF-8 text are not valid UTF-8.
+    InvalidUtf8 = 10,
+    /// The operation requires a mutable container.
+    NotMutable = 11,
+    /// A NaN value cannot be converted to an integer dtype.
+    NotANumber = 12,
+    /// The container is mutably borrowed by a C caller.
+    Borrowed = 13,
+    /// A container pointer is null.
+    NullPointer = 14,
+    /// An element index is out of range.
+    OutOfBounds = 15,
+    /// The data cannot be moved or freed while views of the container are alive.
+    HasViews = 16,
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ContainerError::MissingDtype => "no dtype specified",
+            ContainerError::InvalidShape => "invalid shape",
+            ContainerError::InvalidAlignment => "invalid alignment",
+            ContainerError::AllocationFailed => "memory allocation failed",
+            ContainerError::DtypeMismatch => "dtype mismatch",
+            ContainerError::NotOwner => "container does not own its data",
+            ContainerError::NotContiguous => "data is not contiguous",
+            ContainerError::InvalidFormat => "invalid serialized container",
+            ContainerError::Empty => "container is empty",
+            ContainerError::InvalidUtf8 => "invalid UTF-8 text",
+            ContainerError::NotMutable => "container is not mutable",
+            ContainerError::NotANumber => "NaN cannot be converted to an integer",
+            ContainerError::Borrowed => "container is mutably borrowed by a C caller",
+            ContainerError::NullPointer => "null container pointer",
+            ContainerError::OutOfBounds => "index out of bounds",
+            ContainerError::HasViews => "container has live views",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+thread_local! {
+    static LAST_ERROR: Cell<Option<ContainerError>> = const { Cell::new(None) };
+}
+
+/// Store `error` as the last error of the calling thread.
+pub(crate) fn set_last_error(error: ContainerError) {
+    LAST_ERROR.with(|last| last.set(Some(error)));
+}
+
+/// The last error of the calling thread, if any.
+pub fn last_error() -> Option<ContainerError> {
+    LAST_ERROR.with(Cell::get)
+}
+
+/// Get the code of the last error of the calling thread, or 0 if there is none.
+#[no_mangle]
+pub extern "C" fn rusty_last_error() -> u32 {
+    last_error().map_or(0, |error| error as u32)
+}
+
+/// Clear the last error of the calling thread.
+#[no_mangle]
+pub extern "C" fn rusty_clear_last_error() {
+    LAST_ERROR.with(|last| last.set(None));
+}
+
+/// Copy the message of the last error of the calling thread as a null-terminated string
+/// into `buf`, truncated to `len` bytes including the terminator.
+///
+/// Returns the length of the full message without terminator, or 0 if there is no error.
+///
+/// # Safety
+/// `buf` must be valid for writing `len` bytes, or `len` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_last_error_message(buf: *mut c_char, len: size_t) -> size_t {
+    let message = last_error().map_or(String::new(), |error| error.to_string());
+    write_c_string(&message, buf, len)
+}
+
+/// Copy `text` as a null-terminated string into `buf`, truncated to `len` bytes
+/// including the terminator, and return the length of `text`.
+///
+/// # Safety
+/// `buf` must be valid for writing `len` bytes, or `len` must be 0.
+pub(crate) unsafe fn write_c_string(text: &str, buf: *mut c_char, len: size_t) -> size_t {
+    if len > 0 {
+        let count = text.len().min(len - 1);
+        std::ptr::copy_nonoverlapping(text.as_ptr(), buf as *mut u8, count);
+        *buf.add(count) = 0;
+    }
+    text.len()
+}
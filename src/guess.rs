@@ -0,0 +1,123 @@
+//! Best-effort detection of the dtype of opaque byte buffers.
+//!
+//! The heuristics are meant for debugging and inspection tools only.
+//! They are not authoritative: many buffers are valid under several
+//! interpretations, and the guess can be wrong.
+
+use crate::DTYPE;
+use libc::size_t;
+
+/// The result of guessing the type of a byte buffer.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DtypeGuess {
+    /// The buffer looks like elements of the given dtype.
+    Dtype(DTYPE),
+    /// The buffer looks like UTF-8 encoded text.
+    Utf8,
+    /// No interpretation is plausible enough.
+    Undefined,
+}
+
+/// Code returned by [rusty_guess_dtype] for buffers that look like UTF-8 text.
+pub const RUSTY_GUESS_UTF8: u32 = 0x100;
+/// Code returned by [rusty_guess_dtype] if no interpretation is plausible enough.
+pub const RUSTY_GUESS_UNDEFINED: u32 = u32::MAX;
+
+fn is_text(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok_and(|text| {
+        text.chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+    })
+}
+
+/// Whether a float is zero or has a magnitude that typically occurs in data.
+/// Reinterpreted integers and text usually give NaN, subnormal or huge values.
+fn is_plausible(value: f64, min: f64, max: f64) -> bool {
+    value == 0.0 || (value.is_finite() && (min..=max).contains(&value.abs()))
+}
+
+/// Guess the type of the native endian data in `bytes`.
+///
+/// The checks are, in order: every byte sequence of printable UTF-8 text is
+/// [DtypeGuess::Utf8]. A length divisible by 8 where every value read as f64 is
+/// zero or has a magnitude in `[1e-100, 1e100]` is [DTYPE::Float64], and the same
+/// for f32 with `[1e-30, 1e30]`. Everything else, including empty buffers, is
+/// [DtypeGuess::Undefined].
+pub fn guess_dtype(bytes: &[u8]) -> DtypeGuess {
+    if bytes.is_empty() {
+        return DtypeGuess::Undefined;
+    }
+    if is_text(bytes) {
+        return DtypeGuess::Utf8;
+    }
+    if bytes.len().is_multiple_of(8)
+        && bytes
+            .chunks_exact(8)
+            .all(|chunk| is_plausible(f64::from_ne_bytes(chunk.try_into().unwrap()), 1e-100, 1e100))
+    {
+        return DtypeGuess::Dtype(DTYPE::Float64);
+    }
+    if bytes.len().is_multiple_of(4)
+        && bytes.chunks_exact(4).all(|chunk| {
+            is_plausible(
+                f32::from_ne_bytes(chunk.try_into().unwrap()) as f64,
+                1e-30,
+                1e30,
+            )
+        })
+    {
+        return DtypeGuess::Dtype(DTYPE::Float32);
+    }
+    DtypeGuess::Undefined
+}
+
+/// Guess the type of the `len` bytes at `bytes`. This is a non-authoritative
+/// heuristic for debugging, see [guess_dtype].
+///
+/// Returns the dtype code, [RUSTY_GUESS_UTF8] for text or [RUSTY_GUESS_UNDEFINED]
+/// if no interpretation is plausible enough.
+///
+/// # Safety
+/// `bytes` must be valid for reading `len` bytes, or `len` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_guess_dtype(bytes: *const u8, len: size_t) -> u32 {
+    if len == 0 {
+        return RUSTY_GUESS_UNDEFINED;
+    }
+    match guess_dtype(std::slice::from_raw_parts(bytes, len)) {
+        DtypeGuess::Dtype(dtype) => dtype.discriminant(),
+        DtypeGuess::Utf8 => RUSTY_GUESS_UTF8,
+        DtypeGuess::Undefined => RUSTY_GUESS_UNDEFINED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_floats_and_text() {
+        let bytes: Vec<u8> = [1.5_f64, -2.25, 0.0, 3e8]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        assert_eq!(guess_dtype(&bytes), DtypeGuess::Dtype(DTYPE::Float64));
+        assert_eq!(
+            unsafe { rusty_guess_dtype(bytes.as_ptr(), bytes.len()) },
+            DTYPE::Float64.discriminant()
+        );
+
+        let text = b"rusty containers\n";
+        assert_eq!(guess_dtype(text), DtypeGuess::Utf8);
+        assert_eq!(
+            unsafe { rusty_guess_dtype(text.as_ptr(), text.len()) },
+            RUSTY_GUESS_UTF8
+        );
+
+        assert_eq!(guess_dtype(&[0xFF, 0x00, 0x01]), DtypeGuess::Undefined);
+        assert_eq!(
+            unsafe { rusty_guess_dtype(std::ptr::null(), 0) },
+            RUSTY_GUESS_UNDEFINED
+        );
+    }
+}
@@ -1,7 +1,45 @@
 //! Tools for interfacing Rust via CFFI
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod builder;
+pub mod canary;
+pub mod checksum;
 pub mod containers;
+pub mod csv;
+pub mod error;
+pub mod guess;
+pub mod manipulation;
+pub mod nested;
+pub mod operations;
+pub mod pybuffer;
+pub mod random;
+pub mod serialize;
+pub mod small;
+pub mod sparse;
+pub mod tensor;
+#[cfg(test)]
+mod testing;
+pub mod typed;
 pub mod types;
+pub mod views;
 
+pub use builder::*;
+pub use canary::*;
+pub use checksum::*;
 pub use containers::*;
+pub use csv::*;
+pub use error::*;
+pub use guess::*;
+pub use manipulation::*;
+pub use nested::*;
+pub use operations::*;
+pub use pybuffer::*;
+pub use random::*;
+pub use serialize::*;
+pub use small::*;
+pub use sparse::*;
+pub use tensor::*;
+pub use typed::*;
 pub use types::*;
+pub use views::*;
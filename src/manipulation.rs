@@ -0,0 +1,610 @@
+//! Functions to combine and rearrange data containers.
+
+use crate::{
+    dispatch_dtype, CastFrom, Complex, ConversionType, RustyDataContainer, DTYPE, OWNERSHIP,
+};
+use libc::size_t;
+
+/// Copy the element `src_index` of `src` to the element `dst_index` of `dst`.
+/// Both containers must have the same dtype and `dst` must be contiguous.
+pub(crate) fn copy_element(
+    dst: &mut RustyDataContainer,
+    dst_index: usize,
+    src: &RustyDataContainer,
+    src_index: usize,
+) {
+    let itemsize = dst.itemsize;
+    let bytes = src.element_bytes(src_index);
+    dst.bytes_mut()[dst_index * itemsize..(dst_index + 1) * itemsize].copy_from_slice(bytes);
+}
+
+/// Stack two 2-D containers vertically into a new owning contiguous container.
+///
+/// Returns `None` if the containers are not 2-D, have different dtypes,
+/// or have different numbers of columns.
+pub fn vstack(a: &RustyDataContainer, b: &RustyDataContainer) -> Option<RustyDataContainer> {
+    if a.ndim != 2 || b.ndim != 2 || a.dtype != b.dtype || a.shape[1] != b.shape[1] {
+        return None;
+    }
+    let cols = a.shape[1];
+    let mut result = RustyDataContainer::new_zeroed(a.nitems + b.nitems, a.dtype);
+    result.set_contiguous_shape(&[a.shape[0] + b.shape[0], cols]);
+    for index in 0..a.nitems {
+        copy_element(&mut result, index, a, index);
+    }
+    for index in 0..b.nitems {
+        copy_element(&mut result, a.nitems + index, b, index);
+    }
+    Some(result)
+}
+
+/// Stack two 2-D containers horizontally into a new owning contiguous container.
+///
+/// Returns `None` if the containers are not 2-D, have different dtypes,
+/// or have different numbers of rows.
+pub fn hstack(a: &RustyDataContainer, b: &RustyDataContainer) -> Option<RustyDataContainer> {
+    if a.ndim != 2 || b.ndim != 2 || a.dtype != b.dtype || a.shape[0] != b.shape[0] {
+        return None;
+    }
+    let rows = a.shape[0];
+    let (acols, bcols) = (a.shape[1], b.shape[1]);
+    let cols = acols + bcols;
+    let mut result = RustyDataContainer::new_zeroed(a.nitems + b.nitems, a.dtype);
+    result.set_contiguous_shape(&[rows, cols]);
+    for row in 0..rows {
+        for col in 0..acols {
+            copy_element(&mut result, row * cols + col, a, row * acols + col);
+        }
+        for col in 0..bcols {
+            copy_element(&mut result, row * cols + acols + col, b, row * bcols + col);
+        }
+    }
+    Some(result)
+}
+
+/// Stack two 2-D containers vertically. Returns null if the number of columns differs.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_vstack(
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    vstack(RustyDataContainer::leak(a), RustyDataContainer::leak(b)).map(|c| c.to_box())
+}
+
+/// Stack two 2-D containers horizontally. Returns null if the number of rows differs.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_hstack(
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    hstack(RustyDataContainer::leak(a), RustyDataContainer::leak(b)).map(|c| c.to_box())
+}
+
+/// Swap the elements with row-major indices `i` and `j` of a mutable container.
+/// Returns false if an index is out of bounds. Returns false and sets the last
+/// error if the container is not mutable or is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_swap_elements(
+    ptr: Option<Box<RustyDataContainer>>,
+    i: size_t,
+    j: size_t,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if i >= container.nitems || j >= container.nitems {
+        return false;
+    }
+    if i != j {
+        unsafe {
+            std::ptr::swap_nonoverlapping(
+                container.element_ptr(i),
+                container.element_ptr(j),
+                container.itemsize,
+            );
+        }
+    }
+    true
+}
+
+/// Swap the entire contents of two containers.
+///
+/// Returns false and sets the last error without changing either container
+/// if one of them is borrowed by a C caller or has live views, see [crate::views].
+#[no_mangle]
+pub extern "C" fn rusty_data_container_swap(
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> bool {
+    // Check both handles before returning, so that neither is destroyed on failure.
+    let a = RustyDataContainer::try_leak_mut(a, false);
+    let b = RustyDataContainer::try_leak_mut(b, false);
+    let (Some(a), Some(b)) = (a, b) else {
+        return false;
+    };
+    let views = crate::views::ensure_no_views(a).and_then(|_| crate::views::ensure_no_views(b));
+    if let Err(error) = views {
+        crate::set_last_error(error);
+        return false;
+    }
+    if !std::ptr::eq(a, b) {
+        std::mem::swap(a, b);
+    }
+    true
+}
+
+/// Select the elements of `src` at which the Bool container `mask` is true.
+///
+/// Returns a new owning one dimensional container, or `None` if the
+/// mask is not of type [DTYPE::Bool] or has a different number of items.
+pub fn mask_select(
+    src: &RustyDataContainer,
+    mask: &RustyDataContainer,
+) -> Option<RustyDataContainer> {
+    if mask.dtype != DTYPE::Bool || mask.nitems != src.nitems {
+        return None;
+    }
+    let count = mask.values::<bool>().filter(|&selected| selected).count();
+    let mut result = RustyDataContainer::new_zeroed(count, src.dtype);
+    let selected = mask
+        .values::<bool>()
+        .enumerate()
+        .filter(|&(_, selected)| selected);
+    for (dst_index, (src_index, _)) in selected.enumerate() {
+        copy_element(&mut result, dst_index, src, src_index);
+    }
+    Some(result)
+}
+
+/// Select the elements of `src` at which `mask` is true.
+/// Returns null if the mask is not a Bool container of the same length.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_mask_select(
+    src: Option<Box<RustyDataContainer>>,
+    mask: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    mask_select(
+        RustyDataContainer::leak(src),
+        RustyDataContainer::leak(mask),
+    )
+    .map(|c| c.to_box())
+}
+
+/// Set the elements of the mutable container `dst` at which `mask` is true to `value`.
+///
+/// The value is converted to the dtype of `dst` like an `as` cast.
+/// Returns false if the mask is not a Bool container of the same length. Returns false
+/// and sets the last error if `dst` is not mutable or is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_mask_assign(
+    dst: Option<Box<RustyDataContainer>>,
+    mask: Option<Box<RustyDataContainer>>,
+    value: f64,
+) -> bool {
+    let mask = RustyDataContainer::leak(mask);
+    let Some(dst) = RustyDataContainer::try_leak_mut(dst, true) else {
+        return false;
+    };
+    if mask.dtype != DTYPE::Bool || mask.nitems != dst.nitems {
+        return false;
+    }
+    dispatch_dtype!(dst.dtype, T => {
+        let value = T::cast_from(value);
+        for (index, selected) in mask.values::<bool>().enumerate() {
+            if selected {
+                unsafe { *(dst.element_ptr(index) as *mut T) = value };
+            }
+        }
+    });
+    true
+}
+
+fn split_parts<F: ConversionType + Copy>(
+    src: &RustyDataContainer,
+) -> (RustyDataContainer, RustyDataContainer)
+where
+    Complex<F>: ConversionType,
+{
+    let (re, im) = src.values::<Complex<F>>().map(|z| (z.re, z.im)).unzip();
+    let mut re = RustyDataContainer::from_vec::<F>(re);
+    let mut im = RustyDataContainer::from_vec::<F>(im);
+    re.set_contiguous_shape(&src.shape[..src.ndim]);
+    im.set_contiguous_shape(&src.shape[..src.ndim]);
+    (re, im)
+}
+
+fn merge_parts<F: ConversionType + Copy>(
+    re: &RustyDataContainer,
+    im: &RustyDataContainer,
+) -> RustyDataContainer
+where
+    Complex<F>: ConversionType,
+{
+    let data: Vec<Complex<F>> = re
+        .values::<F>()
+        .zip(im.values::<F>())
+        .map(|(re, im)| Complex::new(re, im))
+        .collect();
+    let mut result = RustyDataContainer::from_vec(data);
+    result.set_contiguous_shape(&re.shape[..re.ndim]);
+    result
+}
+
+/// Split a complex container into new containers with its real and imaginary parts.
+///
+/// The parts have the float dtype of the components and the shape of `src`.
+/// Returns `None` if `src` is not complex.
+pub fn split_complex(src: &RustyDataContainer) -> Option<(RustyDataContainer, RustyDataContainer)> {
+    match src.dtype {
+        DTYPE::Complex32 => Some(split_parts::<f32>(src)),
+        DTYPE::Complex64 => Some(split_parts::<f64>(src)),
+        _ => None,
+    }
+}
+
+/// Merge containers with real and imaginary parts into a new complex container.
+///
+/// The result has the shape of `re`. Returns `None` if `re` and `im` do not have
+/// the same float dtype and number of items.
+pub fn merge_complex(
+    re: &RustyDataContainer,
+    im: &RustyDataContainer,
+) -> Option<RustyDataContainer> {
+    if re.dtype != im.dtype || re.nitems != im.nitems {
+        return None;
+    }
+    match re.dtype {
+        DTYPE::Float32 => Some(merge_parts::<f32>(re, im)),
+        DTYPE::Float64 => Some(merge_parts::<f64>(re, im)),
+        _ => None,
+    }
+}
+
+/// The real and imaginary parts of a complex container.
+#[repr(C)]
+pub struct RustyComplexParts {
+    /// The real part, or null on failure.
+    pub re: Option<Box<RustyDataContainer>>,
+    /// The imaginary part, or null on failure.
+    pub im: Option<Box<RustyDataContainer>>,
+}
+
+/// Split a complex container into new containers with its real and imaginary parts.
+/// Both parts are null if the container is not complex.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_split_complex(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> RustyComplexParts {
+    match split_complex(RustyDataContainer::leak(ptr)) {
+        Some((re, im)) => RustyComplexParts {
+            re: Some(re.to_box()),
+            im: Some(im.to_box()),
+        },
+        None => RustyComplexParts { re: None, im: None },
+    }
+}
+
+/// Merge containers with real and imaginary parts into a new complex container.
+/// Returns null if the parts do not have the same float dtype and number of items.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_merge_complex(
+    re: Option<Box<RustyDataContainer>>,
+    im: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    merge_complex(RustyDataContainer::leak(re), RustyDataContainer::leak(im)).map(|c| c.to_box())
+}
+
+/// Pack a Bool container into a new u8 container with one bit per element.
+///
+/// Bit `k % 8` of byte `k / 8` is set if element `k` is true. Unused bits of the
+/// last byte are zero. Returns `None` if `src` is not a Bool container.
+pub fn pack_bits(src: &RustyDataContainer) -> Option<RustyDataContainer> {
+    if src.dtype != DTYPE::Bool {
+        return None;
+    }
+    let mut bytes = vec![0_u8; src.nitems.div_ceil(8)];
+    for (index, value) in src.values::<bool>().enumerate() {
+        bytes[index / 8] |= (value as u8) << (index % 8);
+    }
+    Some(RustyDataContainer::from_vec(bytes))
+}
+
+/// Unpack the first `nbits` bits of a u8 container into a new Bool container.
+///
+/// This reverses [pack_bits]. Returns `None` if `src` is not a u8 container
+/// or has fewer than `nbits` bits.
+pub fn unpack_bits(src: &RustyDataContainer, nbits: usize) -> Option<RustyDataContainer> {
+    if src.dtype != DTYPE::Unsigned8 || nbits > 8 * src.nitems {
+        return None;
+    }
+    let values = (0..nbits)
+        .map(|index| src.element::<u8>(index / 8) >> (index % 8) & 1 == 1)
+        .collect::<Vec<bool>>();
+    Some(RustyDataContainer::from_vec(values))
+}
+
+/// Pack a Bool container into a new u8 container with one bit per element.
+/// Returns null if the container is not a Bool container.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_pack_bits(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    pack_bits(RustyDataContainer::leak(ptr)).map(|c| c.to_box())
+}
+
+/// Unpack the first `nbits` bits of a u8 container into a new Bool container.
+/// Returns null if the container is not a u8 container or has fewer than `nbits` bits.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_unpack_bits(
+    ptr: Option<Box<RustyDataContainer>>,
+    nbits: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    unpack_bits(RustyDataContainer::leak(ptr), nbits).map(|c| c.to_box())
+}
+
+/// Keep only the elements of an owning f64 container for which `keep` returns true.
+///
+/// The kept elements are compacted in place in their original order and the number
+/// of items is reduced accordingly. The capacity is unchanged. The container must be
+/// mutable. Returns false without changing the container if it is not an owning,
+/// one-dimensional and contiguous f64 container. Returns false and sets the last
+/// error if the container is not mutable or is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_retain_f64(
+    ptr: Option<Box<RustyDataContainer>>,
+    keep: extern "C" fn(f64) -> bool,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if container.is_owner != OWNERSHIP::Owner
+        || container.dtype != DTYPE::Float64
+        || container.ndim != 1
+        || !container.is_contiguous()
+    {
+        return false;
+    }
+    let data = container.typed_mut::<f64>();
+    let mut count = 0;
+    for index in 0..data.len() {
+        if keep(data[index]) {
+            data[count] = data[index];
+            count += 1;
+        }
+    }
+    container.nitems = count;
+    container.shape[0] = count;
+    true
+}
+
+impl RustyDataContainer {
+    /// Remove consecutive duplicate elements of an owning, one-dimensional and contiguous container.
+    ///
+    /// Elements are compared bitwise, so for floats `0.0` and `-0.0` differ, and NaNs
+    /// are equal if they have the same bits. The kept elements are compacted in place
+    /// and the number of items is reduced accordingly, the capacity is unchanged.
+    /// Returns false without changing the container if the requirements are not met.
+    /// Panics if the container is not mutable.
+    pub fn dedup(&mut self) -> bool {
+        if self.is_owner != OWNERSHIP::Owner || self.ndim != 1 || !self.is_contiguous() {
+            return false;
+        }
+        let itemsize = self.itemsize;
+        let bytes = self.bytes_mut();
+        let mut count = 0;
+        for index in 0..bytes.len() / itemsize {
+            let offset = index * itemsize;
+            if count == 0
+                || bytes[offset..offset + itemsize]
+                    != bytes[(count - 1) * itemsize..count * itemsize]
+            {
+                bytes.copy_within(offset..offset + itemsize, count * itemsize);
+                count += 1;
+            }
+        }
+        self.nitems = count;
+        self.shape[0] = count;
+        true
+    }
+}
+
+/// Remove consecutive duplicate elements of an owning, mutable container in place.
+///
+/// This removes all duplicates of sorted data. Despite the name, containers of every
+/// dtype are accepted and their elements are compared bitwise, see [RustyDataContainer::dedup]. Returns false without changing the
+/// container if it is not an owning, one-dimensional and contiguous container. Returns
+/// false and sets the last error if the container is not mutable or is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_dedup_f64(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    RustyDataContainer::try_leak_mut(ptr, true).is_some_and(|container| container.dedup())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn matrix(values: Vec<i32>, rows: usize, cols: usize) -> *mut RustyDataContainer {
+        let mut container = RustyDataContainer::from_vec(values);
+        container.reshape(&[rows, cols]).unwrap();
+        boxed(container)
+    }
+
+    #[test]
+    fn vstack_appends_rows() {
+        let a = matrix(vec![1, 2, 3, 4, 5, 6], 2, 3);
+        let b = matrix(vec![7, 8, 9], 1, 3);
+        let stacked = rusty_data_container_vstack(handle(a), handle(b)).unwrap();
+        assert_eq!(&stacked.shape[..stacked.ndim], [3, 3]);
+        assert_eq!(
+            stacked.row(1).unwrap().values::<i32>().collect::<Vec<_>>(),
+            [4, 5, 6]
+        );
+        assert_eq!(
+            stacked.row(2).unwrap().values::<i32>().collect::<Vec<_>>(),
+            [7, 8, 9]
+        );
+        assert!(rusty_data_container_hstack(handle(a), handle(b)).is_none());
+
+        let c = matrix(vec![10, 20], 2, 1);
+        assert!(rusty_data_container_vstack(handle(a), handle(c)).is_none());
+        let stacked = rusty_data_container_hstack(handle(a), handle(c)).unwrap();
+        assert_eq!(&stacked.shape[..stacked.ndim], [2, 4]);
+        assert_eq!(stacked.typed::<i32>(), [1, 2, 3, 10, 4, 5, 6, 20]);
+        destroy(a);
+        destroy(b);
+        destroy(c);
+    }
+
+    #[test]
+    fn swap_elements_and_containers() {
+        let a = boxed(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        assert!(rusty_data_container_swap_elements(handle(a), 0, 2));
+        assert!(!rusty_data_container_swap_elements(handle(a), 0, 3));
+        assert_eq!(
+            RustyDataContainer::leak(handle(a)).typed::<i32>(),
+            [3, 2, 1]
+        );
+
+        let b = boxed(RustyDataContainer::from_vec(vec![0.5_f64]));
+        rusty_data_container_swap(handle(a), handle(b));
+        assert_eq!(RustyDataContainer::leak(handle(a)).typed::<f64>(), [0.5]);
+        assert_eq!(
+            RustyDataContainer::leak(handle(b)).typed::<i32>(),
+            [3, 2, 1]
+        );
+        rusty_data_container_swap(handle(a), handle(a));
+        assert_eq!(RustyDataContainer::leak(handle(a)).typed::<f64>(), [0.5]);
+        destroy(a);
+        destroy(b);
+    }
+
+    #[test]
+    fn mask_select_and_assign() {
+        let src = boxed(RustyDataContainer::from_vec(vec![10_i32, 20, 30]));
+        let mask = boxed(RustyDataContainer::from_vec(vec![true, false, true]));
+        let selected = rusty_data_container_mask_select(handle(src), handle(mask)).unwrap();
+        assert_eq!(selected.typed::<i32>(), [10, 30]);
+        assert!(rusty_data_container_mask_assign(
+            handle(src),
+            handle(mask),
+            -1.0
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(src)).typed::<i32>(),
+            [-1, 20, -1]
+        );
+
+        let short = boxed(RustyDataContainer::from_vec(vec![true, false]));
+        let ints = boxed(RustyDataContainer::from_vec(vec![1_u8, 0, 1]));
+        assert!(rusty_data_container_mask_select(handle(src), handle(short)).is_none());
+        assert!(rusty_data_container_mask_select(handle(src), handle(ints)).is_none());
+        assert!(!rusty_data_container_mask_assign(
+            handle(src),
+            handle(ints),
+            0.0
+        ));
+        for ptr in [src, mask, short, ints] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn split_and_merge_complex() {
+        let values = vec![
+            crate::Complex64::new(1.0, -1.0),
+            crate::Complex64::new(2.5, 0.0),
+            crate::Complex64::new(0.0, 3.0),
+        ];
+        let mut container = RustyDataContainer::from_vec(values.clone());
+        container.reshape(&[3, 1]).unwrap();
+        let ptr = boxed(container);
+        let parts = rusty_data_container_split_complex(handle(ptr));
+        let (re, im) = (
+            Box::into_raw(parts.re.unwrap()),
+            Box::into_raw(parts.im.unwrap()),
+        );
+        assert_eq!(
+            RustyDataContainer::leak(handle(re)).typed::<f64>(),
+            [1.0, 2.5, 0.0]
+        );
+        assert_eq!(
+            RustyDataContainer::leak(handle(im)).typed::<f64>(),
+            [-1.0, 0.0, 3.0]
+        );
+        let merged = rusty_data_container_merge_complex(handle(re), handle(im)).unwrap();
+        assert!(*merged == *RustyDataContainer::leak(handle(ptr)));
+        assert_eq!(merged.typed::<crate::Complex64>(), values);
+
+        let real = boxed(RustyDataContainer::from_vec(vec![1.0_f64]));
+        let parts = rusty_data_container_split_complex(handle(real));
+        assert!(parts.re.is_none() && parts.im.is_none());
+        assert!(rusty_data_container_merge_complex(handle(re), handle(real)).is_none());
+        let floats = boxed(RustyDataContainer::from_vec(vec![1.0_f32, 2.0, 3.0]));
+        assert!(rusty_data_container_merge_complex(handle(re), handle(floats)).is_none());
+        for ptr in [ptr, re, im, real, floats] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn pack_and_unpack_bits() {
+        let mask = boxed(RustyDataContainer::from_vec(vec![true, false, true, true]));
+        let packed = rusty_data_container_pack_bits(handle(mask)).unwrap();
+        assert_eq!(packed.dtype, DTYPE::Unsigned8);
+        assert_eq!(packed.typed::<u8>(), [0b00001101]);
+        let packed = Box::into_raw(packed);
+        let unpacked = rusty_data_container_unpack_bits(handle(packed), 4).unwrap();
+        assert_eq!(unpacked.typed::<bool>(), [true, false, true, true]);
+        assert!(rusty_data_container_unpack_bits(handle(packed), 9).is_none());
+        assert!(rusty_data_container_pack_bits(handle(packed)).is_none());
+        destroy(packed);
+        destroy(mask);
+
+        // The final partial byte holds the bits after the last full byte.
+        let values: Vec<bool> = (0..10).map(|index| index % 3 == 0).collect();
+        let packed = pack_bits(&RustyDataContainer::from_vec(values.clone())).unwrap();
+        assert_eq!(packed.typed::<u8>(), [0b01001001, 0b00000010]);
+        assert_eq!(unpack_bits(&packed, 10).unwrap().typed::<bool>(), values);
+    }
+
+    extern "C" fn is_even(value: f64) -> bool {
+        value % 2.0 == 0.0
+    }
+
+    #[test]
+    fn retain_even_values() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 4.0]));
+        let capacity = RustyDataContainer::leak(handle(ptr)).capacity;
+        assert!(rusty_data_container_retain_f64(handle(ptr), is_even));
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(container.nitems, 2);
+        assert_eq!(container.shape[0], 2);
+        assert_eq!(container.capacity, capacity);
+        assert_eq!(container.typed::<f64>(), [2.0, 4.0]);
+
+        let ints = boxed(RustyDataContainer::from_vec(vec![1_i64, 2]));
+        assert!(!rusty_data_container_retain_f64(handle(ints), is_even));
+        assert_eq!(RustyDataContainer::leak(handle(ints)).nitems, 2);
+        destroy(ints);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn dedup_removes_consecutive_duplicates() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![
+            1.0f64, 1.0, 2.0, 3.0, 3.0, 3.0,
+        ]));
+        assert!(rusty_data_container_dedup_f64(handle(ptr)));
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(container.nitems, 3);
+        assert_eq!(container.shape[0], 3);
+        assert_eq!(
+            (0..3)
+                .map(|index| container.get::<f64>(index).unwrap())
+                .collect::<Vec<_>>(),
+            [1.0, 2.0, 3.0]
+        );
+        destroy(ptr);
+    }
+}
@@ -0,0 +1,218 @@
+//! Jagged arrays stored as flat values with offsets.
+//!
+//! A [RustyNested] bundles a data container with the values of all
+//! sub-arrays one after another, and a u64 container with the offsets
+//! at which each sub-array starts, followed by the total number of values.
+//!
+//! An array of strings is a nested array of u8 sub-arrays with the UTF-8 bytes
+//! of each string, see [RustyDataContainer::from_strings].
+
+use crate::{ContainerError, ConversionType, RustyDataContainer, DTYPE};
+use libc::{c_char, size_t};
+use std::ffi::CStr;
+use std::ops::Range;
+
+/// A jagged array of sub-arrays with the same dtype.
+pub struct RustyNested {
+    /// The values of all sub-arrays in order.
+    values: RustyDataContainer,
+    /// The `len() + 1` start offsets of the sub-arrays into `values`.
+    /// The dtype is [crate::DTYPE::Unsigned64].
+    offsets: RustyDataContainer,
+}
+
+impl RustyNested {
+    /// The number of sub-arrays.
+    pub fn len(&self) -> usize {
+        self.offsets.nitems - 1
+    }
+
+    /// Whether there are no sub-arrays.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The values of all sub-arrays.
+    pub fn values(&self) -> &RustyDataContainer {
+        &self.values
+    }
+
+    /// The offsets of the sub-arrays.
+    pub fn offsets(&self) -> &RustyDataContainer {
+        &self.offsets
+    }
+
+    /// A non-owning view of the sub-array `index`, or `None` if it does not exist.
+    pub fn get(&self, index: usize) -> Option<RustyDataContainer> {
+        if index >= self.len() {
+            return None;
+        }
+        let range = self.range(index);
+        self.values.slice_step(range.start, range.end, 1)
+    }
+
+    /// The range of the values of the sub-array `index`.
+    fn range(&self, index: usize) -> Range<usize> {
+        self.offsets.element::<u64>(index) as usize..self.offsets.element::<u64>(index + 1) as usize
+    }
+
+    /// The sub-arrays of a nested u8 array as UTF-8 strings.
+    fn strs(&self) -> impl Iterator<Item = Result<&str, ContainerError>> + '_ {
+        let bytes = if self.values.dtype == DTYPE::Unsigned8 {
+            Ok(self.values.bytes())
+        } else {
+            Err(ContainerError::DtypeMismatch)
+        };
+        (0..self.len()).map(move |index| {
+            std::str::from_utf8(&bytes?[self.range(index)]).map_err(|_| ContainerError::InvalidUtf8)
+        })
+    }
+
+    /// Convert a nested u8 array of UTF-8 text into strings.
+    ///
+    /// Fails if the values are not u8 or a sub-array is not valid UTF-8.
+    pub fn to_string_vec(self) -> Result<Vec<String>, ContainerError> {
+        self.strs().map(|text| text.map(str::to_owned)).collect()
+    }
+
+    /// Concatenate the strings of a nested u8 array of UTF-8 text, separated by `sep`.
+    ///
+    /// Fails if the values are not u8 or a sub-array is not valid UTF-8.
+    pub fn join_to_string(&self, sep: &str) -> Result<String, ContainerError> {
+        Ok(self.strs().collect::<Result<Vec<&str>, _>>()?.join(sep))
+    }
+
+    /// Get a reference to a RustyNested from a ptr.
+    /// Ensures that the destructor of the nested array is not run.
+    pub fn leak(ptr: Option<Box<RustyNested>>) -> &'static RustyNested {
+        Box::leak(ptr.unwrap())
+    }
+}
+
+impl RustyDataContainer {
+    /// Flatten nested vectors into a [RustyNested].
+    pub fn from_nested<T: ConversionType>(nested: Vec<Vec<T>>) -> RustyNested {
+        let mut offsets = Vec::with_capacity(nested.len() + 1);
+        offsets.push(0_u64);
+        let mut values = Vec::with_capacity(nested.iter().map(Vec::len).sum());
+        for inner in nested {
+            values.extend(inner);
+            offsets.push(values.len() as u64);
+        }
+        RustyNested {
+            values: RustyDataContainer::from_vec(values),
+            offsets: RustyDataContainer::from_vec(offsets),
+        }
+    }
+
+    /// Store strings as a nested u8 array of their UTF-8 bytes.
+    pub fn from_strings<S: AsRef<str>>(strings: &[S]) -> RustyNested {
+        Self::from_nested(
+            strings
+                .iter()
+                .map(|text| text.as_ref().as_bytes().to_vec())
+                .collect(),
+        )
+    }
+}
+
+/// Destroy a nested array together with its containers.
+#[no_mangle]
+pub extern "C" fn rusty_nested_destroy(_: Option<Box<RustyNested>>) {}
+
+/// Get the number of sub-arrays.
+#[no_mangle]
+pub extern "C" fn rusty_nested_len(ptr: Option<Box<RustyNested>>) -> size_t {
+    RustyNested::leak(ptr).len()
+}
+
+/// Get a non-owning view of the sub-array `index`. Returns null if it does not exist.
+#[no_mangle]
+pub extern "C" fn rusty_nested_get(
+    ptr: Option<Box<RustyNested>>,
+    index: size_t,
+) -> Option<Box<RustyDataContainer>> {
+    RustyNested::leak(ptr).get(index).map(|c| c.to_box())
+}
+
+/// Get a non-owning view of the values of all sub-arrays.
+#[no_mangle]
+pub extern "C" fn rusty_nested_values(ptr: Option<Box<RustyNested>>) -> Box<RustyDataContainer> {
+    RustyNested::leak(ptr).values.view().to_box()
+}
+
+/// Get a non-owning view of the offsets of the sub-arrays.
+#[no_mangle]
+pub extern "C" fn rusty_nested_offsets(ptr: Option<Box<RustyNested>>) -> Box<RustyDataContainer> {
+    RustyNested::leak(ptr).offsets.view().to_box()
+}
+
+/// Write the strings of a nested u8 array of UTF-8 text, separated by `sep`, into `buf`.
+///
+/// The text is null-terminated and truncated to `len` bytes including the terminator.
+/// Returns the length of the full text without terminator. If the values are not u8,
+/// `sep` or a sub-array is not valid UTF-8, the last error is set and `SIZE_MAX` is
+/// returned.
+///
+/// # Safety
+/// `sep` must be a null-terminated string. `buf` must be valid for writing `len`
+/// bytes, or `len` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_nested_join_to_string(
+    ptr: Option<Box<RustyNested>>,
+    sep: *const c_char,
+    buf: *mut c_char,
+    len: size_t,
+) -> size_t {
+    let nested = RustyNested::leak(ptr);
+    let joined = CStr::from_ptr(sep)
+        .to_str()
+        .map_err(|_| ContainerError::InvalidUtf8)
+        .and_then(|sep| nested.join_to_string(sep));
+    match joined {
+        Ok(text) => crate::write_c_string(&text, buf, len),
+        Err(error) => {
+            crate::set_last_error(error);
+            size_t::MAX
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_sub_array_lengths() {
+        let nested = RustyDataContainer::from_nested(vec![vec![1_i32, 2], vec![], vec![3]]);
+        assert_eq!(nested.len(), 3);
+        assert_eq!(nested.offsets().typed::<u64>(), [0, 2, 2, 3]);
+        let ptr = Box::into_raw(Box::new(nested));
+        let lengths: Vec<_> = (0..3)
+            .map(|index| {
+                rusty_nested_get(unsafe { Some(Box::from_raw(ptr)) }, index)
+                    .unwrap()
+                    .nitems
+            })
+            .collect();
+        assert_eq!(lengths, [2, 0, 1]);
+        let last = rusty_nested_get(unsafe { Some(Box::from_raw(ptr)) }, 2).unwrap();
+        assert_eq!(last.typed::<i32>(), [3]);
+        assert!(rusty_nested_get(unsafe { Some(Box::from_raw(ptr)) }, 3).is_none());
+        drop(last);
+        rusty_nested_destroy(unsafe { Some(Box::from_raw(ptr)) });
+    }
+
+    #[test]
+    fn strings_round_trip() {
+        let nested = RustyDataContainer::from_strings(&["a", "bb", "ccc"]);
+        assert_eq!(nested.join_to_string(", ").unwrap(), "a, bb, ccc");
+        assert_eq!(nested.to_string_vec().unwrap(), ["a", "bb", "ccc"]);
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        let nested = RustyDataContainer::from_nested(vec![vec![b'a'], vec![0xff_u8]]);
+        assert_eq!(nested.to_string_vec(), Err(ContainerError::InvalidUtf8));
+    }
+}
@@ -0,0 +1,1763 @@
+//! Numeric operations on data containers.
+//!
+//! The operations dispatch on the dtype of the container and
+//! work on all numeric types. Operations that change a container
+//! return false and set the last error if it is not mutable or is
+//! borrowed by a C caller, see [crate::rusty_data_container_borrow_mut].
+
+use crate::{
+    dispatch_dtype, dispatch_real_dtype, get_alignment, get_itemsize, set_last_error, CastFrom,
+    Complex, ContainerError, ConversionType, RustyDataContainer, DTYPE, MUTABILITY, OWNERSHIP,
+};
+use libc::size_t;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+fn clamp<T: PartialOrd>(value: T, lo: T, hi: T) -> T {
+    if value < lo {
+        lo
+    } else if value > hi {
+        hi
+    } else {
+        value
+    }
+}
+
+/// Clamp each element of a mutable container to the range `[lo, hi]`.
+///
+/// The bounds are converted to the dtype of the container like an `as` cast.
+/// NaN values are left unchanged. The container must be mutable, strided views are supported.
+/// Returns false if `lo > hi` or if the dtype is complex.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_clamp_f64(
+    ptr: Option<Box<RustyDataContainer>>,
+    lo: f64,
+    hi: f64,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if lo > hi {
+        return false;
+    }
+    dispatch_real_dtype!(container.dtype, T => {
+        let (lo, hi) = (T::cast_from(lo), T::cast_from(hi));
+        unary_inplace::<T>(container, |value| clamp(value, lo, hi))
+    }, _ => return false);
+    true
+}
+
+/// Clamp each element of a mutable container to the range `[lo, hi]`.
+///
+/// The bounds are converted to the dtype of the container like an `as` cast.
+/// NaN values are left unchanged. The container must be mutable, strided views are supported.
+/// Returns false if `lo > hi` or if the dtype is complex.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_clamp_i64(
+    ptr: Option<Box<RustyDataContainer>>,
+    lo: i64,
+    hi: i64,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if lo > hi {
+        return false;
+    }
+    dispatch_real_dtype!(container.dtype, T => {
+        let (lo, hi) = (T::cast_from(lo), T::cast_from(hi));
+        unary_inplace::<T>(container, |value| clamp(value, lo, hi))
+    }, _ => return false);
+    true
+}
+
+/// Clamp each element of a mutable container to the range `[lo, hi]`.
+///
+/// The bounds are converted to the dtype of the container like an `as` cast.
+/// NaN values are left unchanged. The container must be mutable, strided views are supported.
+/// Returns false if `lo > hi` or if the dtype is complex.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_clamp_u64(
+    ptr: Option<Box<RustyDataContainer>>,
+    lo: u64,
+    hi: u64,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if lo > hi {
+        return false;
+    }
+    dispatch_real_dtype!(container.dtype, T => {
+        let (lo, hi) = (T::cast_from(lo), T::cast_from(hi));
+        unary_inplace::<T>(container, |value| clamp(value, lo, hi))
+    }, _ => return false);
+    true
+}
+
+/// Convert the elements of `src` with `cast` into the elements of `dst` with the same row-major index.
+fn convert<S: ConversionType + Copy, D>(
+    src: &RustyDataContainer,
+    dst: &mut RustyDataContainer,
+    cast: impl Fn(S) -> D,
+) {
+    for (index, value) in src.values::<S>().enumerate() {
+        unsafe { (dst.element_ptr(index) as *mut D).write(cast(value)) };
+    }
+}
+
+/// Copy the data of `src` into the mutable container `dst`.
+///
+/// If the dtypes differ each element is converted like an `as` cast. The elements
+/// are matched by their row-major index, so either container can be a strided view.
+/// Returns false if the number of items differs. The data of the two
+/// containers must not overlap.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_convert_into(
+    dst: Option<Box<RustyDataContainer>>,
+    src: Option<Box<RustyDataContainer>>,
+) -> bool {
+    let src = RustyDataContainer::leak(src);
+    let Some(dst) = RustyDataContainer::try_leak_mut(dst, true) else {
+        return false;
+    };
+    if dst.nitems != src.nitems {
+        return false;
+    }
+    dispatch_dtype!(src.dtype, S => dispatch_dtype!(dst.dtype, D => convert::<S, D>(src, dst, D::cast_from)));
+    true
+}
+
+fn convert_inplace<S: Copy, D: SaturatingCastFrom<S>>(data: *mut u8, nitems: usize) {
+    for index in 0..nitems {
+        unsafe {
+            let value = (data as *const S).add(index).read();
+            (data as *mut D)
+                .add(index)
+                .write(D::saturating_cast_from(value));
+        }
+    }
+}
+
+/// Conversions like an `as` cast, except that integers are clamped to the range of
+/// an integer target type instead of wrapping.
+trait SaturatingCastFrom<S>: CastFrom<S> {
+    fn saturating_cast_from(value: S) -> Self {
+        Self::cast_from(value)
+    }
+}
+
+macro_rules! impl_saturating_cast_from {
+    (@int $src:ty; $($dst:ty),*) => {
+        $(
+            impl SaturatingCastFrom<$src> for $dst {
+                fn saturating_cast_from(value: $src) -> Self {
+                    <$dst>::try_from(value).unwrap_or(if value > 0 { <$dst>::MAX } else { <$dst>::MIN })
+                }
+            }
+        )*
+    };
+    (@as $src:ty; $($dst:ty),*) => {
+        $(impl SaturatingCastFrom<$src> for $dst {})*
+    };
+    ($($src:ty),*) => {
+        $(
+            impl_saturating_cast_from!(@int $src; i8, i32, i64, u8, u32, u64, usize, i128, u128);
+            impl_saturating_cast_from!(@as $src; f32, f64, bool, Complex<f32>, Complex<f64>);
+        )*
+    };
+}
+
+// Integers saturate when converted to integers, all other pairs convert like an `as` cast.
+impl_saturating_cast_from!(i8, i32, i64, u8, u32, u64, usize, i128, u128);
+
+macro_rules! impl_as_cast_from {
+    ($($src:ty),*) => {
+        $(
+            impl_saturating_cast_from!(@as $src; i8, i32, i64, u8, u32, u64, usize, i128, u128);
+            impl_saturating_cast_from!(@as $src; f32, f64, bool, Complex<f32>, Complex<f64>);
+        )*
+    };
+}
+
+impl_as_cast_from!(f32, f64, bool, Complex<f32>, Complex<f64>);
+
+impl RustyDataContainer {
+    /// Convert the elements of an owning, mutable and contiguous container to `dtype`.
+    ///
+    /// The elements are converted like an `as` cast, except that values outside of the
+    /// range of an integer dtype saturate at its minimum or maximum, also for integer sources. If the itemsize and alignment
+    /// of the two dtypes agree the data is converted in place without allocation, otherwise
+    /// a new buffer is allocated and the old one is freed. The shape is preserved.
+    /// Returns the errors of [RustyDataContainer::check_cast_inplace] without changing
+    /// the container.
+    pub fn cast_inplace(&mut self, dtype: DTYPE) -> Result<(), ContainerError> {
+        self.check_cast_inplace(dtype)?;
+        if dtype == self.dtype {
+            return Ok(());
+        }
+        if get_itemsize(dtype) == self.itemsize && get_alignment(dtype) == self.alignment {
+            let data = self.data as *mut u8;
+            dispatch_dtype!(self.dtype, S => dispatch_dtype!(dtype, D => {
+                convert_inplace::<S, D>(data, self.nitems)
+            }));
+            self.dtype = dtype;
+        } else {
+            let mut result = RustyDataContainer::new_zeroed(self.nitems, dtype);
+            dispatch_dtype!(self.dtype, S => dispatch_dtype!(dtype, D => {
+                convert::<S, D>(self, &mut result, D::saturating_cast_from)
+            }));
+            result.set_contiguous_shape(&self.shape[..self.ndim]);
+            result.zeroize = self.zeroize;
+            result.generation = self.generation + 1;
+            // Swap instead of assigning so that the container stays registered at its address.
+            std::mem::swap(self, &mut result);
+        }
+        Ok(())
+    }
+
+    /// Whether [RustyDataContainer::cast_inplace] can convert the container to `dtype`.
+    ///
+    /// Returns [ContainerError::NotOwner], [ContainerError::NotMutable] or
+    /// [ContainerError::NotContiguous] if the container is not owning, mutable and
+    /// contiguous, and [ContainerError::NotANumber] if a float containing NaN
+    /// would be converted to an integer dtype.
+    pub fn check_cast_inplace(&self, dtype: DTYPE) -> Result<(), ContainerError> {
+        if self.is_owner != OWNERSHIP::Owner {
+            return Err(ContainerError::NotOwner);
+        }
+        if self.is_mutable != MUTABILITY::Mutable {
+            return Err(ContainerError::NotMutable);
+        }
+        if !self.is_contiguous() {
+            return Err(ContainerError::NotContiguous);
+        }
+        crate::views::ensure_no_views(self)?;
+        if dtype.is_integer() && self.contains_nan() {
+            return Err(ContainerError::NotANumber);
+        }
+        Ok(())
+    }
+
+    /// Whether a real float element or the real part of a complex element is NaN.
+    fn contains_nan(&self) -> bool {
+        if !self.dtype.is_float() && !self.dtype.is_complex() {
+            return false;
+        }
+        dispatch_dtype!(self.dtype, T => self.values::<T>().any(|value| f64::cast_from(value).is_nan()))
+    }
+}
+
+/// Convert the elements of an owning, mutable and contiguous container to the dtype
+/// with code `dtype` in place.
+///
+/// The data is reused if the itemsizes agree, otherwise it is reallocated. Values outside
+/// of the range of an integer dtype saturate at its minimum or maximum. Returns false if
+/// `dtype` is not a valid dtype code. Returns false and sets the last error if the container
+/// is not owning, mutable and contiguous, or if a NaN would be converted to an integer dtype.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_cast_inplace(
+    ptr: Option<Box<RustyDataContainer>>,
+    dtype: u32,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    let Some(dtype) = DTYPE::from_discriminant(dtype) else {
+        return false;
+    };
+    container
+        .cast_inplace(dtype)
+        .map_err(set_last_error)
+        .is_ok()
+}
+
+/// Write the common dtype of `count` containers, folded with [DTYPE::promote], to `dtype`.
+///
+/// Returns false and sets the last error if `count` is zero or if `ptrs`, one of
+/// the containers or `dtype` is null.
+///
+/// # Safety
+/// `ptrs` must point to `count` container pointers, each null or valid, and `dtype`
+/// must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_containers_unify_dtype(
+    ptrs: *const *mut RustyDataContainer,
+    count: size_t,
+    dtype: *mut DTYPE,
+) -> bool {
+    if ptrs.is_null() || dtype.is_null() {
+        set_last_error(ContainerError::NullPointer);
+        return false;
+    }
+    let ptrs = std::slice::from_raw_parts(ptrs, count);
+    if ptrs.iter().any(|ptr| ptr.is_null()) {
+        set_last_error(ContainerError::NullPointer);
+        return false;
+    }
+    let Some(common) = ptrs.iter().map(|&ptr| (*ptr).dtype).reduce(DTYPE::promote) else {
+        set_last_error(ContainerError::InvalidShape);
+        return false;
+    };
+    *dtype = common;
+    true
+}
+
+/// Convert `count` containers to the dtype with the code `dtype` in place, see
+/// [rusty_data_container_cast_inplace].
+///
+/// Returns false for an unknown dtype code. Returns false and sets the last error
+/// without changing any container if `ptrs` or one of the containers is null, or if
+/// one of them is not owning, mutable and contiguous, or contains a NaN that would be
+/// converted to an integer dtype.
+///
+/// # Safety
+/// `ptrs` must point to `count` distinct container pointers, each null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn rusty_containers_cast_all(
+    ptrs: *const *mut RustyDataContainer,
+    count: size_t,
+    dtype: u32,
+) -> bool {
+    let Some(dtype) = DTYPE::from_discriminant(dtype) else {
+        return false;
+    };
+    if count == 0 {
+        return true;
+    }
+    if ptrs.is_null() {
+        set_last_error(ContainerError::NullPointer);
+        return false;
+    }
+    let ptrs = std::slice::from_raw_parts(ptrs, count);
+    if ptrs.iter().any(|ptr| ptr.is_null()) {
+        set_last_error(ContainerError::NullPointer);
+        return false;
+    }
+    if let Err(error) = ptrs
+        .iter()
+        .try_for_each(|&ptr| (*ptr).check_cast_inplace(dtype))
+    {
+        set_last_error(error);
+        return false;
+    }
+    ptrs.iter().all(|&ptr| (*ptr).cast_inplace(dtype).is_ok())
+}
+
+/// Rounding modes for conversions from floating point to integer dtypes.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[repr(u32)]
+pub enum RoundingMode {
+    /// Round towards zero, like an `as` cast.
+    Truncate = 0,
+    /// Round to the nearest integer, ties to even.
+    HalfEven = 1,
+    /// Round towards negative infinity.
+    Floor = 2,
+    /// Round towards positive infinity.
+    Ceil = 3,
+    /// Round to the nearest integer, ties away from zero.
+    HalfAway = 4,
+}
+
+impl RoundingMode {
+    /// The rounding mode with the code `value`.
+    pub fn from_discriminant(value: u32) -> Option<RoundingMode> {
+        match value {
+            0 => Some(RoundingMode::Truncate),
+            1 => Some(RoundingMode::HalfEven),
+            2 => Some(RoundingMode::Floor),
+            3 => Some(RoundingMode::Ceil),
+            4 => Some(RoundingMode::HalfAway),
+            _ => None,
+        }
+    }
+}
+
+/// Element types that can be rounded to integral values.
+///
+/// Integers and booleans are already integral and are returned unchanged.
+/// Complex values are rounded componentwise.
+pub(crate) trait Round: Copy {
+    fn round_with(self, _mode: RoundingMode) -> Self {
+        self
+    }
+}
+
+macro_rules! impl_round_float {
+    ($($t:ty),*) => {
+        $(
+            impl Round for $t {
+                fn round_with(self, mode: RoundingMode) -> Self {
+                    match mode {
+                        RoundingMode::Truncate => self.trunc(),
+                        RoundingMode::HalfEven => self.round_ties_even(),
+                        RoundingMode::Floor => self.floor(),
+                        RoundingMode::Ceil => self.ceil(),
+                        RoundingMode::HalfAway => self.round(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_round_float!(f32, f64);
+
+impl Round for i8 {}
+impl Round for i32 {}
+impl Round for i64 {}
+impl Round for u8 {}
+impl Round for u32 {}
+impl Round for u64 {}
+impl Round for usize {}
+impl Round for i128 {}
+impl Round for u128 {}
+impl Round for bool {}
+
+impl<T: Round> Round for Complex<T> {
+    fn round_with(self, mode: RoundingMode) -> Self {
+        Complex::new(self.re.round_with(mode), self.im.round_with(mode))
+    }
+}
+
+impl RustyDataContainer {
+    /// Convert the elements to `dtype` in a new owning container, rounding as given by `mode`.
+    ///
+    /// Values are rounded before they are converted to an integer dtype, all other
+    /// conversions behave like an `as` cast. Rounded values outside of the range of
+    /// the integer dtype saturate. The shape is preserved. Returns
+    /// [ContainerError::NotANumber] if a NaN would be converted to an integer dtype.
+    pub fn cast_round(&self, dtype: DTYPE, mode: RoundingMode) -> Result<Self, ContainerError> {
+        let round = dtype.is_integer();
+        if round && self.contains_nan() {
+            return Err(ContainerError::NotANumber);
+        }
+        let mut result = RustyDataContainer::new_zeroed(self.nitems, dtype);
+        dispatch_dtype!(self.dtype, S => dispatch_dtype!(dtype, D => {
+            for (target, value) in result.typed_mut::<D>().iter_mut().zip(self.values::<S>()) {
+                *target = D::cast_from(if round { value.round_with(mode) } else { value });
+            }
+        }));
+        result.set_contiguous_shape(&self.shape[..self.ndim]);
+        Ok(result)
+    }
+}
+
+/// Convert a container to the dtype with code `dtype` with an explicit rounding mode
+/// for float to integer casts.
+///
+/// `mode` is the code of a [RoundingMode]. Returns a new owning contiguous container
+/// with the same shape. Returns null if `dtype` or `mode` is not a valid code, and
+/// returns null and sets the last error if a NaN would be converted to an integer dtype.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_cast_round(
+    ptr: Option<Box<RustyDataContainer>>,
+    dtype: u32,
+    mode: u32,
+) -> Option<Box<RustyDataContainer>> {
+    let container = RustyDataContainer::leak(ptr);
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    let mode = RoundingMode::from_discriminant(mode)?;
+    container
+        .cast_round(dtype, mode)
+        .map_err(set_last_error)
+        .ok()
+        .map(RustyDataContainer::to_box)
+}
+
+/// Basic statistics of the elements of a container.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RustyStats {
+    /// Number of elements.
+    pub count: size_t,
+    /// Sum of the elements.
+    pub sum: f64,
+    /// Arithmetic mean of the elements.
+    pub mean: f64,
+    /// Smallest element.
+    pub min: f64,
+    /// Largest element.
+    pub max: f64,
+    /// Population variance of the elements.
+    pub variance: f64,
+}
+
+fn stats<T: Copy>(values: impl Iterator<Item = T>) -> RustyStats
+where
+    f64: CastFrom<T>,
+{
+    let mut result = RustyStats {
+        count: 0,
+        sum: 0.0,
+        mean: f64::NAN,
+        min: f64::NAN,
+        max: f64::NAN,
+        variance: f64::NAN,
+    };
+    // Welford's algorithm for the running mean and variance.
+    let mut mean = 0.0;
+    let mut m2 = 0.0;
+    for value in values.map(f64::cast_from) {
+        result.count += 1;
+        result.sum += value;
+        result.min = result.min.min(value);
+        result.max = result.max.max(value);
+        let delta = value - mean;
+        mean += delta / result.count as f64;
+        m2 += delta * (value - mean);
+    }
+    if result.count > 0 {
+        result.mean = mean;
+        result.variance = m2 / result.count as f64;
+    }
+    result
+}
+
+/// Compute count, sum, mean, min, max and variance of a container in one pass.
+///
+/// All values are promoted to `f64`, complex values by their real part.
+/// Strided views are supported. For an empty container the count is 0
+/// and mean, min, max and variance are NaN.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_stats_f64(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> RustyStats {
+    let container = RustyDataContainer::leak(ptr);
+    dispatch_dtype!(container.dtype, T => stats(container.values::<T>()))
+}
+
+fn histogram<T: ConversionType + Copy>(
+    container: &RustyDataContainer,
+    counts: &mut [u64],
+    lo: f64,
+    hi: f64,
+    clamp: bool,
+) where
+    f64: CastFrom<T>,
+{
+    let nbins = counts.len();
+    for value in container.values::<T>().map(f64::cast_from) {
+        if value.is_nan() || (!clamp && (value < lo || value > hi)) {
+            continue;
+        }
+        let bin = ((value - lo) / (hi - lo) * nbins as f64).floor();
+        counts[(bin.max(0.0) as usize).min(nbins - 1)] += 1;
+    }
+}
+
+/// Count the elements of a container in `nbins` equally sized bins over `[lo, hi]`.
+///
+/// Values are promoted to `f64`, complex values by their real part. The last bin includes `hi`. Values outside of
+/// `[lo, hi]` are dropped, or counted in the first or last bin if `clamp` is true.
+/// NaN values are always dropped. Returns a new u64 container with the counts, or
+/// null if `nbins == 0` or `hi <= lo`.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_histogram_f64(
+    ptr: Option<Box<RustyDataContainer>>,
+    nbins: size_t,
+    lo: f64,
+    hi: f64,
+    clamp: bool,
+) -> Option<Box<RustyDataContainer>> {
+    let container = RustyDataContainer::leak(ptr);
+    if nbins == 0 || hi <= lo || hi.is_nan() || lo.is_nan() {
+        return None;
+    }
+    let mut counts = vec![0_u64; nbins];
+    dispatch_dtype!(container.dtype, T => histogram::<T>(container, &mut counts, lo, hi, clamp));
+    Some(RustyDataContainer::from_vec(counts).to_box())
+}
+
+/// Element types with addition and multiplication.
+///
+/// Integer arithmetic wraps on overflow. For booleans addition is logical or
+/// and multiplication is logical and, like in numpy.
+pub(crate) trait Arithmetic: Copy + Default {
+    fn add(self, other: Self) -> Self;
+    fn mul(self, other: Self) -> Self;
+    /// The complex conjugate. Real values are returned unchanged.
+    fn conj(self) -> Self {
+        self
+    }
+    /// Addition that saturates at the bounds of integer types instead of wrapping.
+    fn saturating_add(self, other: Self) -> Self {
+        self.add(other)
+    }
+}
+
+macro_rules! impl_arithmetic_float {
+    ($($t:ty),*) => {
+        $(
+            impl Arithmetic for $t {
+                fn add(self, other: Self) -> Self {
+                    self + other
+                }
+                fn mul(self, other: Self) -> Self {
+                    self * other
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_arithmetic_int {
+    ($($t:ty),*) => {
+        $(
+            impl Arithmetic for $t {
+                fn add(self, other: Self) -> Self {
+                    self.wrapping_add(other)
+                }
+                fn mul(self, other: Self) -> Self {
+                    self.wrapping_mul(other)
+                }
+                fn saturating_add(self, other: Self) -> Self {
+                    <$t>::saturating_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_arithmetic_float!(f32, f64);
+impl_arithmetic_int!(i8, i32, i64, u8, u32, u64, usize, i128, u128);
+
+impl Arithmetic for bool {
+    fn add(self, other: Self) -> Self {
+        self || other
+    }
+    fn mul(self, other: Self) -> Self {
+        self && other
+    }
+}
+
+impl<T: Arithmetic + std::ops::Neg<Output = T> + std::ops::Sub<Output = T>> Arithmetic
+    for Complex<T>
+{
+    fn add(self, other: Self) -> Self {
+        Complex::new(self.re.add(other.re), self.im.add(other.im))
+    }
+    fn mul(self, other: Self) -> Self {
+        Complex::new(
+            self.re.mul(other.re) - self.im.mul(other.im),
+            self.re.mul(other.im).add(self.im.mul(other.re)),
+        )
+    }
+    fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+}
+
+fn binary_into<T: ConversionType + Arithmetic>(
+    dst: &mut RustyDataContainer,
+    a: &RustyDataContainer,
+    b: &RustyDataContainer,
+    op: fn(T, T) -> T,
+) {
+    // A length-1 operand is broadcast against all elements.
+    let operand = |container: &RustyDataContainer, index: usize| {
+        container.element::<T>(if container.nitems == 1 { 0 } else { index })
+    };
+    // Writing through the element pointers supports strided destinations.
+    for index in 0..dst.nitems {
+        let value = op(operand(a, index), operand(b, index));
+        unsafe { (dst.element_ptr(index) as *mut T).write(value) };
+    }
+}
+
+/// The elementwise operations of [binary_into_dispatch].
+#[derive(Clone, Copy)]
+enum BinaryOp {
+    Add,
+    Mul,
+    SaturatingAdd,
+}
+
+fn binary_into_dispatch(
+    dst: Option<Box<RustyDataContainer>>,
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+    op: BinaryOp,
+) -> bool {
+    let a = RustyDataContainer::leak(a);
+    let b = RustyDataContainer::leak(b);
+    let Some(dst) = RustyDataContainer::try_leak_mut(dst, true) else {
+        return false;
+    };
+    if a.dtype != dst.dtype || b.dtype != dst.dtype {
+        return false;
+    }
+    if matches!(op, BinaryOp::SaturatingAdd) && !dst.dtype.is_integer() {
+        return false;
+    }
+    let broadcasts = |nitems: usize| nitems == dst.nitems || nitems == 1;
+    if !broadcasts(a.nitems) || !broadcasts(b.nitems) {
+        return false;
+    }
+    dispatch_dtype!(dst.dtype, T => binary_into::<T>(
+        dst,
+        a,
+        b,
+        match op {
+            BinaryOp::Add => T::add,
+            BinaryOp::Mul => T::mul,
+            BinaryOp::SaturatingAdd => T::saturating_add,
+        },
+    ));
+    true
+}
+
+/// Store the elementwise sum of `a` and `b` in the mutable container `dst`.
+///
+/// All three containers must have the same dtype, and `a` and `b` must have the
+/// number of items of `dst` or a single item, which is broadcast to all elements.
+/// Otherwise false is returned. Integers wrap on overflow. The data of `dst` must not overlap
+/// with the data of `a` or `b`.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_add_into(
+    dst: Option<Box<RustyDataContainer>>,
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> bool {
+    binary_into_dispatch(dst, a, b, BinaryOp::Add)
+}
+
+/// Store the elementwise product of `a` and `b` in the mutable container `dst`.
+///
+/// Complex values are multiplied as complex numbers. All three containers must
+/// have the same dtype, and `a` and `b` must have the number of items of `dst` or
+/// a single item, which is broadcast to all elements. Otherwise false is returned.
+/// Integers wrap on overflow. The data of `dst` must not overlap with the data of
+/// `a` or `b`.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_mul_into(
+    dst: Option<Box<RustyDataContainer>>,
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> bool {
+    binary_into_dispatch(dst, a, b, BinaryOp::Mul)
+}
+
+/// Store the elementwise sum of `a` and `b` in the mutable container `dst`, saturating on overflow.
+///
+/// Sums outside of the range of the integer dtype are clamped to its minimum or
+/// maximum. The containers must have the same integer dtype, and `a` and `b` must
+/// have the number of items of `dst` or a single item, which is broadcast to all
+/// elements. Otherwise false is returned. The data of `dst` must not overlap with
+/// the data of `a` or `b`.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_add_into_saturating(
+    dst: Option<Box<RustyDataContainer>>,
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+) -> bool {
+    binary_into_dispatch(dst, a, b, BinaryOp::SaturatingAdd)
+}
+
+fn accumulate<T: ConversionType + Arithmetic>(
+    acc: &mut RustyDataContainer,
+    src: &RustyDataContainer,
+) {
+    for (index, value) in src.values::<T>().enumerate() {
+        let target = acc.element_ptr(index) as *mut T;
+        unsafe { target.write(target.read().add(value)) };
+    }
+}
+
+/// Add the elements of `src` to the elements of the mutable container `acc` in place.
+///
+/// This is the mutating counterpart of [rusty_data_container_add_into] for folding
+/// partial results. Both containers must have the same dtype and number of items,
+/// otherwise false is returned. Integers wrap on overflow. The data of the two
+/// containers must not overlap.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_add_accumulate(
+    acc: Option<Box<RustyDataContainer>>,
+    src: Option<Box<RustyDataContainer>>,
+) -> bool {
+    let src = RustyDataContainer::leak(src);
+    let Some(acc) = RustyDataContainer::try_leak_mut(acc, true) else {
+        return false;
+    };
+    if acc.dtype != src.dtype || acc.nitems != src.nitems {
+        return false;
+    }
+    dispatch_dtype!(acc.dtype, T => accumulate::<T>(acc, src));
+    true
+}
+
+/// Element types with a negation and an absolute value of the same type.
+///
+/// Integers wrap on overflow, so both operations return the minimum of a signed
+/// integer type unchanged. Unsigned and boolean values are returned unchanged, and
+/// complex values are negated componentwise but have no absolute value of their type.
+pub(crate) trait Negate: Copy {
+    fn neg(self) -> Self {
+        self
+    }
+    fn abs(self) -> Self {
+        self
+    }
+}
+
+macro_rules! impl_negate_float {
+    ($($t:ty),*) => {
+        $(
+            impl Negate for $t {
+                fn neg(self) -> Self {
+                    -self
+                }
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_negate_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Negate for $t {
+                fn neg(self) -> Self {
+                    self.wrapping_neg()
+                }
+                fn abs(self) -> Self {
+                    self.wrapping_abs()
+                }
+            }
+        )*
+    };
+}
+
+impl_negate_float!(f32, f64);
+impl_negate_signed!(i8, i32, i64, i128);
+
+impl Negate for u8 {}
+impl Negate for u32 {}
+impl Negate for u64 {}
+impl Negate for usize {}
+impl Negate for u128 {}
+impl Negate for bool {}
+
+impl<T: Negate> Negate for Complex<T> {
+    fn neg(self) -> Self {
+        Complex::new(self.re.neg(), self.im.neg())
+    }
+}
+
+fn unary_inplace<T: ConversionType>(container: &mut RustyDataContainer, op: impl Fn(T) -> T) {
+    for index in 0..container.nitems {
+        let element = container.element_ptr(index) as *mut T;
+        unsafe { element.write(op(element.read())) };
+    }
+}
+
+/// Replace the elements of a mutable container by their absolute values in place.
+///
+/// Unsigned integers are unchanged and signed integers wrap on overflow. Floats
+/// follow IEEE 754, so `-0.0` becomes `0.0` and the sign of NaN is cleared.
+/// Returns false for boolean and complex containers.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_abs(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if container.dtype == DTYPE::Bool {
+        return false;
+    }
+    dispatch_real_dtype!(container.dtype, T => unary_inplace::<T>(container, <T as Negate>::abs), _ => return false);
+    true
+}
+
+/// Negate the elements of a mutable container in place.
+///
+/// Signed integers wrap on overflow. Floats follow IEEE 754, so `0.0` becomes
+/// `-0.0` and the sign of NaN is flipped. Complex values are negated componentwise.
+/// Returns false for unsigned and boolean containers, which cannot be negated.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_neg(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    let dtype = container.dtype;
+    if !(dtype.is_signed() || dtype.is_float() || dtype.is_complex()) {
+        return false;
+    }
+    dispatch_dtype!(dtype, T => unary_inplace::<T>(container, <T as Negate>::neg));
+    true
+}
+
+fn round_inplace(ptr: Option<Box<RustyDataContainer>>, mode: RoundingMode) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    match container.dtype {
+        DTYPE::Float32 => unary_inplace::<f32>(container, |value| value.round_with(mode)),
+        DTYPE::Float64 => unary_inplace::<f64>(container, |value| value.round_with(mode)),
+        _ => return false,
+    }
+    true
+}
+
+/// Round the elements of a mutable float container to the nearest integer in place.
+///
+/// Ties are rounded away from zero, so `-1.5` becomes `-2.0`. Returns false for
+/// dtypes other than f32 and f64.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_round(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    round_inplace(ptr, RoundingMode::HalfAway)
+}
+
+/// Round the elements of a mutable float container towards negative infinity in place.
+///
+/// Returns false for dtypes other than f32 and f64.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_floor(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    round_inplace(ptr, RoundingMode::Floor)
+}
+
+/// Round the elements of a mutable float container towards positive infinity in place.
+///
+/// Returns false for dtypes other than f32 and f64.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_ceil(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    round_inplace(ptr, RoundingMode::Ceil)
+}
+
+/// Round the elements of a mutable float container towards zero in place.
+///
+/// Returns false for dtypes other than f32 and f64.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_trunc(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    round_inplace(ptr, RoundingMode::Truncate)
+}
+
+/// The number of elements per rayon task of [rusty_data_container_fill_f64_parallel].
+#[cfg(feature = "rayon")]
+const PARALLEL_FILL_CHUNK: usize = 1 << 18;
+
+/// Set all elements of `data` to `value`, split into chunks that are filled on the rayon thread pool.
+#[cfg(feature = "rayon")]
+fn fill_parallel<T: Copy + Send + Sync>(data: &mut [T], value: T) {
+    use rayon::prelude::*;
+    data.par_chunks_mut(PARALLEL_FILL_CHUNK)
+        .for_each(|part| part.fill(value));
+}
+
+/// Set all elements of `data` to `value` on the calling thread.
+#[cfg(not(feature = "rayon"))]
+fn fill_parallel<T: Copy>(data: &mut [T], value: T) {
+    data.fill(value);
+}
+
+/// Set all elements of a mutable f64 container to `value`, using several threads for large containers.
+///
+/// With the `rayon` feature contiguous containers are split into chunks of 2^18 elements
+/// that are filled on the global rayon thread pool. Without it, and for strided views,
+/// the container is filled serially by the calling thread. Returns false if the dtype
+/// is not f64.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_fill_f64_parallel(
+    ptr: Option<Box<RustyDataContainer>>,
+    value: f64,
+) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    if container.dtype != DTYPE::Float64 {
+        return false;
+    }
+    if container.is_contiguous() {
+        fill_parallel(container.typed_mut::<f64>(), value);
+    } else {
+        unary_inplace::<f64>(container, |_| value);
+    }
+    true
+}
+
+/// Sum the elements of a container.
+///
+/// Returns a new container with a single element of the same dtype.
+/// Integers wrap on overflow and the sum of an empty container is zero.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_sum(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> Box<RustyDataContainer> {
+    let container = RustyDataContainer::leak(ptr);
+    dispatch_dtype!(container.dtype, T => {
+        let sum = container.values::<T>().fold(T::default(), T::add);
+        RustyDataContainer::from_vec(vec![sum]).to_box()
+    })
+}
+
+/// Replace each element of a mutable container by its complex conjugate.
+///
+/// Containers with real dtypes are left unchanged.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_conj(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    dispatch_dtype!(container.dtype, T => unary_inplace::<T>(container, T::conj));
+    true
+}
+
+/// The index of the first element `x` for which `better(x, current)` holds against all
+/// previous candidates, skipping unordered values such as NaN.
+fn arg_extreme<T: ConversionType + Copy + PartialOrd>(
+    container: &RustyDataContainer,
+    better: fn(&T, &T) -> bool,
+) -> Option<usize> {
+    let mut result: Option<(usize, T)> = None;
+    for (index, value) in container.values::<T>().enumerate() {
+        if value.partial_cmp(&value).is_none() {
+            continue;
+        }
+        if result.is_none_or(|(_, current)| better(&value, &current)) {
+            result = Some((index, value));
+        }
+    }
+    result.map(|(index, _)| index)
+}
+
+fn arg_extreme_dispatch(ptr: Option<Box<RustyDataContainer>>, max: bool) -> size_t {
+    let container = RustyDataContainer::leak(ptr);
+    let result = dispatch_real_dtype!(container.dtype, T => {
+        arg_extreme::<T>(container, if max { T::gt } else { T::lt })
+            .ok_or(ContainerError::Empty)
+    }, _ => Err(ContainerError::DtypeMismatch));
+    result.unwrap_or_else(|error| {
+        set_last_error(error);
+        size_t::MAX
+    })
+}
+
+/// Get the index of the smallest element of a container, skipping NaN values.
+///
+/// The first index is returned for ties. If the container has no element that is
+/// not NaN, or if its dtype is complex, `SIZE_MAX` is returned and the last error
+/// is set.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_argmin_f64(ptr: Option<Box<RustyDataContainer>>) -> size_t {
+    arg_extreme_dispatch(ptr, false)
+}
+
+/// Get the index of the largest element of a container, skipping NaN values.
+///
+/// The first index is returned for ties. If the container has no element that is
+/// not NaN, or if its dtype is complex, `SIZE_MAX` is returned and the last error
+/// is set.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_argmax_f64(ptr: Option<Box<RustyDataContainer>>) -> size_t {
+    arg_extreme_dispatch(ptr, true)
+}
+
+/// Count the elements of a container that are not zero.
+///
+/// NaN values are counted as nonzero. A complex value is nonzero if either
+/// part is nonzero.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_count_nonzero(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> size_t {
+    let container = RustyDataContainer::leak(ptr);
+    dispatch_dtype!(container.dtype, T => container
+        .values::<T>()
+        .filter(|value| *value != T::default())
+        .count())
+}
+
+/// Count the elements of a container that are exactly equal to `value`.
+///
+/// Returns 0 if `value` cannot be represented exactly in the dtype of the
+/// container, and NaN never matches.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_count_value_f64(
+    ptr: Option<Box<RustyDataContainer>>,
+    value: f64,
+) -> size_t {
+    let container = RustyDataContainer::leak(ptr);
+    dispatch_dtype!(container.dtype, T => {
+        let target = T::cast_from(value);
+        if f64::cast_from(target) != value {
+            return 0;
+        }
+        container.values::<T>().filter(|item| *item == target).count()
+    })
+}
+
+impl RustyDataContainer {
+    /// Compare the values of two containers of possibly different dtypes.
+    ///
+    /// Both containers are promoted to complex f64 values, and two values are equal if
+    /// the real and imaginary parts differ by at most `tol`. NaN is not equal to any
+    /// value. Returns false if the number of items differs, the shapes are not compared.
+    pub fn value_eq(&self, other: &RustyDataContainer, tol: f64) -> bool {
+        if self.nitems != other.nitems {
+            return false;
+        }
+        let a: Vec<Complex<f64>> = dispatch_dtype!(self.dtype, T => self
+            .values::<T>()
+            .map(Complex::<f64>::cast_from)
+            .collect());
+        dispatch_dtype!(other.dtype, T => other
+            .values::<T>()
+            .map(Complex::<f64>::cast_from)
+            .zip(a)
+            .all(|(x, y)| (x.re - y.re).abs() <= tol && (x.im - y.im).abs() <= tol))
+    }
+}
+
+/// Compare the values of two containers of possibly different dtypes up to `tol`.
+///
+/// The values are promoted to `f64`, complex values are compared in both parts.
+/// Returns false if the number of items differs or if any value differs by more
+/// than `tol`. NaN is not equal to any value.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_value_eq(
+    a: Option<Box<RustyDataContainer>>,
+    b: Option<Box<RustyDataContainer>>,
+    tol: f64,
+) -> bool {
+    RustyDataContainer::leak(a).value_eq(RustyDataContainer::leak(b), tol)
+}
+
+/// Atomically replace element `index` of an i64 or u64 container by `new` if it equals `expected`.
+///
+/// Returns the previous value of the element, so the swap happened if and only if it
+/// equals `expected`. u64 values are reinterpreted as i64. Other threads or processes
+/// must only access the element atomically while it is in use as a flag. Returns
+/// `!expected` and sets the last error if the container is not mutable or is borrowed
+/// by a C caller, if the dtype is not i64 or u64, if `index` is out of range or if the
+/// element is not aligned for atomic access. A failed call therefore never looks like
+/// a successful swap, and C callers that need to tell it apart from a lost race clear
+/// the last error with [crate::rusty_clear_last_error] before the call.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_cas_i64(
+    ptr: Option<Box<RustyDataContainer>>,
+    index: size_t,
+    expected: i64,
+    new: i64,
+) -> i64 {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return !expected;
+    };
+    let error = if !matches!(container.dtype, DTYPE::Int64 | DTYPE::Unsigned64) {
+        Some(ContainerError::DtypeMismatch)
+    } else if index >= container.nitems {
+        Some(ContainerError::OutOfBounds)
+    } else if !(container.element_ptr(index) as usize).is_multiple_of(align_of::<AtomicI64>()) {
+        Some(ContainerError::InvalidAlignment)
+    } else {
+        None
+    };
+    if let Some(error) = error {
+        set_last_error(error);
+        return !expected;
+    }
+    let atomic = unsafe { AtomicI64::from_ptr(container.element_ptr(index) as *mut i64) };
+    let (Ok(previous) | Err(previous)) =
+        atomic.compare_exchange(expected, new, Ordering::SeqCst, Ordering::SeqCst);
+    previous
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::Complex64;
+
+    #[test]
+    fn clamp_bounds_elements() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![-5_i32, 0, 10]));
+        assert!(rusty_data_container_clamp_f64(handle(ptr), 0.0, 5.0));
+        assert_eq!(
+            RustyDataContainer::leak(handle(ptr)).typed::<i32>(),
+            [0, 0, 5]
+        );
+        assert!(!rusty_data_container_clamp_i64(handle(ptr), 5, 0));
+        destroy(ptr);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![-5.0_f64, f64::NAN, 10.0]));
+        assert!(rusty_data_container_clamp_f64(handle(ptr), 0.0, 5.0));
+        let values = RustyDataContainer::leak(handle(ptr)).typed::<f64>();
+        assert_eq!((values[0], values[2]), (0.0, 5.0));
+        assert!(values[1].is_nan());
+        destroy(ptr);
+    }
+
+    #[test]
+    fn convert_into_promotes_values() {
+        let src = boxed(RustyDataContainer::from_vec(vec![1_i32, -2, 3]));
+        let dst = boxed(RustyDataContainer::from_vec(vec![0.0_f64; 3]));
+        assert!(rusty_data_container_convert_into(handle(dst), handle(src)));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<f64>(),
+            [1.0, -2.0, 3.0]
+        );
+        let short = boxed(RustyDataContainer::from_vec(vec![0.0_f64; 2]));
+        assert!(!rusty_data_container_convert_into(
+            handle(short),
+            handle(src)
+        ));
+        destroy(src);
+        destroy(dst);
+        destroy(short);
+    }
+
+    #[test]
+    fn convert_into_strided_containers() {
+        let values = [1_u8, 2, 3];
+        let src = boxed(RustyDataContainer::from_slice(&values).reverse_view());
+        let mut data = [0_i64; 6];
+        let dst = boxed(
+            RustyDataContainer::from_slice_mut(&mut data)
+                .slice_step(0, 6, 2)
+                .unwrap(),
+        );
+        assert!(rusty_data_container_convert_into(handle(dst), handle(src)));
+        destroy(src);
+        destroy(dst);
+        assert_eq!(data, [3, 0, 2, 0, 1, 0]);
+    }
+
+    #[test]
+    fn stats_of_known_values() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![
+            2_i32, 4, 4, 4, 5, 5, 7, 9,
+        ]));
+        let stats = rusty_data_container_stats_f64(handle(ptr));
+        destroy(ptr);
+        assert_eq!(stats.count, 8);
+        assert_eq!(stats.sum, 40.0);
+        assert_eq!(stats.mean, 5.0);
+        assert_eq!(stats.min, 2.0);
+        assert_eq!(stats.max, 9.0);
+        assert_eq!(stats.variance, 4.0);
+
+        let data = [1.0_f32, 2.0, 3.0];
+        let ptr = boxed(
+            RustyDataContainer::from_slice(&data)
+                .slice_step(0, 3, 2)
+                .unwrap(),
+        );
+        let stats = rusty_data_container_stats_f64(handle(ptr));
+        destroy(ptr);
+        assert_eq!((stats.count, stats.sum, stats.max), (2, 4.0, 3.0));
+
+        let ptr = boxed(RustyDataContainer::from_vec(Vec::<f64>::new()));
+        let stats = rusty_data_container_stats_f64(handle(ptr));
+        destroy(ptr);
+        assert_eq!((stats.count, stats.sum), (0, 0.0));
+        assert!(stats.mean.is_nan() && stats.min.is_nan() && stats.max.is_nan());
+    }
+
+    #[test]
+    fn clamp_strided_view() {
+        let mut data = [1_u64, 20, 3, 40];
+        let view = RustyDataContainer::from_slice_mut(&mut data)
+            .slice_step(1, 4, 2)
+            .unwrap();
+        let ptr = boxed(view);
+        assert!(rusty_data_container_clamp_u64(handle(ptr), 0, 10));
+        destroy(ptr);
+        assert_eq!(data, [1, 10, 3, 10]);
+    }
+
+    #[test]
+    fn histogram_counts_bins() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0_i32, 1, 2, 3, 4]));
+        let counts = rusty_data_container_histogram_f64(handle(ptr), 2, 0.0, 4.0, false).unwrap();
+        assert_eq!(counts.typed::<u64>(), [2, 3]);
+        let counts = rusty_data_container_histogram_f64(handle(ptr), 2, 1.0, 3.0, false).unwrap();
+        assert_eq!(counts.typed::<u64>(), [1, 2]);
+        let counts = rusty_data_container_histogram_f64(handle(ptr), 2, 1.0, 3.0, true).unwrap();
+        assert_eq!(counts.typed::<u64>(), [2, 3]);
+        assert!(rusty_data_container_histogram_f64(handle(ptr), 0, 0.0, 4.0, false).is_none());
+        assert!(rusty_data_container_histogram_f64(handle(ptr), 2, 4.0, 4.0, false).is_none());
+        destroy(ptr);
+    }
+
+    #[test]
+    fn complex_products_sums_and_conjugates() {
+        let a = boxed(RustyDataContainer::from_vec(vec![
+            Complex64::new(1.0, 2.0),
+            Complex64::new(0.0, 1.0),
+        ]));
+        let b = boxed(RustyDataContainer::from_vec(vec![
+            Complex64::new(3.0, -1.0),
+            Complex64::new(0.0, 1.0),
+        ]));
+        let dst = boxed(RustyDataContainer::new_zeroed(2, DTYPE::Complex64));
+        assert!(rusty_data_container_mul_into(
+            handle(dst),
+            handle(a),
+            handle(b)
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<Complex64>(),
+            [Complex64::new(5.0, 5.0), Complex64::new(-1.0, 0.0)]
+        );
+        let sum = rusty_data_container_sum(handle(dst));
+        assert_eq!(sum.typed::<Complex64>(), [Complex64::new(4.0, 5.0)]);
+
+        let view = boxed(RustyDataContainer::leak(handle(a)).reverse_view());
+        rusty_data_container_conj(handle(view));
+        assert_eq!(
+            RustyDataContainer::leak(handle(a)).typed::<Complex64>(),
+            [Complex64::new(1.0, -2.0), Complex64::new(0.0, -1.0)]
+        );
+        for ptr in [view, a, b, dst] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn cast_inplace_reuses_the_buffer() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![-1_i32, 0, 7]));
+        let data = RustyDataContainer::leak(handle(ptr)).data;
+        assert!(rusty_data_container_cast_inplace(
+            handle(ptr),
+            DTYPE::Unsigned32.discriminant()
+        ));
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(container.data, data);
+        assert_eq!(container.typed::<u32>(), [0, 0, 7]);
+        assert!(rusty_data_container_cast_inplace(
+            handle(ptr),
+            DTYPE::Float64.discriminant()
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(ptr)).typed::<f64>(),
+            [0.0, 0.0, 7.0]
+        );
+        assert!(!rusty_data_container_cast_inplace(handle(ptr), 14));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn cast_inplace_reports_nan_and_saturates() {
+        let mut container = RustyDataContainer::from_vec(vec![1e10_f64, -1e10, 2.5]);
+        assert_eq!(container.cast_inplace(DTYPE::Int32), Ok(()));
+        assert_eq!(container.typed::<i32>(), [i32::MAX, i32::MIN, 2]);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f32, f32::NAN]));
+        assert!(!rusty_data_container_cast_inplace(
+            handle(ptr),
+            DTYPE::Int64.discriminant()
+        ));
+        assert_eq!(crate::last_error(), Some(ContainerError::NotANumber));
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).dtype, DTYPE::Float32);
+        assert!(rusty_data_container_cast_inplace(
+            handle(ptr),
+            DTYPE::Float64.discriminant()
+        ));
+        let view = boxed(RustyDataContainer::leak(handle(ptr)).view());
+        assert!(!rusty_data_container_cast_inplace(
+            handle(view),
+            DTYPE::Float32.discriminant()
+        ));
+        assert_eq!(crate::last_error(), Some(ContainerError::NotOwner));
+        destroy(view);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn add_into_broadcasts_length_one_operands() {
+        let dst = boxed(RustyDataContainer::from_vec(vec![0.0_f64; 4]));
+        let a = boxed(RustyDataContainer::from_vec(vec![5.0_f64]));
+        let b = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0, 4.0]));
+        assert!(rusty_data_container_add_into(
+            handle(dst),
+            handle(a),
+            handle(b)
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<f64>(),
+            [6.0, 7.0, 8.0, 9.0]
+        );
+        assert!(rusty_data_container_mul_into(
+            handle(dst),
+            handle(b),
+            handle(a)
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<f64>(),
+            [5.0, 10.0, 15.0, 20.0]
+        );
+        let short = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0]));
+        assert!(!rusty_data_container_add_into(
+            handle(dst),
+            handle(short),
+            handle(b)
+        ));
+        for ptr in [dst, a, b, short] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn binary_into_writes_strided_destinations() {
+        let parent = boxed(RustyDataContainer::from_vec(vec![0_i32; 6]));
+        let dst = boxed(
+            RustyDataContainer::leak(handle(parent))
+                .slice_step(0, 6, 2)
+                .unwrap(),
+        );
+        let a = boxed(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        let b = boxed(RustyDataContainer::from_vec(vec![10_i32]));
+        assert!(rusty_data_container_add_into(
+            handle(dst),
+            handle(a),
+            handle(b)
+        ));
+        assert!(rusty_data_container_add_accumulate(handle(dst), handle(a)));
+        assert_eq!(
+            RustyDataContainer::leak(handle(parent)).typed::<i32>(),
+            [12, 0, 14, 0, 16, 0]
+        );
+        for ptr in [dst, a, b, parent] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn add_accumulate_folds_partial_results() {
+        let acc = boxed(RustyDataContainer::from_vec(vec![0.0_f64; 3]));
+        let parts = [
+            vec![1.0_f64, 2.0, 3.0],
+            vec![0.5, 0.5, 0.5],
+            vec![-1.0, 10.0, 100.0],
+        ]
+        .map(|values| boxed(RustyDataContainer::from_vec(values)));
+        for &part in &parts {
+            assert!(rusty_data_container_add_accumulate(
+                handle(acc),
+                handle(part)
+            ));
+        }
+        assert_eq!(
+            RustyDataContainer::leak(handle(acc)).typed::<f64>(),
+            [0.5, 12.5, 103.5]
+        );
+        let short = boxed(RustyDataContainer::from_vec(vec![1.0_f64]));
+        let ints = boxed(RustyDataContainer::from_vec(vec![1_i64, 2, 3]));
+        assert!(!rusty_data_container_add_accumulate(
+            handle(acc),
+            handle(short)
+        ));
+        assert!(!rusty_data_container_add_accumulate(
+            handle(acc),
+            handle(ints)
+        ));
+        for ptr in parts.into_iter().chain([acc, short, ints]) {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn argmin_argmax_skip_nan() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![
+            3.0_f64,
+            f64::NAN,
+            5.0,
+            1.0,
+        ]));
+        assert_eq!(rusty_data_container_argmax_f64(handle(ptr)), 2);
+        assert_eq!(rusty_data_container_argmin_f64(handle(ptr)), 3);
+        destroy(ptr);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![4_i32, -2, 7, -2]));
+        assert_eq!(rusty_data_container_argmin_f64(handle(ptr)), 1);
+        assert_eq!(rusty_data_container_argmax_f64(handle(ptr)), 2);
+        destroy(ptr);
+
+        for values in [vec![], vec![f64::NAN]] {
+            let ptr = boxed(RustyDataContainer::from_vec(values));
+            assert_eq!(rusty_data_container_argmax_f64(handle(ptr)), size_t::MAX);
+            assert_eq!(crate::last_error(), Some(ContainerError::Empty));
+            destroy(ptr);
+        }
+        let ptr = boxed(RustyDataContainer::from_vec(vec![Complex64::new(1.0, 0.0)]));
+        assert_eq!(rusty_data_container_argmin_f64(handle(ptr)), size_t::MAX);
+        assert_eq!(crate::last_error(), Some(ContainerError::DtypeMismatch));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn cast_round_with_each_mode() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.5_f64, 2.5, -1.5]));
+        let expected = [
+            (RoundingMode::Truncate, [1, 2, -1]),
+            (RoundingMode::HalfEven, [2, 2, -2]),
+            (RoundingMode::Floor, [1, 2, -2]),
+            (RoundingMode::Ceil, [2, 3, -1]),
+            (RoundingMode::HalfAway, [2, 3, -2]),
+        ];
+        for (mode, values) in expected {
+            let result =
+                rusty_data_container_cast_round(handle(ptr), DTYPE::Int32 as u32, mode as u32)
+                    .unwrap();
+            assert_eq!(result.typed::<i32>(), values, "{mode:?}");
+        }
+        let floats =
+            rusty_data_container_cast_round(handle(ptr), DTYPE::Float32 as u32, 2).unwrap();
+        assert_eq!(floats.typed::<f32>(), [1.5, 2.5, -1.5]);
+        assert!(rusty_data_container_cast_round(handle(ptr), DTYPE::Int32 as u32, 5).is_none());
+        assert!(rusty_data_container_cast_round(handle(ptr), 14, 0).is_none());
+        destroy(ptr);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1.0_f32, f32::NAN]));
+        assert!(rusty_data_container_cast_round(handle(ptr), DTYPE::Int64 as u32, 0).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::NotANumber));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn count_nonzeros_and_values() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0_i32, 1, 0, 2]));
+        assert_eq!(rusty_data_container_count_nonzero(handle(ptr)), 2);
+        assert_eq!(rusty_data_container_count_value_f64(handle(ptr), 0.0), 2);
+        assert_eq!(rusty_data_container_count_value_f64(handle(ptr), 0.5), 0);
+        destroy(ptr);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![
+            0.0_f64,
+            f64::NAN,
+            -0.0,
+            2.5,
+        ]));
+        assert_eq!(rusty_data_container_count_nonzero(handle(ptr)), 2);
+        assert_eq!(
+            rusty_data_container_count_value_f64(handle(ptr), f64::NAN),
+            0
+        );
+        assert_eq!(rusty_data_container_count_value_f64(handle(ptr), 2.5), 1);
+        destroy(ptr);
+    }
+
+    #[test]
+    fn value_eq_across_dtypes() {
+        let ints = boxed(RustyDataContainer::from_vec(vec![1_i32, 2, 3]));
+        let floats = boxed(RustyDataContainer::from_vec(vec![1.0_f64, 2.0, 3.0]));
+        let close = boxed(RustyDataContainer::from_vec(vec![1.0_f32, 2.0, 3.001]));
+        let short = boxed(RustyDataContainer::from_vec(vec![1_u8, 2]));
+        assert!(rusty_data_container_value_eq(
+            handle(ints),
+            handle(floats),
+            0.0
+        ));
+        assert!(!rusty_data_container_value_eq(
+            handle(ints),
+            handle(close),
+            0.0
+        ));
+        assert!(rusty_data_container_value_eq(
+            handle(ints),
+            handle(close),
+            1e-2
+        ));
+        assert!(!rusty_data_container_value_eq(
+            handle(ints),
+            handle(short),
+            1.0
+        ));
+        for ptr in [ints, floats, close, short] {
+            destroy(ptr);
+        }
+        let nan = RustyDataContainer::from_vec(vec![f64::NAN]);
+        assert!(!nan.value_eq(&nan, f64::INFINITY));
+    }
+
+    #[test]
+    fn cas_increments_a_counter_from_threads() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0_i64, 0]));
+        let address = ptr as usize;
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(move || {
+                    let ptr = address as *mut RustyDataContainer;
+                    for _ in 0..1000 {
+                        // Retry with the observed value until the swap succeeds.
+                        let mut current = 0;
+                        loop {
+                            let previous =
+                                rusty_data_container_cas_i64(handle(ptr), 1, current, current + 1);
+                            if previous == current {
+                                break;
+                            }
+                            current = previous;
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(
+            RustyDataContainer::leak(handle(ptr)).typed::<i64>(),
+            [0, 4000]
+        );
+
+        assert_eq!(rusty_data_container_cas_i64(handle(ptr), 2, 0, 1), !0);
+        assert_eq!(crate::last_error(), Some(ContainerError::OutOfBounds));
+        destroy(ptr);
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0_i32]));
+        assert_eq!(rusty_data_container_cas_i64(handle(ptr), 0, 5, 1), !5);
+        assert_eq!(crate::last_error(), Some(ContainerError::DtypeMismatch));
+        destroy(ptr);
+        assert_eq!(rusty_data_container_cas_i64(None, 0, 0, 1), !0);
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+    }
+
+    #[test]
+    fn saturating_add_clamps_integers() {
+        let dst = boxed(RustyDataContainer::from_vec(vec![0_u8; 3]));
+        let a = boxed(RustyDataContainer::from_vec(vec![200_u8, 100, 255]));
+        let b = boxed(RustyDataContainer::from_vec(vec![100_u8, 100, 1]));
+        assert!(rusty_data_container_add_into_saturating(
+            handle(dst),
+            handle(a),
+            handle(b)
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<u8>(),
+            [255, 200, 255]
+        );
+        assert!(rusty_data_container_add_into(
+            handle(dst),
+            handle(a),
+            handle(b)
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<u8>(),
+            [44, 200, 0]
+        );
+        for ptr in [dst, a, b] {
+            destroy(ptr);
+        }
+
+        let dst = boxed(RustyDataContainer::from_vec(vec![0_i8; 2]));
+        let a = boxed(RustyDataContainer::from_vec(vec![-100_i8, 100]));
+        assert!(rusty_data_container_add_into_saturating(
+            handle(dst),
+            handle(a),
+            handle(a)
+        ));
+        assert_eq!(
+            RustyDataContainer::leak(handle(dst)).typed::<i8>(),
+            [-128, 127]
+        );
+        let floats = boxed(RustyDataContainer::from_vec(vec![1.0_f64]));
+        assert!(!rusty_data_container_add_into_saturating(
+            handle(floats),
+            handle(floats),
+            handle(floats)
+        ));
+        for ptr in [dst, a, floats] {
+            destroy(ptr);
+        }
+    }
+
+    #[test]
+    fn unify_and_cast_all_promote_mixed_dtypes_to_f64() {
+        let ptrs = [
+            boxed(RustyDataContainer::from_vec(vec![-1i32, 2])),
+            boxed(RustyDataContainer::from_vec(vec![0.5f32])),
+            boxed(RustyDataContainer::from_vec(vec![255u8])),
+        ];
+        let mut dtype = DTYPE::Bool;
+        assert!(unsafe { rusty_containers_unify_dtype(ptrs.as_ptr(), 3, &mut dtype) });
+        assert_eq!(dtype, DTYPE::Float64);
+
+        assert!(unsafe { rusty_containers_cast_all(ptrs.as_ptr(), 3, dtype as u32) });
+        let get = |ptr, index| RustyDataContainer::leak(handle(ptr)).get::<f64>(index);
+        assert_eq!(get(ptrs[0], 0), Some(-1.0));
+        assert_eq!(get(ptrs[1], 0), Some(0.5));
+        assert_eq!(get(ptrs[2], 0), Some(255.0));
+        ptrs.into_iter().for_each(destroy);
+    }
+
+    #[test]
+    fn unify_dtype_reports_empty_and_null_input() {
+        let mut dtype = DTYPE::Bool;
+        let ptrs = [std::ptr::null_mut()];
+        assert!(!unsafe { rusty_containers_unify_dtype(ptrs.as_ptr(), 0, &mut dtype) });
+        assert_eq!(crate::last_error(), Some(ContainerError::InvalidShape));
+        assert!(!unsafe { rusty_containers_unify_dtype(ptrs.as_ptr(), 1, &mut dtype) });
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+        assert!(!unsafe { rusty_containers_unify_dtype(std::ptr::null(), 1, &mut dtype) });
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+        assert!(!unsafe { rusty_containers_cast_all(ptrs.as_ptr(), 1, DTYPE::Float64 as u32) });
+        assert_eq!(crate::last_error(), Some(ContainerError::NullPointer));
+        assert_eq!(dtype, DTYPE::Bool);
+    }
+
+    #[test]
+    fn abs_of_signed_integers() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![-1i32, 2, -3]));
+        assert!(rusty_data_container_abs(handle(ptr)));
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert_eq!(
+            (0..3)
+                .map(|index| container.get::<i32>(index).unwrap())
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        destroy(ptr);
+    }
+
+    #[test]
+    fn neg_fails_for_unsigned_integers() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1u32, 2]));
+        assert!(!rusty_data_container_neg(handle(ptr)));
+        assert_eq!(RustyDataContainer::leak(handle(ptr)).get::<u32>(1), Some(2));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn rounding_functions_of_float_containers() {
+        let rounded = |round: extern "C" fn(Option<Box<RustyDataContainer>>) -> bool| {
+            let ptr = boxed(RustyDataContainer::from_vec(vec![1.4f64, 1.6, -1.5, 2.5]));
+            assert!(round(handle(ptr)));
+            let container = RustyDataContainer::leak(handle(ptr));
+            let values: Vec<f64> = (0..4).map(|index| container.get(index).unwrap()).collect();
+            destroy(ptr);
+            values
+        };
+        assert_eq!(rounded(rusty_data_container_round), [1.0, 2.0, -2.0, 3.0]);
+        assert_eq!(rounded(rusty_data_container_floor), [1.0, 1.0, -2.0, 2.0]);
+        assert_eq!(rounded(rusty_data_container_ceil), [2.0, 2.0, -1.0, 3.0]);
+        assert_eq!(rounded(rusty_data_container_trunc), [1.0, 1.0, -1.0, 2.0]);
+    }
+
+    #[test]
+    fn rounding_fails_for_integer_containers() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![1i32]));
+        assert!(!rusty_data_container_round(handle(ptr)));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn parallel_fill_of_ten_million_elements() {
+        let nitems = 10_000_000;
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0.0f64; nitems]));
+        assert!(rusty_data_container_fill_f64_parallel(handle(ptr), 2.5));
+        let container = RustyDataContainer::leak(handle(ptr));
+        assert!(container.typed::<f64>().iter().all(|&value| value == 2.5));
+        destroy(ptr);
+
+        let ptr = boxed(RustyDataContainer::from_vec(vec![0i64; 4]));
+        assert!(!rusty_data_container_fill_f64_parallel(handle(ptr), 2.5));
+        destroy(ptr);
+    }
+
+    #[test]
+    fn cast_inplace_saturates_integers() {
+        let mut container = RustyDataContainer::from_vec(vec![-300_i64, 300, 5, i64::MAX]);
+        assert_eq!(container.cast_inplace(DTYPE::Int8), Ok(()));
+        assert_eq!(container.typed::<i8>(), [i8::MIN, i8::MAX, 5, i8::MAX]);
+        let mut container = RustyDataContainer::from_vec(vec![u64::MAX, 1]);
+        assert_eq!(container.cast_inplace(DTYPE::Int64), Ok(()));
+        assert_eq!(container.typed::<i64>(), [i64::MAX, 1]);
+    }
+}
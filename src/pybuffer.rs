@@ -0,0 +1,173 @@
+//! Python buffer protocol interop.
+//!
+//! A [RustyPyBuffer] has the layout of the CPython `Py_buffer` struct, so a C
+//! extension can fill the `Py_buffer` of its `bf_getbuffer` slot directly with
+//! [rusty_data_container_fill_py_buffer] and set `obj` itself. The byte strides
+//! are allocated and stored in `internal`, so [rusty_py_buffer_release] must be
+//! called from `bf_releasebuffer`.
+
+use crate::{RustyDataContainer, DTYPE, MAX_NDIM, MUTABILITY};
+use libc::{c_char, c_int, c_void};
+
+/// The fields of a CPython `Py_buffer` in its layout.
+#[derive(Debug)]
+#[repr(C)]
+pub struct RustyPyBuffer {
+    /// The address of the first element.
+    pub buf: *mut c_void,
+    /// The exporting Python object, never set by this crate.
+    pub obj: *mut c_void,
+    /// The number of bytes of the data.
+    pub len: isize,
+    /// The number of bytes of an element.
+    pub itemsize: isize,
+    /// 1 if the data must not be written, otherwise 0.
+    pub readonly: c_int,
+    /// The number of dimensions.
+    pub ndim: c_int,
+    /// The element type as a `struct` module format string.
+    pub format: *const c_char,
+    /// The `ndim` extents of the dimensions.
+    pub shape: *mut isize,
+    /// The `ndim` strides of the dimensions in bytes.
+    pub strides: *mut isize,
+    /// Always null since the data contains no indirections.
+    pub suboffsets: *mut isize,
+    /// The allocation of the strides, owned by the buffer.
+    pub internal: *mut c_void,
+}
+
+/// The `struct` module format string of `dtype`.
+///
+/// Complex types use the `Zf` and `Zd` codes of PEP 3118 as numpy does. The 128
+/// bit integers have no format code.
+pub fn py_format(dtype: DTYPE) -> Option<&'static std::ffi::CStr> {
+    match dtype {
+        DTYPE::Float32 => Some(c"f"),
+        DTYPE::Float64 => Some(c"d"),
+        DTYPE::Int8 => Some(c"b"),
+        DTYPE::Int32 => Some(c"i"),
+        DTYPE::Int64 => Some(c"q"),
+        DTYPE::Unsigned8 => Some(c"B"),
+        DTYPE::Unsigned32 => Some(c"I"),
+        DTYPE::Unsigned64 => Some(c"Q"),
+        DTYPE::Usize => Some(c"N"),
+        DTYPE::Bool => Some(c"?"),
+        DTYPE::Complex32 => Some(c"Zf"),
+        DTYPE::Complex64 => Some(c"Zd"),
+        DTYPE::Int128 | DTYPE::Unsigned128 => None,
+    }
+}
+
+/// Fill a `Py_buffer` compatible struct with the description of the data of a container.
+///
+/// The shape points into the container, so the buffer is only valid as long as the
+/// container is alive and its shape is not changed. `obj` is left unchanged. Returns
+/// false without changing `view` if the dtype has no `struct` format code.
+///
+/// # Safety
+/// `view` must point to writable memory for a [RustyPyBuffer].
+#[no_mangle]
+pub unsafe extern "C" fn rusty_data_container_fill_py_buffer(
+    ptr: Option<Box<RustyDataContainer>>,
+    view: *mut RustyPyBuffer,
+) -> bool {
+    let container = RustyDataContainer::leak(ptr);
+    let Some(format) = py_format(container.dtype) else {
+        return false;
+    };
+    let mut strides = Box::new([0_isize; MAX_NDIM]);
+    for (stride, &elements) in strides.iter_mut().zip(&container.strides[..container.ndim]) {
+        *stride = elements * container.itemsize as isize;
+    }
+    let strides = Box::into_raw(strides);
+    let view = &mut *view;
+    view.buf = container.data;
+    view.len = (container.nitems * container.itemsize) as isize;
+    view.itemsize = container.itemsize as isize;
+    view.readonly = c_int::from(container.is_mutable != MUTABILITY::Mutable);
+    view.ndim = container.ndim as c_int;
+    view.format = format.as_ptr();
+    view.shape = container.shape.as_ptr() as *mut isize;
+    view.strides = strides as *mut isize;
+    view.suboffsets = std::ptr::null_mut();
+    view.internal = strides as *mut c_void;
+    true
+}
+
+/// Free the strides of a buffer filled by [rusty_data_container_fill_py_buffer].
+///
+/// The container is not affected. Releasing a buffer twice does nothing.
+///
+/// # Safety
+/// `view` must point to a buffer filled by [rusty_data_container_fill_py_buffer].
+#[no_mangle]
+pub unsafe extern "C" fn rusty_py_buffer_release(view: *mut RustyPyBuffer) {
+    let view = &mut *view;
+    if !view.internal.is_null() {
+        drop(Box::from_raw(view.internal as *mut [isize; MAX_NDIM]));
+    }
+    view.internal = std::ptr::null_mut();
+    view.strides = std::ptr::null_mut();
+}
+
+// Compile-time check of the `Py_buffer` layout: nine pointer sized fields and two ints.
+const _: () = assert!(
+    std::mem::size_of::<RustyPyBuffer>() == 9 * std::mem::size_of::<usize>() + 8
+        && std::mem::offset_of!(RustyPyBuffer, format) == 4 * std::mem::size_of::<usize>() + 8
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn empty_buffer() -> RustyPyBuffer {
+        RustyPyBuffer {
+            buf: std::ptr::null_mut(),
+            obj: std::ptr::null_mut(),
+            len: 0,
+            itemsize: 0,
+            readonly: 0,
+            ndim: 0,
+            format: std::ptr::null(),
+            shape: std::ptr::null_mut(),
+            strides: std::ptr::null_mut(),
+            suboffsets: std::ptr::null_mut(),
+            internal: std::ptr::null_mut(),
+        }
+    }
+
+    #[test]
+    fn fill_describes_a_2x3_f64_container() {
+        let mut container = RustyDataContainer::from_vec(vec![0.0f64; 6]);
+        container.reshape(&[2, 3]).unwrap();
+        let ptr = boxed(container);
+        let mut view = empty_buffer();
+
+        assert!(unsafe { rusty_data_container_fill_py_buffer(handle(ptr), &mut view) });
+        unsafe {
+            assert_eq!(std::ffi::CStr::from_ptr(view.format), c"d");
+            assert_eq!(std::slice::from_raw_parts(view.shape, 2), [2, 3]);
+            assert_eq!(std::slice::from_raw_parts(view.strides, 2), [24, 8]);
+        }
+        assert_eq!((view.len, view.itemsize, view.ndim), (48, 8, 2));
+        assert_eq!(view.readonly, 0);
+
+        unsafe { rusty_py_buffer_release(&mut view) };
+        assert!(view.strides.is_null());
+        destroy(ptr);
+    }
+
+    #[test]
+    fn fill_marks_immutable_containers_readonly() {
+        let values = [1.0f64, 2.0];
+        let ptr = boxed(RustyDataContainer::from_slice(&values));
+        let mut view = empty_buffer();
+
+        assert!(unsafe { rusty_data_container_fill_py_buffer(handle(ptr), &mut view) });
+        assert_eq!(view.readonly, 1);
+        unsafe { rusty_py_buffer_release(&mut view) };
+        destroy(ptr);
+    }
+}
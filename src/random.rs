@@ -0,0 +1,137 @@
+//! Deterministic pseudo-random containers for tests and benchmarks.
+//!
+//! The values are generated with the SplitMix64 generator, so the same
+//! seed produces the same data on every platform and in every run.
+
+use crate::{dispatch_dtype, Complex, RustyDataContainer, DTYPE};
+use libc::size_t;
+
+/// The SplitMix64 pseudo-random number generator.
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed value in `[0, 1)` with 53 random bits.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
+    }
+}
+
+/// Element types that can be drawn from a [SplitMix64] generator.
+///
+/// Floats are uniform in `[0, 1)`, integers are uniform in `[0, 100)`
+/// and booleans are true with probability one half.
+pub(crate) trait Random {
+    fn random(rng: &mut SplitMix64) -> Self;
+}
+
+macro_rules! impl_random_int {
+    ($($t:ty),*) => {
+        $(
+            impl Random for $t {
+                fn random(rng: &mut SplitMix64) -> Self {
+                    (rng.next_u64() % 100) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_random_int!(i8, i32, i64, u8, u32, u64, usize, i128, u128);
+
+impl Random for f32 {
+    fn random(rng: &mut SplitMix64) -> Self {
+        (rng.next_u64() >> 40) as f32 * (1.0 / (1_u32 << 24) as f32)
+    }
+}
+
+impl Random for f64 {
+    fn random(rng: &mut SplitMix64) -> Self {
+        rng.next_f64()
+    }
+}
+
+impl Random for bool {
+    fn random(rng: &mut SplitMix64) -> Self {
+        rng.next_u64() >> 63 == 1
+    }
+}
+
+impl<T: Random> Random for Complex<T> {
+    fn random(rng: &mut SplitMix64) -> Self {
+        let re = T::random(rng);
+        Complex::new(re, T::random(rng))
+    }
+}
+
+impl RustyDataContainer {
+    /// Create a new owning and mutable container of type `dtype` with pseudo-random values.
+    ///
+    /// The values only depend on `nitems`, `dtype` and `seed`. Floats are uniform in
+    /// `[0, 1)`, integers are uniform in `[0, 100)` and complex numbers have both parts
+    /// uniform in `[0, 1)`.
+    pub fn new_random(nitems: usize, dtype: DTYPE, seed: u64) -> Self {
+        let mut rng = SplitMix64::new(seed);
+        dispatch_dtype!(dtype, T => RustyDataContainer::from_vec(
+            (0..nitems).map(|_| T::random(&mut rng)).collect::<Vec<T>>()
+        ))
+    }
+}
+
+/// Create a new f64 data container with pseudo-random values uniform in `[0, 1)`.
+///
+/// The same seed always yields the same values.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_random_f64(
+    nitems: size_t,
+    seed: u64,
+) -> Box<RustyDataContainer> {
+    RustyDataContainer::new_random(nitems, DTYPE::Float64, seed).to_box()
+}
+
+/// Create a new data container with pseudo-random values of the dtype with code `dtype`.
+///
+/// See [RustyDataContainer::new_random] for the ranges of the values.
+/// Returns null if `dtype` is not a valid dtype code.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_new_random(
+    nitems: size_t,
+    dtype: u32,
+    seed: u64,
+) -> Option<Box<RustyDataContainer>> {
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    Some(RustyDataContainer::new_random(nitems, dtype, seed).to_box())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_values_depend_only_on_the_seed() {
+        let a = rusty_data_container_new_random_f64(100, 42);
+        let b = rusty_data_container_new_random_f64(100, 42);
+        let c = rusty_data_container_new_random_f64(100, 43);
+        assert_eq!(a.typed::<f64>(), b.typed::<f64>());
+        assert_ne!(a.typed::<f64>(), c.typed::<f64>());
+        assert!(a.values::<f64>().all(|value| (0.0..1.0).contains(&value)));
+
+        let ints = rusty_data_container_new_random(100, DTYPE::Int32.discriminant(), 7).unwrap();
+        assert_eq!(ints.dtype, DTYPE::Int32);
+        assert!(ints.values::<i32>().all(|value| (0..100).contains(&value)));
+        assert!(rusty_data_container_new_random(100, 14, 7).is_none());
+    }
+}
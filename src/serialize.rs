@@ -0,0 +1,451 @@
+//! Serialization of containers into portable byte blobs.
+//!
+//! A blob consists of a 16 byte header followed by the shape and the data:
+//!
+//! | offset | size     | content                                    |
+//! |--------|----------|--------------------------------------------|
+//! | 0      | 4        | the magic bytes `RDCS`                     |
+//! | 4      | 1        | the endianness, 0 for little and 1 for big |
+//! | 5      | 1        | flags, [FLAG_RLE] or 0                     |
+//! | 6      | 2        | reserved, always 0                         |
+//! | 8      | 4        | the dtype code                             |
+//! | 12     | 4        | the number of dimensions `ndim`            |
+//! | 16     | 8 `ndim` | the shape as u64 values                    |
+//! | ...    | ...      | the elements in row-major order            |
+//!
+//! All multi-byte values, including the elements, are stored in the endianness
+//! given in the header. Complex numbers are stored as two floats.
+//!
+//! If the [FLAG_RLE] flag is set the elements are run-length encoded: each run of
+//! identical elements is stored as its length as a u64 value followed by the element.
+//! Elements are identical if their bytes are equal.
+
+use crate::{get_itemsize, ContainerError, RustyDataContainer, DTYPE, MAX_NDIM, MUTABILITY};
+
+const MAGIC: &[u8; 4] = b"RDCS";
+const HEADER_SIZE: usize = 16;
+
+/// The header flag of blobs with run-length encoded elements.
+pub const FLAG_RLE: u8 = 1;
+
+/// The byte order of a serialized container.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum Endianness {
+    Little = 0,
+    Big = 1,
+}
+
+impl Endianness {
+    /// The byte order of the target.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endianness = Endianness::Little;
+    /// The byte order of the target.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endianness = Endianness::Big;
+
+    /// The byte order with the code `value`, 0 for little and 1 for big endian.
+    pub fn from_discriminant(value: u32) -> Option<Endianness> {
+        match value {
+            0 => Some(Endianness::Little),
+            1 => Some(Endianness::Big),
+            _ => None,
+        }
+    }
+
+    /// Convert native bytes of a value to this byte order, or back.
+    fn convert<const N: usize>(self, bytes: [u8; N]) -> [u8; N] {
+        let mut bytes = bytes;
+        if self != Endianness::NATIVE {
+            bytes.reverse();
+        }
+        bytes
+    }
+}
+
+/// Reverse the byte order of every element in the contiguous bytes `data` of type `dtype`.
+///
+/// The two parts of complex numbers are swapped individually.
+pub(crate) fn swap_element_bytes(data: &mut [u8], dtype: DTYPE) {
+    let width = if dtype.is_complex() {
+        get_itemsize(dtype) / 2
+    } else {
+        get_itemsize(dtype)
+    };
+    if width > 1 {
+        for component in data.chunks_exact_mut(width) {
+            component.reverse();
+        }
+    }
+}
+
+impl RustyDataContainer {
+    /// Serialize the container in the native byte order.
+    pub fn serialize(&self) -> Vec<u8> {
+        self.serialize_with_endian(Endianness::NATIVE)
+    }
+
+    /// Serialize the container in the byte order `endianness`.
+    pub fn serialize_with_endian(&self, endianness: Endianness) -> Vec<u8> {
+        let mut blob = self.serialize_header(endianness, 0, self.nitems * self.itemsize);
+        let start = blob.len();
+        for index in 0..self.nitems {
+            blob.extend_from_slice(self.element_bytes(index));
+        }
+        if endianness != Endianness::NATIVE {
+            swap_element_bytes(&mut blob[start..], self.dtype);
+        }
+        blob
+    }
+
+    /// Serialize the container in the native byte order, run-length encoding the
+    /// elements if that makes the blob smaller.
+    pub fn serialize_rle(&self) -> Vec<u8> {
+        self.serialize_rle_with_endian(Endianness::NATIVE)
+    }
+
+    /// Serialize the container in the byte order `endianness`, run-length encoding the
+    /// elements if that makes the blob smaller.
+    pub fn serialize_rle_with_endian(&self, endianness: Endianness) -> Vec<u8> {
+        let mut runs: Vec<(u64, &[u8])> = Vec::new();
+        for index in 0..self.nitems {
+            let element = self.element_bytes(index);
+            match runs.last_mut() {
+                Some((count, run)) if *run == element => *count += 1,
+                _ => runs.push((1, element)),
+            }
+        }
+        let size = runs.len() * (8 + self.itemsize);
+        if size >= self.nitems * self.itemsize {
+            return self.serialize_with_endian(endianness);
+        }
+        let mut blob = self.serialize_header(endianness, FLAG_RLE, size);
+        for (count, element) in runs {
+            blob.extend_from_slice(&endianness.convert(count.to_ne_bytes()));
+            let start = blob.len();
+            blob.extend_from_slice(element);
+            if endianness != Endianness::NATIVE {
+                swap_element_bytes(&mut blob[start..], self.dtype);
+            }
+        }
+        blob
+    }
+
+    /// The header and shape of a blob, with capacity for `data_size` more bytes.
+    fn serialize_header(&self, endianness: Endianness, flags: u8, data_size: usize) -> Vec<u8> {
+        let mut blob = Vec::with_capacity(HEADER_SIZE + 8 * self.ndim + data_size);
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&[endianness as u8, flags, 0, 0]);
+        blob.extend_from_slice(&endianness.convert(self.dtype.discriminant().to_ne_bytes()));
+        blob.extend_from_slice(&endianness.convert((self.ndim as u32).to_ne_bytes()));
+        for &extent in &self.shape[..self.ndim] {
+            blob.extend_from_slice(&endianness.convert((extent as u64).to_ne_bytes()));
+        }
+        blob
+    }
+
+    /// Create a new owning container from a blob created by [RustyDataContainer::serialize],
+    /// [RustyDataContainer::serialize_with_endian] or their run-length encoding variants.
+    ///
+    /// The data is converted to the native byte order. Fails with [ContainerError::InvalidFormat]
+    /// for malformed blobs and with [ContainerError::AllocationFailed] if the elements do not
+    /// fit into memory.
+    pub fn deserialize(blob: &[u8]) -> Result<RustyDataContainer, ContainerError> {
+        let header = blob
+            .get(..HEADER_SIZE)
+            .ok_or(ContainerError::InvalidFormat)?;
+        if &header[..4] != MAGIC || header[5] & !FLAG_RLE != 0 || header[6..8] != [0, 0] {
+            return Err(ContainerError::InvalidFormat);
+        }
+        let endianness =
+            Endianness::from_discriminant(header[4] as u32).ok_or(ContainerError::InvalidFormat)?;
+        let read_u32 = |offset: usize| {
+            u32::from_ne_bytes(endianness.convert(header[offset..offset + 4].try_into().unwrap()))
+        };
+        let dtype = DTYPE::from_discriminant(read_u32(8)).ok_or(ContainerError::InvalidFormat)?;
+        let ndim = read_u32(12) as usize;
+        if ndim == 0 || ndim > MAX_NDIM {
+            return Err(ContainerError::InvalidFormat);
+        }
+        let shape_bytes = blob
+            .get(HEADER_SIZE..HEADER_SIZE + 8 * ndim)
+            .ok_or(ContainerError::InvalidFormat)?;
+        let shape = shape_bytes
+            .chunks_exact(8)
+            .map(|bytes| {
+                usize::try_from(u64::from_ne_bytes(
+                    endianness.convert(bytes.try_into().unwrap()),
+                ))
+            })
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| ContainerError::InvalidFormat)?;
+        let nitems = shape
+            .iter()
+            .try_fold(1_usize, |acc, &extent| acc.checked_mul(extent))
+            .ok_or(ContainerError::InvalidFormat)?;
+        let data = &blob[HEADER_SIZE + 8 * ndim..];
+        let itemsize = get_itemsize(dtype);
+        let runs = if header[5] & FLAG_RLE != 0 {
+            Some(decode_runs(data, itemsize, nitems, endianness)?)
+        } else if nitems.checked_mul(itemsize) == Some(data.len()) {
+            None
+        } else {
+            return Err(ContainerError::InvalidFormat);
+        };
+        // Only the bytes 0 and 1 are valid Bool values.
+        if dtype == DTYPE::Bool {
+            let invalid = match &runs {
+                Some(runs) => runs.iter().any(|(_, run)| run[0] > 1),
+                None => data.iter().any(|&byte| byte > 1),
+            };
+            if invalid {
+                return Err(ContainerError::InvalidFormat);
+            }
+        }
+        // Run-length encoded blobs can describe more data than fits into memory.
+        if nitems
+            .checked_mul(itemsize)
+            .is_none_or(|size| size > isize::MAX as usize)
+        {
+            return Err(ContainerError::InvalidFormat);
+        }
+        let mut container = RustyDataContainer::try_new_zeroed(nitems, dtype)?;
+        let bytes = container.bytes_mut();
+        match runs {
+            Some(runs) => {
+                let mut elements = bytes.chunks_exact_mut(itemsize);
+                for (count, run) in runs {
+                    for element in elements.by_ref().take(count) {
+                        element.copy_from_slice(run);
+                    }
+                }
+            }
+            None => bytes.copy_from_slice(data),
+        }
+        if endianness != Endianness::NATIVE {
+            swap_element_bytes(bytes, dtype);
+        }
+        container.set_contiguous_shape(&shape);
+        Ok(container)
+    }
+
+    /// Reverse the byte order of every element in place. Panics if the container is not mutable.
+    ///
+    /// The two parts of complex numbers are swapped individually, and 1 byte dtypes
+    /// are unchanged. Swapping twice restores the original data.
+    pub fn byteswap(&mut self) {
+        assert_eq!(self.is_mutable, MUTABILITY::Mutable);
+        for index in 0..self.nitems {
+            let bytes =
+                unsafe { std::slice::from_raw_parts_mut(self.element_ptr(index), self.itemsize) };
+            swap_element_bytes(bytes, self.dtype);
+        }
+    }
+}
+
+/// Reverse the byte order of every element of a mutable container in place.
+///
+/// Containers carry no byte order of their own, so the caller is responsible for
+/// tracking whether the data is in the native byte order. Returns false and sets the
+/// last error if the container is not mutable or is borrowed by a C caller.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_byteswap(ptr: Option<Box<RustyDataContainer>>) -> bool {
+    let Some(container) = RustyDataContainer::try_leak_mut(ptr, true) else {
+        return false;
+    };
+    container.byteswap();
+    true
+}
+
+/// Split run-length encoded `data` into its runs of identical elements.
+///
+/// Fails unless the runs are nonempty and add up to `nitems` elements.
+fn decode_runs(
+    data: &[u8],
+    itemsize: usize,
+    nitems: usize,
+    endianness: Endianness,
+) -> Result<Vec<(usize, &[u8])>, ContainerError> {
+    if !data.len().is_multiple_of(8 + itemsize) {
+        return Err(ContainerError::InvalidFormat);
+    }
+    let mut total: usize = 0;
+    let mut runs = Vec::with_capacity(data.len() / (8 + itemsize));
+    for run in data.chunks_exact(8 + itemsize) {
+        let count = usize::try_from(u64::from_ne_bytes(
+            endianness.convert(run[..8].try_into().unwrap()),
+        ))
+        .ok()
+        .filter(|&count| count > 0)
+        .ok_or(ContainerError::InvalidFormat)?;
+        total = total
+            .checked_add(count)
+            .filter(|&total| total <= nitems)
+            .ok_or(ContainerError::InvalidFormat)?;
+        runs.push((count, &run[8..]));
+    }
+    if total != nitems {
+        return Err(ContainerError::InvalidFormat);
+    }
+    Ok(runs)
+}
+
+/// Serialize a container into a new u8 container in the byte order `endianness`,
+/// 0 for little and 1 for big endian.
+///
+/// Returns null if `endianness` is not a valid code.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_serialize(
+    ptr: Option<Box<RustyDataContainer>>,
+    endianness: u32,
+) -> Option<Box<RustyDataContainer>> {
+    let container = RustyDataContainer::leak(ptr);
+    let blob = container.serialize_with_endian(Endianness::from_discriminant(endianness)?);
+    Some(RustyDataContainer::from_vec(blob).to_box())
+}
+
+/// Serialize a container into a new u8 container in the byte order `endianness`,
+/// run-length encoding the elements if that makes the blob smaller.
+///
+/// Returns null if `endianness` is not a valid code, see [rusty_data_container_serialize].
+#[no_mangle]
+pub extern "C" fn rusty_data_container_serialize_rle(
+    ptr: Option<Box<RustyDataContainer>>,
+    endianness: u32,
+) -> Option<Box<RustyDataContainer>> {
+    let container = RustyDataContainer::leak(ptr);
+    let blob = container.serialize_rle_with_endian(Endianness::from_discriminant(endianness)?);
+    Some(RustyDataContainer::from_vec(blob).to_box())
+}
+
+/// Create a new container from a u8 container holding a serialized container.
+///
+/// Returns null if the blob is not a u8 container. Returns null and sets the last
+/// error if the blob is malformed or its elements cannot be allocated.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_deserialize(
+    blob: Option<Box<RustyDataContainer>>,
+) -> Option<Box<RustyDataContainer>> {
+    let blob = RustyDataContainer::leak(blob);
+    if blob.dtype != DTYPE::Unsigned8 {
+        return None;
+    }
+    RustyDataContainer::deserialize(blob.to_contiguous().bytes())
+        .map_err(crate::set_last_error)
+        .ok()
+        .map(RustyDataContainer::to_box)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn round_trip_in_both_byte_orders() {
+        let container = RustyDataContainer::from_vec(vec![1.5_f64, -2.0, 1e300, 0.25]);
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let blob = container.serialize_with_endian(endianness);
+            assert_eq!(blob[4], endianness as u8);
+            let restored = RustyDataContainer::deserialize(&blob).unwrap();
+            assert_eq!(restored.typed::<f64>(), container.typed::<f64>());
+        }
+        let big = container.serialize_with_endian(Endianness::Big);
+        assert_eq!(big[8..12], DTYPE::Float64.discriminant().to_be_bytes());
+        assert_eq!(big[24..32], 1.5_f64.to_be_bytes());
+    }
+
+    #[test]
+    fn deserialize_rejects_invalid_bools() {
+        let mut blob = RustyDataContainer::from_vec(vec![true, false, true]).serialize();
+        assert_eq!(
+            RustyDataContainer::deserialize(&blob)
+                .unwrap()
+                .typed::<bool>(),
+            [true, false, true]
+        );
+        *blob.last_mut().unwrap() = 2;
+        assert_eq!(
+            RustyDataContainer::deserialize(&blob).err(),
+            Some(ContainerError::InvalidFormat)
+        );
+
+        let mut blob = RustyDataContainer::from_vec(vec![true; 100]).serialize_rle();
+        assert_eq!(blob[5], FLAG_RLE);
+        *blob.last_mut().unwrap() = 0xFF;
+        assert_eq!(
+            RustyDataContainer::deserialize(&blob).err(),
+            Some(ContainerError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn serialize_takes_an_endianness_code() {
+        let ptr = boxed(RustyDataContainer::from_vec(vec![7_u32, 8]));
+        let blob = rusty_data_container_serialize(handle(ptr), Endianness::Big as u32).unwrap();
+        assert_eq!(blob.typed::<u8>()[4], 1);
+        let blob = Box::into_raw(blob);
+        let restored = rusty_data_container_deserialize(handle(blob)).unwrap();
+        assert_eq!(restored.typed::<u32>(), [7, 8]);
+        destroy(blob);
+        assert!(rusty_data_container_serialize(handle(ptr), 2).is_none());
+        assert!(rusty_data_container_serialize_rle(handle(ptr), 2).is_none());
+        destroy(ptr);
+    }
+
+    #[test]
+    fn byteswap_twice_restores_u32_data() {
+        let values = vec![1u32, 0x0102_0304, u32::MAX];
+        let ptr = boxed(RustyDataContainer::from_vec(values.clone()));
+
+        let get = |index| RustyDataContainer::leak(handle(ptr)).get::<u32>(index);
+        assert!(rusty_data_container_byteswap(handle(ptr)));
+        assert_eq!(get(0), Some(0x0100_0000));
+        assert_eq!(get(1), Some(0x0403_0201));
+
+        assert!(rusty_data_container_byteswap(handle(ptr)));
+        for (index, &value) in values.iter().enumerate() {
+            assert_eq!(get(index), Some(value));
+        }
+        destroy(ptr);
+    }
+
+    #[test]
+    fn rle_round_trip_of_identical_values() {
+        let values = vec![1.5f64; 1000];
+        let blob = RustyDataContainer::from_vec(values.clone()).serialize_rle();
+        assert_eq!(blob[5], FLAG_RLE);
+        assert_eq!(blob.len(), HEADER_SIZE + 8 + 8 + 8);
+        let container = RustyDataContainer::deserialize(&blob).unwrap();
+        assert_eq!(container.typed::<f64>(), values);
+    }
+
+    /// A run-length encoded blob of a single run of `nitems` elements of `value`.
+    fn single_run_blob<T: crate::ConversionType + Clone>(value: T, nitems: u64) -> Vec<u8> {
+        let mut blob = RustyDataContainer::from_vec(vec![value; 100])
+            .serialize_rle_with_endian(Endianness::Little);
+        assert_eq!(blob[5], FLAG_RLE);
+        blob[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&nitems.to_le_bytes());
+        blob[HEADER_SIZE + 8..HEADER_SIZE + 16].copy_from_slice(&nitems.to_le_bytes());
+        blob
+    }
+
+    #[test]
+    fn deserialize_rejects_rle_blobs_that_do_not_fit_into_memory() {
+        let too_large = single_run_blob(1.5f64, 1 << 61);
+        assert_eq!(
+            RustyDataContainer::deserialize(&too_large).err(),
+            Some(ContainerError::InvalidFormat)
+        );
+        let unallocatable = single_run_blob(7u8, 1 << 61);
+        assert_eq!(
+            RustyDataContainer::deserialize(&unallocatable).err(),
+            Some(ContainerError::AllocationFailed)
+        );
+
+        let ptr = boxed(RustyDataContainer::from_vec(unallocatable));
+        assert!(rusty_data_container_deserialize(handle(ptr)).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::AllocationFailed));
+        destroy(ptr);
+    }
+}
@@ -0,0 +1,154 @@
+//! Containers with inline storage for a few elements.
+//!
+//! A [SmallContainer] keeps its data in the struct itself, so small
+//! fixed arrays can be passed around without a heap allocation. Its
+//! data can be accessed through a non-owning [RustyDataContainer] view.
+
+use crate::{get_itemsize, ConversionType, RustyDataContainer, DTYPE, MUTABILITY, OWNERSHIP};
+use libc::{c_void, size_t};
+
+/// The number of bytes reserved per element, enough for every dtype.
+const SLOT_SIZE: usize = 16;
+
+/// Inline storage for `N` elements, aligned for every dtype.
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct SmallStorage<const N: usize>([[u8; SLOT_SIZE]; N]);
+
+/// A container with inline storage for up to `N` elements.
+///
+/// The elements are stored contiguously at the start of the storage,
+/// so `N` elements of any dtype fit.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SmallContainer<const N: usize> {
+    /// The number of elements.
+    nitems: size_t,
+    /// The type of the data.
+    dtype: DTYPE,
+    /// Mutability of the data through views.
+    is_mutable: MUTABILITY,
+    /// The data.
+    data: SmallStorage<N>,
+}
+
+impl<const N: usize> SmallContainer<N> {
+    /// Create a new mutable container with `nitems` zero elements of type `dtype`.
+    /// Returns `None` if `nitems > N`.
+    pub fn new(nitems: usize, dtype: DTYPE) -> Option<Self> {
+        if nitems > N {
+            return None;
+        }
+        Some(Self {
+            nitems,
+            dtype,
+            is_mutable: MUTABILITY::Mutable,
+            data: SmallStorage([[0; SLOT_SIZE]; N]),
+        })
+    }
+
+    /// Create a new mutable container with a copy of `values`.
+    /// Returns `None` if there are more than `N` values.
+    pub fn from_slice<T: ConversionType + Copy>(values: &[T]) -> Option<Self> {
+        let mut container = Self::new(values.len(), crate::get_dtype::<T>())?;
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                values.as_ptr(),
+                container.data.0.as_mut_ptr() as *mut T,
+                values.len(),
+            )
+        };
+        Some(container)
+    }
+
+    /// Set the mutability of views of the data.
+    pub fn set_mutable(&mut self, is_mutable: MUTABILITY) {
+        self.is_mutable = is_mutable;
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.nitems
+    }
+
+    /// Return true if the container has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.nitems == 0
+    }
+
+    /// The type of the data.
+    pub fn dtype(&self) -> DTYPE {
+        self.dtype
+    }
+
+    /// A non-owning and non-mutable view of the data.
+    ///
+    /// The view must not be used after the small container is moved or dropped.
+    pub fn view(&self) -> RustyDataContainer {
+        RustyDataContainer::new_1d(
+            self.data.0.as_ptr() as *mut c_void,
+            self.nitems,
+            self.nitems,
+            self.dtype,
+            OWNERSHIP::NotOwner,
+            MUTABILITY::NotMutable,
+        )
+    }
+
+    /// A non-owning view of the data with the mutability of the container.
+    ///
+    /// The view must not be used after the small container is moved or dropped.
+    pub fn view_mut(&mut self) -> RustyDataContainer {
+        RustyDataContainer::new_1d(
+            self.data.0.as_mut_ptr() as *mut c_void,
+            self.nitems,
+            self.nitems,
+            self.dtype,
+            OWNERSHIP::NotOwner,
+            self.is_mutable,
+        )
+    }
+}
+
+// Every dtype fits into a slot and is sufficiently aligned by the storage.
+const _: () = {
+    let mut index = 0;
+    while index < DTYPE::ALL.len() {
+        assert!(get_itemsize(DTYPE::ALL[index]) <= SLOT_SIZE);
+        assert!(crate::get_alignment(DTYPE::ALL[index]) <= std::mem::align_of::<SmallStorage<1>>());
+        index += 1;
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn read_small_container_through_a_view() {
+        let mut small = SmallContainer::<4>::from_slice(&[1.0_f64, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!((small.len(), small.dtype()), (4, DTYPE::Float64));
+        let view = boxed(small.view());
+        assert_eq!(crate::rusty_data_container_get_nitems(handle(view)), 4);
+        assert_eq!(
+            crate::rusty_data_container_get_f64_or(handle(view), 2, 0.0),
+            3.0
+        );
+        assert!(!crate::rusty_data_container_add_accumulate(
+            handle(view),
+            handle(view)
+        ));
+        destroy(view);
+
+        small.view_mut().typed_mut::<f64>()[0] = -1.0;
+        assert_eq!(small.view().typed::<f64>(), [-1.0, 2.0, 3.0, 4.0]);
+        small.set_mutable(MUTABILITY::NotMutable);
+        assert_eq!(small.view_mut().is_mutable, MUTABILITY::NotMutable);
+
+        assert!(SmallContainer::<4>::from_slice(&[0_u8; 5]).is_none());
+        assert!(SmallContainer::<2>::new(0, DTYPE::Complex64)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+//! Sparse matrices in coordinate (COO) format.
+//!
+//! A [RustySparseCoo] bundles three data containers of equal length
+//! holding the row indices, the column indices and the values of
+//! the nonzero entries.
+
+use crate::{ContainerError, RustyDataContainer, DTYPE};
+use libc::size_t;
+
+/// A sparse matrix in coordinate format.
+pub struct RustySparseCoo {
+    /// The row indices. The dtype is [DTYPE::Unsigned64].
+    rows: RustyDataContainer,
+    /// The column indices. The dtype is [DTYPE::Unsigned64].
+    cols: RustyDataContainer,
+    /// The values of the nonzero entries.
+    values: RustyDataContainer,
+}
+
+impl RustySparseCoo {
+    /// Create a new sparse matrix with `nnz` entries of type `dtype` from
+    /// row indices, column indices and values.
+    ///
+    /// Returns [ContainerError::InvalidShape] if one of the containers does not
+    /// hold `nnz` items and [ContainerError::DtypeMismatch] if the indices are not
+    /// of type [DTYPE::Unsigned64] or the values are not of the numeric type `dtype`.
+    pub fn new(
+        rows: RustyDataContainer,
+        cols: RustyDataContainer,
+        values: RustyDataContainer,
+        dtype: DTYPE,
+        nnz: usize,
+    ) -> Result<Self, ContainerError> {
+        if rows.nitems != nnz || cols.nitems != nnz || values.nitems != nnz {
+            return Err(ContainerError::InvalidShape);
+        }
+        if rows.dtype != DTYPE::Unsigned64 || cols.dtype != DTYPE::Unsigned64 {
+            return Err(ContainerError::DtypeMismatch);
+        }
+        if dtype == DTYPE::Bool || values.dtype != dtype {
+            return Err(ContainerError::DtypeMismatch);
+        }
+        Ok(Self { rows, cols, values })
+    }
+
+    /// The number of nonzero entries.
+    pub fn nnz(&self) -> usize {
+        self.values.nitems
+    }
+
+    /// The row indices.
+    pub fn rows(&self) -> &RustyDataContainer {
+        &self.rows
+    }
+
+    /// The column indices.
+    pub fn cols(&self) -> &RustyDataContainer {
+        &self.cols
+    }
+
+    /// The values.
+    pub fn values(&self) -> &RustyDataContainer {
+        &self.values
+    }
+
+    /// Get a reference to a RustySparseCoo from a ptr.
+    /// Ensures that the destructor of the matrix is not run.
+    pub fn leak(ptr: Option<Box<RustySparseCoo>>) -> &'static RustySparseCoo {
+        Box::leak(ptr.unwrap())
+    }
+}
+
+/// Create a new sparse COO matrix with `nnz` entries of type `dtype`.
+///
+/// The three containers are consumed by this function, also if the
+/// creation fails. Returns null if a container is null or `dtype` is unknown.
+/// Returns null and sets the last error if the containers do not hold `nnz`
+/// items, if the indices are not of type u64 or if the values are not of the
+/// numeric type `dtype`.
+#[no_mangle]
+pub extern "C" fn rusty_sparse_coo_new(
+    rows: Option<Box<RustyDataContainer>>,
+    cols: Option<Box<RustyDataContainer>>,
+    values: Option<Box<RustyDataContainer>>,
+    dtype: u32,
+    nnz: size_t,
+) -> Option<Box<RustySparseCoo>> {
+    let rows = RustyDataContainer::from_box(rows?);
+    let cols = RustyDataContainer::from_box(cols?);
+    let values = RustyDataContainer::from_box(values?);
+    let dtype = DTYPE::from_discriminant(dtype)?;
+    RustySparseCoo::new(rows, cols, values, dtype, nnz)
+        .map_err(crate::set_last_error)
+        .ok()
+        .map(Box::new)
+}
+
+/// Destroy a sparse COO matrix together with its containers.
+#[no_mangle]
+pub extern "C" fn rusty_sparse_coo_destroy(_: Option<Box<RustySparseCoo>>) {}
+
+/// Get the number of nonzero entries.
+#[no_mangle]
+pub extern "C" fn rusty_sparse_coo_nnz(ptr: Option<Box<RustySparseCoo>>) -> size_t {
+    RustySparseCoo::leak(ptr).nnz()
+}
+
+/// Get a non-owning view of the row indices.
+#[no_mangle]
+pub extern "C" fn rusty_sparse_coo_rows(
+    ptr: Option<Box<RustySparseCoo>>,
+) -> Box<RustyDataContainer> {
+    RustySparseCoo::leak(ptr).rows.view().to_box()
+}
+
+/// Get a non-owning view of the column indices.
+#[no_mangle]
+pub extern "C" fn rusty_sparse_coo_cols(
+    ptr: Option<Box<RustySparseCoo>>,
+) -> Box<RustyDataContainer> {
+    RustySparseCoo::leak(ptr).cols.view().to_box()
+}
+
+/// Get a non-owning view of the values.
+#[no_mangle]
+pub extern "C" fn rusty_sparse_coo_values(
+    ptr: Option<Box<RustySparseCoo>>,
+) -> Box<RustyDataContainer> {
+    RustySparseCoo::leak(ptr).values.view().to_box()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn triple(
+        rows: Vec<u64>,
+        cols: Vec<u64>,
+        values: Vec<f64>,
+    ) -> [Option<Box<RustyDataContainer>>; 3] {
+        [
+            handle(boxed(RustyDataContainer::from_vec(rows))),
+            handle(boxed(RustyDataContainer::from_vec(cols))),
+            handle(boxed(RustyDataContainer::from_vec(values))),
+        ]
+    }
+
+    #[test]
+    fn coo_reads_back_a_triple() {
+        let [rows, cols, values] = triple(vec![0, 1, 2], vec![2, 0, 1], vec![1.5, -2.0, 4.0]);
+        let coo = rusty_sparse_coo_new(rows, cols, values, DTYPE::Float64.discriminant(), 3);
+        let coo = Box::into_raw(coo.unwrap());
+        assert_eq!(rusty_sparse_coo_nnz(unsafe { Some(Box::from_raw(coo)) }), 3);
+        let matrix = RustySparseCoo::leak(unsafe { Some(Box::from_raw(coo)) });
+        assert_eq!(matrix.rows().values::<u64>().nth(1), Some(1));
+        assert_eq!(matrix.cols().values::<u64>().nth(1), Some(0));
+        assert_eq!(matrix.values().values::<f64>().nth(1), Some(-2.0));
+        rusty_sparse_coo_destroy(unsafe { Some(Box::from_raw(coo)) });
+    }
+
+    #[test]
+    fn coo_rejects_invalid_triples() {
+        let [rows, cols, values] = triple(vec![0, 1, 2], vec![2, 0, 1], vec![1.5, -2.0, 4.0]);
+        assert!(
+            rusty_sparse_coo_new(rows, cols, values, DTYPE::Float64.discriminant(), 2).is_none()
+        );
+        assert_eq!(crate::last_error(), Some(ContainerError::InvalidShape));
+
+        let [rows, cols, values] = triple(vec![0, 1], vec![2, 0, 1], vec![1.5, -2.0, 4.0]);
+        assert!(
+            rusty_sparse_coo_new(rows, cols, values, DTYPE::Float64.discriminant(), 3).is_none()
+        );
+        assert_eq!(crate::last_error(), Some(ContainerError::InvalidShape));
+
+        let [rows, cols, values] = triple(vec![0, 1, 2], vec![2, 0, 1], vec![1.5, -2.0, 4.0]);
+        assert!(
+            rusty_sparse_coo_new(rows, cols, values, DTYPE::Float32.discriminant(), 3).is_none()
+        );
+        assert_eq!(crate::last_error(), Some(ContainerError::DtypeMismatch));
+
+        let rows = handle(boxed(RustyDataContainer::from_vec(vec![0_u64])));
+        let cols = handle(boxed(RustyDataContainer::from_vec(vec![0_u64])));
+        let values = handle(boxed(RustyDataContainer::from_vec(vec![true])));
+        assert!(rusty_sparse_coo_new(rows, cols, values, DTYPE::Bool.discriminant(), 1).is_none());
+        assert_eq!(crate::last_error(), Some(ContainerError::DtypeMismatch));
+    }
+}
@@ -0,0 +1,141 @@
+//! Tensor descriptors for DLPack-like interop.
+//!
+//! A [RustyTensorDesc] describes the data of a container with the fields of a
+//! DLPack `DLTensor`, apart from the device, which is always the CPU. The
+//! descriptor borrows the data, shape and strides of the container, so it is
+//! only valid as long as the container is alive and its shape is unchanged.
+
+use crate::{RustyDataContainer, DTYPE};
+use libc::{c_void, size_t};
+
+/// DLPack type code for signed integers.
+pub const RUSTY_DL_INT: u8 = 0;
+/// DLPack type code for unsigned integers.
+pub const RUSTY_DL_UINT: u8 = 1;
+/// DLPack type code for floats.
+pub const RUSTY_DL_FLOAT: u8 = 2;
+/// DLPack type code for complex numbers.
+pub const RUSTY_DL_COMPLEX: u8 = 5;
+/// DLPack type code for booleans.
+pub const RUSTY_DL_BOOL: u8 = 6;
+
+/// The element type in the layout of a DLPack `DLDataType`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RustyDLDataType {
+    /// One of the `RUSTY_DL_*` type codes.
+    pub code: u8,
+    /// The number of bits of an element, for complex numbers of both parts together.
+    pub bits: u8,
+    /// The number of lanes, always 1.
+    pub lanes: u16,
+}
+
+impl RustyDLDataType {
+    /// The DLPack data type of `dtype`.
+    pub fn from_dtype(dtype: DTYPE) -> Self {
+        let code = if dtype.is_float() {
+            RUSTY_DL_FLOAT
+        } else if dtype.is_signed() {
+            RUSTY_DL_INT
+        } else if dtype.is_integer() {
+            RUSTY_DL_UINT
+        } else if dtype.is_complex() {
+            RUSTY_DL_COMPLEX
+        } else {
+            RUSTY_DL_BOOL
+        };
+        Self {
+            code,
+            bits: (8 * crate::get_itemsize(dtype)) as u8,
+            lanes: 1,
+        }
+    }
+}
+
+/// A read-only description of the data of a container.
+///
+/// The fields follow `DLTensor` of DLPack, except that `shape` and `strides` point
+/// to `size_t` and `ptrdiff_t` values instead of `int64_t`, which agree on 64 bit
+/// targets, and that the dtype is additionally given as a [DTYPE] code. The strides
+/// are in units of elements as in DLPack.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RustyTensorDesc {
+    /// The address of the first element.
+    pub data: *mut c_void,
+    /// The number of dimensions.
+    pub ndim: i32,
+    /// The DLPack element type.
+    pub dl_dtype: RustyDLDataType,
+    /// The dtype of the container.
+    pub dtype: DTYPE,
+    /// The `ndim` extents of the dimensions.
+    pub shape: *const size_t,
+    /// The `ndim` strides of the dimensions in elements.
+    pub strides: *const isize,
+    /// The offset in bytes from `data` to the first element, always 0.
+    pub byte_offset: u64,
+}
+
+impl RustyDataContainer {
+    /// Describe the data of the container. The container keeps ownership of the data.
+    pub fn tensor_desc(&self) -> RustyTensorDesc {
+        RustyTensorDesc {
+            data: self.data,
+            ndim: self.ndim as i32,
+            dl_dtype: RustyDLDataType::from_dtype(self.dtype),
+            dtype: self.dtype,
+            shape: self.shape.as_ptr(),
+            strides: self.strides.as_ptr(),
+            byte_offset: 0,
+        }
+    }
+}
+
+/// Get a DLPack-like descriptor of the data of a container.
+///
+/// The descriptor is a read-only view that is valid as long as the container
+/// is alive and its shape is not changed. The container keeps ownership.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_to_tensor_desc(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> RustyTensorDesc {
+    RustyDataContainer::leak(ptr).tensor_desc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn tensor_desc_of_a_matrix() {
+        let mut container = RustyDataContainer::from_vec(vec![0.0_f64; 6]);
+        container.reshape(&[2, 3]).unwrap();
+        let ptr = boxed(container);
+        let desc = rusty_data_container_to_tensor_desc(handle(ptr));
+        assert_eq!(desc.ndim, 2);
+        assert_eq!(desc.dtype, DTYPE::Float64);
+        assert_eq!(
+            (desc.dl_dtype.code, desc.dl_dtype.bits, desc.dl_dtype.lanes),
+            (RUSTY_DL_FLOAT, 64, 1)
+        );
+        let shape = unsafe { std::slice::from_raw_parts(desc.shape, 2) };
+        let strides = unsafe { std::slice::from_raw_parts(desc.strides, 2) };
+        assert_eq!((shape, strides), (&[2, 3][..], &[3, 1][..]));
+        assert_eq!(desc.data, RustyDataContainer::leak(handle(ptr)).data);
+        assert_eq!(desc.byte_offset, 0);
+        destroy(ptr);
+
+        let codes = [
+            (DTYPE::Int32, RUSTY_DL_INT),
+            (DTYPE::Unsigned8, RUSTY_DL_UINT),
+            (DTYPE::Complex32, RUSTY_DL_COMPLEX),
+            (DTYPE::Bool, RUSTY_DL_BOOL),
+        ];
+        for (dtype, code) in codes {
+            assert_eq!(RustyDLDataType::from_dtype(dtype).code, code);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Helpers for the unit tests of the C functions.
+
+use crate::RustyDataContainer;
+
+/// Box a container like the constructors of the C API and return the raw pointer.
+///
+/// The container must be destroyed with [crate::rusty_data_container_destroy].
+pub(crate) fn boxed(container: RustyDataContainer) -> *mut RustyDataContainer {
+    Box::into_raw(container.to_box())
+}
+
+/// The handle to `ptr` as it is passed to the C functions, which leak it again.
+pub(crate) fn handle(ptr: *mut RustyDataContainer) -> Option<Box<RustyDataContainer>> {
+    unsafe { Some(Box::from_raw(ptr)) }
+}
+
+/// Destroy the container behind `ptr`.
+pub(crate) fn destroy(ptr: *mut RustyDataContainer) {
+    unsafe { crate::rusty_data_container_destroy(ptr) };
+}
@@ -0,0 +1,232 @@
+//! Typed wrapper around a data container.
+//!
+//! A [TypedContainer] fixes the element type of a [RustyDataContainer]
+//! at compile time, so that elements can be accessed idiomatically,
+//! e.g. as `container[3]`.
+
+use crate::{assert_dtype, ConversionType, RustyDataContainer, MUTABILITY, OWNERSHIP};
+use libc::c_void;
+use std::marker::PhantomData;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
+
+/// A data container with known element type `T`.
+pub struct TypedContainer<T: ConversionType> {
+    container: RustyDataContainer,
+    _marker: PhantomData<T>,
+}
+
+impl<T: ConversionType> TypedContainer<T> {
+    /// Create a new typed container. Panics if `T` does not match the dtype.
+    pub fn new(container: RustyDataContainer) -> Self {
+        assert_dtype::<T>(container.dtype);
+        Self {
+            container,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the underlying untyped container.
+    pub fn into_inner(self) -> RustyDataContainer {
+        self.container
+    }
+
+    /// The underlying untyped container.
+    pub fn container(&self) -> &RustyDataContainer {
+        &self.container
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.container.len()
+    }
+
+    /// Return true if the container has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.container.is_empty()
+    }
+
+    /// A non-owning view of the elements in `range`, e.g. `container.slice(2..5)`.
+    ///
+    /// Panics like slice indexing if the range is out of bounds, or if
+    /// the container is not one-dimensional.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> TypedContainer<T> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let stop = match range.end_bound() {
+            Bound::Included(&stop) => stop + 1,
+            Bound::Excluded(&stop) => stop,
+            Bound::Unbounded => self.len(),
+        };
+        assert_eq!(
+            self.container.ndim, 1,
+            "slicing requires a one-dimensional container"
+        );
+        let view = self
+            .container
+            .slice_step(start, stop, 1)
+            .unwrap_or_else(|| {
+                panic!(
+                    "range {}..{} out of bounds for length {}",
+                    start,
+                    stop,
+                    self.len()
+                )
+            });
+        TypedContainer::new(view)
+    }
+
+    /// Pointer to the element with row-major index `index`.
+    fn element_ptr(&self, index: usize) -> *mut T {
+        assert!(
+            index < self.len(),
+            "index {} out of bounds for length {}",
+            index,
+            self.len()
+        );
+        unsafe { (self.container.data as *mut T).offset(self.container.element_offset(index)) }
+    }
+}
+
+impl<T: ConversionType> Index<usize> for TypedContainer<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        unsafe { &*self.element_ptr(index) }
+    }
+}
+
+impl<T: ConversionType> IndexMut<usize> for TypedContainer<T> {
+    /// Mutable access to an element. Panics if the container is not mutable.
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        assert_eq!(self.container.is_mutable, MUTABILITY::Mutable);
+        unsafe { &mut *self.element_ptr(index) }
+    }
+}
+
+/// An owning container of element type `T` whose data is not yet initialized.
+///
+/// The data can be written through [UninitContainer::as_uninit_slice_mut] or
+/// the raw pointer [UninitContainer::as_mut_ptr], e.g. by C code. Afterwards
+/// [UninitContainer::assume_init] turns it into a normal container.
+pub struct UninitContainer<T: ConversionType> {
+    container: RustyDataContainer,
+    _marker: PhantomData<T>,
+}
+
+impl<T: ConversionType> UninitContainer<T> {
+    /// The uninitialized data as mutable slice.
+    pub fn as_uninit_slice_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.container.data as *mut MaybeUninit<T>,
+                self.container.nitems,
+            )
+        }
+    }
+
+    /// Pointer to the uninitialized data.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.container.data as *mut T
+    }
+
+    /// Mark the data as initialized.
+    ///
+    /// # Safety
+    /// Every element must have been written. Reading uninitialized
+    /// elements through the returned container is undefined behavior.
+    pub unsafe fn assume_init(self) -> RustyDataContainer {
+        self.container
+    }
+}
+
+impl RustyDataContainer {
+    /// Create a new owning and mutable container with uninitialized data.
+    ///
+    /// This avoids the cost of zero filling if every element is overwritten anyway.
+    pub fn new_uninit<T: ConversionType>(nitems: usize) -> UninitContainer<T> {
+        let mut vec = ManuallyDrop::new(Vec::<MaybeUninit<T>>::with_capacity(nitems));
+        let container = RustyDataContainer::new_1d(
+            vec.as_mut_ptr() as *mut c_void,
+            nitems,
+            vec.capacity(),
+            crate::get_dtype::<T>(),
+            OWNERSHIP::Owner,
+            MUTABILITY::Mutable,
+        );
+        UninitContainer {
+            container,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Convert into a [TypedContainer] with element type `T`.
+    /// Panics if `T` does not match the dtype.
+    pub fn into_typed<T: ConversionType>(self) -> TypedContainer<T> {
+        TypedContainer::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_typed_container() {
+        let mut container =
+            RustyDataContainer::from_vec(vec![0.5_f64, 1.5, 2.5, 3.5]).into_typed::<f64>();
+        assert_eq!(container.len(), 4);
+        assert!(!container.is_empty());
+        assert_eq!(container[3], 3.5);
+        container[1] = -1.0;
+        assert_eq!(container[1], -1.0);
+        let slice = container.slice(1..3);
+        assert_eq!((slice.len(), slice[0], slice[1]), (2, -1.0, 2.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 4 out of bounds for length 4")]
+    fn index_out_of_bounds_panics() {
+        let container = RustyDataContainer::from_vec(vec![0_u8; 4]).into_typed::<u8>();
+        let _ = container[4];
+    }
+
+    #[test]
+    fn new_uninit_then_assume_init() {
+        let mut uninit = RustyDataContainer::new_uninit::<i64>(5);
+        for (index, value) in uninit.as_uninit_slice_mut().iter_mut().enumerate() {
+            value.write(10 * index as i64);
+        }
+        let container = unsafe { uninit.assume_init() };
+        assert_eq!(container.is_owner, OWNERSHIP::Owner);
+        assert_eq!(container.typed::<i64>(), [0, 10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn slice_with_range_types() {
+        let container =
+            RustyDataContainer::from_vec((0..8).collect::<Vec<i32>>()).into_typed::<i32>();
+        let values =
+            |slice: TypedContainer<i32>| (0..slice.len()).map(|i| slice[i]).collect::<Vec<_>>();
+        assert_eq!(values(container.slice(2..5)), [2, 3, 4]);
+        assert_eq!(values(container.slice(..2)), [0, 1]);
+        assert_eq!(values(container.slice(6..)), [6, 7]);
+        assert_eq!(values(container.slice(3..=4)), [3, 4]);
+        assert_eq!(container.slice(..).len(), 8);
+        assert!(container.slice(5..5).is_empty());
+        assert_eq!(
+            container.slice(2..5).container.is_owner,
+            OWNERSHIP::NotOwner
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "range 6..9 out of bounds for length 8")]
+    fn slice_out_of_bounds_panics() {
+        let container = RustyDataContainer::from_vec(vec![0_i32; 8]).into_typed::<i32>();
+        let _ = container.slice(6..9);
+    }
+}
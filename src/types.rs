@@ -1,8 +1,16 @@
 //! Representation of basic types
 
+use crate::ContainerError;
+use libc::{c_int, c_void, size_t};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
 // Specification of data types
-#[derive(PartialEq, Debug, Clone, Copy)]
-#[repr(u8)]
+//
+// The discriminants cross the FFI boundary as integers and
+// must never be changed or reordered.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[repr(u32)]
 pub enum DTYPE {
     /// 32 bit float
     Float32 = 0,
@@ -22,22 +30,409 @@ pub enum DTYPE {
     Unsigned64 = 7,
     /// Machine dependent indexing type
     Usize = 8,
+    /// Boolean stored in one byte with value 0 or 1
+    Bool = 9,
+    /// Complex number with 32 bit float components
+    Complex32 = 10,
+    /// Complex number with 64 bit float components
+    Complex64 = 11,
+    /// 128 bit signed integer
+    Int128 = 12,
+    /// 128 bit unsigned integer
+    Unsigned128 = 13,
+}
+
+impl DTYPE {
+    /// All data types, ordered by their integer code.
+    pub const ALL: [DTYPE; 14] = [
+        DTYPE::Float32,
+        DTYPE::Float64,
+        DTYPE::Int8,
+        DTYPE::Int32,
+        DTYPE::Int64,
+        DTYPE::Unsigned8,
+        DTYPE::Unsigned32,
+        DTYPE::Unsigned64,
+        DTYPE::Usize,
+        DTYPE::Bool,
+        DTYPE::Complex32,
+        DTYPE::Complex64,
+        DTYPE::Int128,
+        DTYPE::Unsigned128,
+    ];
+
+    /// The name of the data type. The names agree with the corresponding numpy dtype names.
+    /// Numpy has no 128 bit integers, their names follow the same pattern.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DTYPE::Float32 => "float32",
+            DTYPE::Float64 => "float64",
+            DTYPE::Int8 => "int8",
+            DTYPE::Int32 => "int32",
+            DTYPE::Int64 => "int64",
+            DTYPE::Unsigned8 => "uint8",
+            DTYPE::Unsigned32 => "uint32",
+            DTYPE::Unsigned64 => "uint64",
+            DTYPE::Usize => "uintp",
+            DTYPE::Bool => "bool",
+            DTYPE::Complex32 => "complex64",
+            DTYPE::Complex64 => "complex128",
+            DTYPE::Int128 => "int128",
+            DTYPE::Unsigned128 => "uint128",
+        }
+    }
+
+    /// Return the data type with the given name, if it exists.
+    pub fn from_name(name: &str) -> Option<DTYPE> {
+        DTYPE_NAMES.get(name).copied()
+    }
+
+    /// Whether the data type is a real floating point type.
+    pub const fn is_float(&self) -> bool {
+        matches!(self, DTYPE::Float32 | DTYPE::Float64)
+    }
+
+    /// Whether the data type is a signed or unsigned integer type. Bool is not an integer type.
+    pub const fn is_integer(&self) -> bool {
+        self.is_signed()
+            || matches!(
+                self,
+                DTYPE::Unsigned8
+                    | DTYPE::Unsigned32
+                    | DTYPE::Unsigned64
+                    | DTYPE::Usize
+                    | DTYPE::Unsigned128
+            )
+    }
+
+    /// Whether the data type is a signed integer type.
+    pub const fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            DTYPE::Int8 | DTYPE::Int32 | DTYPE::Int64 | DTYPE::Int128
+        )
+    }
+
+    /// Whether the data type is a complex type.
+    pub const fn is_complex(&self) -> bool {
+        matches!(self, DTYPE::Complex32 | DTYPE::Complex64)
+    }
+
+    /// The smallest data type that can represent the values of both data types.
+    ///
+    /// The rules follow numpy. Bool promotes to any other type. Integers of mixed
+    /// signedness promote to the smallest signed integer that is wider than the
+    /// unsigned one. Integers promote with floats to f32 if they have at most 16 bits
+    /// and to f64 otherwise, and complex types promote the real type of both sides.
+    /// Pairs without an exact common type, such as u128 and a signed integer, give f64.
+    pub fn promote(self, other: DTYPE) -> DTYPE {
+        if self == other {
+            return self;
+        }
+        let (a, b) = (self.canonical(), other.canonical());
+        if a == b || b == DTYPE::Bool {
+            return a;
+        }
+        if a == DTYPE::Bool {
+            return b;
+        }
+        if a.is_complex() || b.is_complex() {
+            return match a.real().promote(b.real()) {
+                DTYPE::Float32 => DTYPE::Complex32,
+                _ => DTYPE::Complex64,
+            };
+        }
+        if a.is_float() || b.is_float() {
+            let needs_f64 = |dtype: DTYPE| {
+                dtype == DTYPE::Float64 || (dtype.is_integer() && get_itemsize(dtype) > 2)
+            };
+            return if needs_f64(a) || needs_f64(b) {
+                DTYPE::Float64
+            } else {
+                DTYPE::Float32
+            };
+        }
+        let wider = |a: DTYPE, b: DTYPE| {
+            if get_itemsize(a) >= get_itemsize(b) {
+                a
+            } else {
+                b
+            }
+        };
+        if a.is_signed() == b.is_signed() {
+            return wider(a, b);
+        }
+        let (signed, unsigned) = if a.is_signed() { (a, b) } else { (b, a) };
+        if get_itemsize(signed) > get_itemsize(unsigned) {
+            return signed;
+        }
+        [DTYPE::Int8, DTYPE::Int32, DTYPE::Int64, DTYPE::Int128]
+            .into_iter()
+            .find(|&dtype| get_itemsize(dtype) > get_itemsize(unsigned))
+            .unwrap_or(DTYPE::Float64)
+    }
+
+    /// The fixed width unsigned type with the layout of `Usize`, or the type itself.
+    fn canonical(self) -> DTYPE {
+        match self {
+            DTYPE::Usize if get_itemsize(DTYPE::Usize) == 8 => DTYPE::Unsigned64,
+            DTYPE::Usize => DTYPE::Unsigned32,
+            _ => self,
+        }
+    }
+
+    /// The type of the parts of a complex type, or the type itself.
+    fn real(self) -> DTYPE {
+        match self {
+            DTYPE::Complex32 => DTYPE::Float32,
+            DTYPE::Complex64 => DTYPE::Float64,
+            _ => self,
+        }
+    }
+
+    /// The bytes of the zero value of the data type.
+    ///
+    /// Zero is represented by all zero bytes for every dtype, including
+    /// IEEE floats and both parts of complex numbers.
+    pub fn zero_bytes(&self) -> Vec<u8> {
+        vec![0; get_itemsize(*self)]
+    }
+
+    /// Return the fixed integer code of the data type used across the C ABI.
+    pub fn discriminant(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Return the data type with the given integer code, if it exists.
+    pub fn from_discriminant(code: u32) -> Option<DTYPE> {
+        match code {
+            0 => Some(DTYPE::Float32),
+            1 => Some(DTYPE::Float64),
+            2 => Some(DTYPE::Int8),
+            3 => Some(DTYPE::Int32),
+            4 => Some(DTYPE::Int64),
+            5 => Some(DTYPE::Unsigned8),
+            6 => Some(DTYPE::Unsigned32),
+            7 => Some(DTYPE::Unsigned64),
+            8 => Some(DTYPE::Usize),
+            9 => Some(DTYPE::Bool),
+            10 => Some(DTYPE::Complex32),
+            11 => Some(DTYPE::Complex64),
+            12 => Some(DTYPE::Int128),
+            13 => Some(DTYPE::Unsigned128),
+            _ => None,
+        }
+    }
+}
+
+/// Lookup table from dtype names to dtypes, initialized on first use.
+static DTYPE_NAMES: LazyLock<HashMap<&'static str, DTYPE>> = LazyLock::new(|| {
+    DTYPE::ALL
+        .iter()
+        .map(|&dtype| (dtype.name(), dtype))
+        .collect()
+});
+
+// Compile-time check that the DTYPE codes are stable.
+const _: () = {
+    assert!(DTYPE::Float32 as u32 == 0);
+    assert!(DTYPE::Float64 as u32 == 1);
+    assert!(DTYPE::Int8 as u32 == 2);
+    assert!(DTYPE::Int32 as u32 == 3);
+    assert!(DTYPE::Int64 as u32 == 4);
+    assert!(DTYPE::Unsigned8 as u32 == 5);
+    assert!(DTYPE::Unsigned32 as u32 == 6);
+    assert!(DTYPE::Unsigned64 as u32 == 7);
+    assert!(DTYPE::Usize as u32 == 8);
+    assert!(DTYPE::Bool as u32 == 9);
+    assert!(DTYPE::Complex32 as u32 == 10);
+    assert!(DTYPE::Complex64 as u32 == 11);
+    assert!(DTYPE::Int128 as u32 == 12);
+    assert!(DTYPE::Unsigned128 as u32 == 13);
+};
+
+/// Evaluate `$body` with the type alias `$T` bound to the Rust type of `$dtype`.
+macro_rules! dispatch_dtype {
+    ($dtype:expr, $T:ident => $body:expr) => {
+        match $dtype {
+            $crate::DTYPE::Float32 => {
+                type $T = f32;
+                $body
+            }
+            $crate::DTYPE::Float64 => {
+                type $T = f64;
+                $body
+            }
+            $crate::DTYPE::Int8 => {
+                type $T = i8;
+                $body
+            }
+            $crate::DTYPE::Int32 => {
+                type $T = i32;
+                $body
+            }
+            $crate::DTYPE::Int64 => {
+                type $T = i64;
+                $body
+            }
+            $crate::DTYPE::Unsigned8 => {
+                type $T = u8;
+                $body
+            }
+            $crate::DTYPE::Unsigned32 => {
+                type $T = u32;
+                $body
+            }
+            $crate::DTYPE::Unsigned64 => {
+                type $T = u64;
+                $body
+            }
+            $crate::DTYPE::Usize => {
+                type $T = usize;
+                $body
+            }
+            $crate::DTYPE::Bool => {
+                type $T = bool;
+                $body
+            }
+            $crate::DTYPE::Complex32 => {
+                type $T = $crate::Complex32;
+                $body
+            }
+            $crate::DTYPE::Complex64 => {
+                type $T = $crate::Complex64;
+                $body
+            }
+            $crate::DTYPE::Int128 => {
+                type $T = i128;
+                $body
+            }
+            $crate::DTYPE::Unsigned128 => {
+                type $T = u128;
+                $body
+            }
+        }
+    };
+}
+
+pub(crate) use dispatch_dtype;
+
+/// Like `dispatch_dtype!` but only for dtypes with ordered, real values.
+/// Evaluates `$other` for complex dtypes.
+macro_rules! dispatch_real_dtype {
+    ($dtype:expr, $T:ident => $body:expr, _ => $other:expr) => {
+        match $dtype {
+            $crate::DTYPE::Float32 => {
+                type $T = f32;
+                $body
+            }
+            $crate::DTYPE::Float64 => {
+                type $T = f64;
+                $body
+            }
+            $crate::DTYPE::Int8 => {
+                type $T = i8;
+                $body
+            }
+            $crate::DTYPE::Int32 => {
+                type $T = i32;
+                $body
+            }
+            $crate::DTYPE::Int64 => {
+                type $T = i64;
+                $body
+            }
+            $crate::DTYPE::Unsigned8 => {
+                type $T = u8;
+                $body
+            }
+            $crate::DTYPE::Unsigned32 => {
+                type $T = u32;
+                $body
+            }
+            $crate::DTYPE::Unsigned64 => {
+                type $T = u64;
+                $body
+            }
+            $crate::DTYPE::Usize => {
+                type $T = usize;
+                $body
+            }
+            $crate::DTYPE::Bool => {
+                type $T = bool;
+                $body
+            }
+            $crate::DTYPE::Int128 => {
+                type $T = i128;
+                $body
+            }
+            $crate::DTYPE::Unsigned128 => {
+                type $T = u128;
+                $body
+            }
+            $crate::DTYPE::Complex32 | $crate::DTYPE::Complex64 => $other,
+        }
+    };
+}
+
+pub(crate) use dispatch_real_dtype;
+
+/// Invoke `$macro!(T)` for the Rust type `T` of every dtype.
+macro_rules! iterate_over_type {
+    ($macro:ident) => {
+        $macro!(f32);
+        $macro!(f64);
+        $macro!(i8);
+        $macro!(i32);
+        $macro!(i64);
+        $macro!(u8);
+        $macro!(u32);
+        $macro!(u64);
+        $macro!(usize);
+        $macro!(bool);
+        $macro!(Complex32);
+        $macro!(Complex64);
+        $macro!(i128);
+        $macro!(u128);
+    };
+}
+
+/// A complex number stored as real part followed by imaginary part.
+///
+/// The layout agrees with C99 `_Complex` types and numpy complex dtypes.
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+/// Complex number with 32 bit float components
+pub type Complex32 = Complex<f32>;
+/// Complex number with 64 bit float components
+pub type Complex64 = Complex<f64>;
+
+impl<T> Complex<T> {
+    pub const fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
 }
 
-// macro_rules! iterate_over_type {
-//     ($macro:ident) => {
-//         $macro!(f32);
-//         $macro!(f64);
-//         $macro!(i8);
-//         $macro!(i32);
-//         $macro!(i64);
-//         $macro!(u8);
-//         $macro!(u32);
-//         $macro!(u64);
-//     };
-// }
+// Complex numbers are formatted like `1+2j` and `1-2j`, as numpy does without parentheses.
+macro_rules! impl_display_complex {
+    ($($f:ty),*) => {
+        $(
+            impl std::fmt::Display for Complex<$f> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    let sign = if self.im.is_sign_negative() { "" } else { "+" };
+                    write!(f, "{}{}{}j", self.re, sign, self.im)
+                }
+            }
+        )*
+    };
+}
 
-// pub(crate) use iterate_over_type;
+impl_display_complex!(f32, f64);
 
 // Mutability Property
 #[derive(PartialEq, Debug, Clone, Copy)]
@@ -53,6 +448,8 @@ pub enum MUTABILITY {
 pub enum OWNERSHIP {
     NotOwner = 0,
     Owner = 1,
+    /// The data is reference counted and shared between several containers.
+    Shared = 2,
 }
 
 pub trait ConversionType: 'static {
@@ -95,22 +492,203 @@ impl ConversionType for u64 {
 
 impl ConversionType for usize {
     const D: DTYPE = DTYPE::Usize;
+    const SIZE: usize = std::mem::size_of::<usize>();
+}
+
+impl ConversionType for bool {
+    const D: DTYPE = DTYPE::Bool;
+    const SIZE: usize = 1;
+}
+
+impl ConversionType for Complex32 {
+    const D: DTYPE = DTYPE::Complex32;
     const SIZE: usize = 8;
 }
 
-pub fn get_dtype<T: ConversionType>() -> DTYPE {
+impl ConversionType for Complex64 {
+    const D: DTYPE = DTYPE::Complex64;
+    const SIZE: usize = 16;
+}
+
+// C has no standard 128 bit integer type. The value is stored in the native byte order
+// of the target, so on little endian targets the low 64 bit word comes first, followed
+// by the high 64 bit word. This is the layout of `__int128` in GCC and Clang.
+impl ConversionType for i128 {
+    const D: DTYPE = DTYPE::Int128;
+    const SIZE: usize = 16;
+}
+
+impl ConversionType for u128 {
+    const D: DTYPE = DTYPE::Unsigned128;
+    const SIZE: usize = 16;
+}
+
+/// Numeric conversion between element types with the semantics of an `as` cast.
+pub trait CastFrom<S>: Sized {
+    fn cast_from(value: S) -> Self;
+}
+
+macro_rules! impl_cast_from {
+    ($($src:ty),*) => {
+        $(impl_cast_from!(@to $src; f32, f64, i8, i32, i64, u8, u32, u64, usize, i128, u128);)*
+    };
+    (@to $src:ty; $($dst:ty),*) => {
+        $(
+            impl CastFrom<$src> for $dst {
+                fn cast_from(value: $src) -> Self {
+                    value as $dst
+                }
+            }
+        )*
+    };
+}
+
+impl_cast_from!(f32, f64, i8, i32, i64, u8, u32, u64, usize, i128, u128);
+
+// Booleans convert to 0 or 1, and numbers convert to true if they are nonzero.
+macro_rules! impl_cast_bool {
+    ($($t:ty),*) => {
+        $(
+            impl CastFrom<bool> for $t {
+                fn cast_from(value: bool) -> Self {
+                    value as u8 as $t
+                }
+            }
+
+            impl CastFrom<$t> for bool {
+                fn cast_from(value: $t) -> Self {
+                    value != <$t>::default()
+                }
+            }
+        )*
+    };
+}
+
+impl_cast_bool!(f32, f64, i8, i32, i64, u8, u32, u64, usize, i128, u128);
+
+impl CastFrom<bool> for bool {
+    fn cast_from(value: bool) -> Self {
+        value
+    }
+}
+
+// Real numbers convert to complex numbers with zero imaginary part, and complex
+// numbers convert to real numbers by discarding the imaginary part like numpy does.
+macro_rules! impl_cast_complex {
+    ($($t:ty),*) => {
+        $(
+            impl_cast_complex!(@real $t; f32);
+            impl_cast_complex!(@real $t; f64);
+        )*
+    };
+    (@real $t:ty; $f:ty) => {
+        impl CastFrom<$t> for Complex<$f> {
+            fn cast_from(value: $t) -> Self {
+                Complex::new(<$f>::cast_from(value), 0.0)
+            }
+        }
+
+        impl CastFrom<Complex<$f>> for $t {
+            fn cast_from(value: Complex<$f>) -> Self {
+                <$t>::cast_from(value.re)
+            }
+        }
+    };
+}
+
+impl_cast_complex!(f32, f64, i8, i32, i64, u8, u32, u64, usize, i128, u128);
+
+impl<S: Copy, D: CastFrom<S>> CastFrom<Complex<S>> for Complex<D> {
+    fn cast_from(value: Complex<S>) -> Self {
+        Complex::new(D::cast_from(value.re), D::cast_from(value.im))
+    }
+}
+
+// Complex numbers convert to true if either part is nonzero.
+macro_rules! impl_cast_complex_bool {
+    ($($f:ty),*) => {
+        $(
+            impl CastFrom<bool> for Complex<$f> {
+                fn cast_from(value: bool) -> Self {
+                    Complex::new(<$f>::cast_from(value), 0.0)
+                }
+            }
+
+            impl CastFrom<Complex<$f>> for bool {
+                fn cast_from(value: Complex<$f>) -> Self {
+                    value.re != 0.0 || value.im != 0.0
+                }
+            }
+        )*
+    };
+}
+
+impl_cast_complex_bool!(f32, f64);
+
+/// Element types that can be converted from the element type of every dtype.
+pub trait CastFromAny:
+    CastFrom<f32>
+    + CastFrom<f64>
+    + CastFrom<i8>
+    + CastFrom<i32>
+    + CastFrom<i64>
+    + CastFrom<u8>
+    + CastFrom<u32>
+    + CastFrom<u64>
+    + CastFrom<usize>
+    + CastFrom<bool>
+    + CastFrom<Complex32>
+    + CastFrom<Complex64>
+    + CastFrom<i128>
+    + CastFrom<u128>
+{
+}
+
+impl<T> CastFromAny for T where
+    T: CastFrom<f32>
+        + CastFrom<f64>
+        + CastFrom<i8>
+        + CastFrom<i32>
+        + CastFrom<i64>
+        + CastFrom<u8>
+        + CastFrom<u32>
+        + CastFrom<u64>
+        + CastFrom<usize>
+        + CastFrom<bool>
+        + CastFrom<Complex32>
+        + CastFrom<Complex64>
+        + CastFrom<i128>
+        + CastFrom<u128>
+{
+}
+
+pub const fn get_dtype<T: ConversionType>() -> DTYPE {
     T::D
 }
 
+/// Assert that `T` is the Rust type of the dtype `d`.
+///
+/// Panics otherwise. See [ensure_dtype] for a non-panicking version.
 pub fn assert_dtype<T: ConversionType>(d: DTYPE) {
     assert_eq!(get_dtype::<T>(), d);
 }
 
-pub fn get_size<T: ConversionType>() -> usize {
+/// Check that `T` is the Rust type of the dtype `d`.
+///
+/// Returns [ContainerError::DtypeMismatch] otherwise.
+pub fn ensure_dtype<T: ConversionType>(d: DTYPE) -> Result<(), ContainerError> {
+    if get_dtype::<T>() == d {
+        Ok(())
+    } else {
+        Err(ContainerError::DtypeMismatch)
+    }
+}
+
+pub const fn get_size<T: ConversionType>() -> usize {
     T::SIZE
 }
 
-pub fn get_itemsize(dtype: DTYPE) -> usize {
+pub const fn get_itemsize(dtype: DTYPE) -> usize {
     match dtype {
         DTYPE::Float32 => crate::get_size::<f32>(),
         DTYPE::Float64 => crate::get_size::<f64>(),
@@ -121,5 +699,392 @@ pub fn get_itemsize(dtype: DTYPE) -> usize {
         DTYPE::Int32 => crate::get_size::<i32>(),
         DTYPE::Int64 => crate::get_size::<i64>(),
         DTYPE::Usize => crate::get_size::<usize>(),
+        DTYPE::Bool => crate::get_size::<bool>(),
+        DTYPE::Complex32 => crate::get_size::<Complex32>(),
+        DTYPE::Complex64 => crate::get_size::<Complex64>(),
+        DTYPE::Int128 => crate::get_size::<i128>(),
+        DTYPE::Unsigned128 => crate::get_size::<u128>(),
+    }
+}
+
+/// The alignment in bytes of the Rust type corresponding to `dtype`.
+pub const fn get_alignment(dtype: DTYPE) -> usize {
+    dispatch_dtype!(dtype, T => std::mem::align_of::<T>())
+}
+
+// Compile-time checks that the size information of every type is consistent.
+macro_rules! check_conversion_type {
+    ($T:ty) => {
+        const _: () = assert!(<$T as ConversionType>::SIZE == std::mem::size_of::<$T>());
+        const _: () = assert!(get_itemsize(get_dtype::<$T>()) == get_size::<$T>());
+    };
+}
+
+iterate_over_type!(check_conversion_type);
+
+/// Get the itemsize in bytes of a dtype code. Returns 0 for invalid codes.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_itemsize(dtype: u32) -> size_t {
+    DTYPE::from_discriminant(dtype).map_or(0, get_itemsize)
+}
+
+/// Check if a dtype code is a real floating point type. Returns false for invalid codes.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_is_float(dtype: u32) -> bool {
+    DTYPE::from_discriminant(dtype).is_some_and(|dtype| dtype.is_float())
+}
+
+/// Check if a dtype code is an integer type. Returns false for invalid codes.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_is_integer(dtype: u32) -> bool {
+    DTYPE::from_discriminant(dtype).is_some_and(|dtype| dtype.is_integer())
+}
+
+/// Check if a dtype code is a signed integer type. Returns false for invalid codes.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_is_signed(dtype: u32) -> bool {
+    DTYPE::from_discriminant(dtype).is_some_and(|dtype| dtype.is_signed())
+}
+
+/// Check if a dtype code is a complex type. Returns false for invalid codes.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_is_complex(dtype: u32) -> bool {
+    DTYPE::from_discriminant(dtype).is_some_and(|dtype| dtype.is_complex())
+}
+
+/// Get the number of entries in the dtype lookup table.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_table_len() -> size_t {
+    DTYPE_NAMES.len()
+}
+
+/// The sort order of two values, with NaN after all other values and equal to itself.
+fn sort_order<T: PartialOrd>(a: &T, b: &T) -> std::cmp::Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| {
+        let is_nan = |value: &T| value.partial_cmp(value).is_none();
+        is_nan(a).cmp(&is_nan(b))
+    })
+}
+
+extern "C" fn compare<T: PartialOrd>(a: *const c_void, b: *const c_void) -> c_int {
+    let (a, b) = unsafe { (&*(a as *const T), &*(b as *const T)) };
+    sort_order(a, b) as c_int
+}
+
+extern "C" fn compare_complex<T: PartialOrd>(a: *const c_void, b: *const c_void) -> c_int {
+    let (a, b) = unsafe { (&*(a as *const Complex<T>), &*(b as *const Complex<T>)) };
+    sort_order(&a.re, &b.re).then_with(|| sort_order(&a.im, &b.im)) as c_int
+}
+
+/// A comparison function of two elements of the given dtype for the C `qsort` function.
+///
+/// The function returns a negative value, zero or a positive value if the first
+/// element is smaller, equal or larger. NaN values are larger than all other values
+/// and equal to each other, so that they are sorted to the end, and `-0.0` is equal
+/// to `0.0`. Complex values are ordered by their real part and then their imaginary
+/// part. Returns null for an invalid dtype code.
+#[no_mangle]
+pub extern "C" fn rusty_dtype_comparator(
+    dtype: u32,
+) -> Option<extern "C" fn(*const c_void, *const c_void) -> c_int> {
+    let compare: extern "C" fn(*const c_void, *const c_void) -> c_int =
+        match DTYPE::from_discriminant(dtype)? {
+            DTYPE::Float32 => compare::<f32>,
+            DTYPE::Float64 => compare::<f64>,
+            DTYPE::Int8 => compare::<i8>,
+            DTYPE::Int32 => compare::<i32>,
+            DTYPE::Int64 => compare::<i64>,
+            DTYPE::Unsigned8 => compare::<u8>,
+            DTYPE::Unsigned32 => compare::<u32>,
+            DTYPE::Unsigned64 => compare::<u64>,
+            DTYPE::Usize => compare::<usize>,
+            DTYPE::Bool => compare::<bool>,
+            DTYPE::Complex32 => compare_complex::<f32>,
+            DTYPE::Complex64 => compare_complex::<f64>,
+            DTYPE::Int128 => compare::<i128>,
+            DTYPE::Unsigned128 => compare::<u128>,
+        };
+    Some(compare)
+}
+
+/// Check that the sizes of the element types agree with the sizes of the caller's compiler.
+///
+/// The arguments are the values of `sizeof` for the C type of each dtype in the order
+/// of the dtype codes. The 128 bit integer dtypes have no standard C type and are
+/// not checked. Returns false if any size differs from the size assumed by this
+/// crate, in which case the containers must not be used.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn rusty_validate_itemsizes(
+    f32_size: size_t,
+    f64_size: size_t,
+    i8_size: size_t,
+    i32_size: size_t,
+    i64_size: size_t,
+    u8_size: size_t,
+    u32_size: size_t,
+    u64_size: size_t,
+    usize_size: size_t,
+    bool_size: size_t,
+    c32_size: size_t,
+    c64_size: size_t,
+) -> bool {
+    let sizes = [
+        f32_size, f64_size, i8_size, i32_size, i64_size, u8_size, u32_size, u64_size, usize_size,
+        bool_size, c32_size, c64_size,
+    ];
+    DTYPE::ALL
+        .iter()
+        .zip(sizes)
+        .all(|(&dtype, size)| get_itemsize(dtype) == size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RustyDataContainer;
+    use std::fmt::Debug;
+
+    /// Generate a module `$name` with one test per element type that runs
+    /// `$body` with the type alias `$T` set to the element type.
+    macro_rules! test_all_dtypes {
+        ($name:ident, |$T:ident| $body:block) => {
+            #[allow(non_snake_case)]
+            mod $name {
+                use super::*;
+
+                macro_rules! instantiate {
+                    ($t:ident) => {
+                        #[test]
+                        fn $t() {
+                            type $T = $t;
+                            $body
+                        }
+                    };
+                }
+
+                iterate_over_type!(instantiate);
+            }
+        };
+    }
+
+    /// Round trip the values `0, 1, 2, 3` through a container and through
+    /// conversions from and to `u8` and `f64`.
+    fn round_trip<T>()
+    where
+        T: ConversionType + CastFromAny + PartialEq + Debug + Copy,
+        u8: CastFrom<T>,
+        f64: CastFrom<T>,
+    {
+        let values: Vec<T> = (0..4_u8).map(T::cast_from).collect();
+        for &value in &values {
+            assert_eq!(T::cast_from(u8::cast_from(value)), value);
+            assert_eq!(T::cast_from(f64::cast_from(value)), value);
+        }
+        let container = RustyDataContainer::from_vec(values.clone());
+        assert_eq!(container.dtype, get_dtype::<T>());
+        assert_eq!(container.itemsize, std::mem::size_of::<T>());
+        assert_eq!(container.values::<T>().collect::<Vec<_>>(), values);
+        let ptr = Box::into_raw(container.to_box());
+        let converted = RustyDataContainer::to_vec_as::<f64>(unsafe { Some(Box::from_raw(ptr)) });
+        if get_dtype::<T>() == DTYPE::Bool {
+            assert_eq!(converted, Err(crate::ContainerError::DtypeMismatch));
+        } else {
+            let converted: Vec<T> = converted.unwrap().into_iter().map(T::cast_from).collect();
+            assert_eq!(converted, values);
+        }
+        let copy = unsafe { &*ptr }.clone();
+        unsafe { crate::rusty_data_container_destroy(ptr) };
+        assert_eq!(copy.typed::<T>(), values);
+    }
+
+    test_all_dtypes!(round_trip_every_dtype, |T| { round_trip::<T>() });
+
+    test_all_dtypes!(zeroed_matches_the_itemsize, |T| {
+        let container = RustyDataContainer::new_zeroed(3, get_dtype::<T>());
+        assert_eq!(container.itemsize, get_itemsize(get_dtype::<T>()));
+        assert_eq!(container.bytes(), vec![0_u8; 3 * get_size::<T>()]);
+    });
+
+    #[test]
+    fn dtype_codes_are_stable() {
+        let codes = [
+            (DTYPE::Float32, 0),
+            (DTYPE::Float64, 1),
+            (DTYPE::Int8, 2),
+            (DTYPE::Int32, 3),
+            (DTYPE::Int64, 4),
+            (DTYPE::Unsigned8, 5),
+            (DTYPE::Unsigned32, 6),
+            (DTYPE::Unsigned64, 7),
+            (DTYPE::Usize, 8),
+            (DTYPE::Bool, 9),
+            (DTYPE::Complex32, 10),
+            (DTYPE::Complex64, 11),
+            (DTYPE::Int128, 12),
+            (DTYPE::Unsigned128, 13),
+        ];
+        assert_eq!(codes.len(), DTYPE::ALL.len());
+        for (dtype, code) in codes {
+            assert_eq!(dtype.discriminant(), code);
+            assert_eq!(dtype as u32, code);
+            assert_eq!(DTYPE::from_discriminant(code), Some(dtype));
+            assert_eq!(DTYPE::ALL[code as usize], dtype);
+        }
+        assert_eq!(DTYPE::from_discriminant(14), None);
+    }
+
+    #[test]
+    fn itemsize_of_dtype_codes() {
+        assert_eq!(rusty_dtype_itemsize(DTYPE::Float64 as u32), 8);
+        assert_eq!(rusty_dtype_itemsize(DTYPE::Bool as u32), 1);
+        assert_eq!(rusty_dtype_itemsize(DTYPE::Complex64 as u32), 16);
+        assert_eq!(rusty_dtype_itemsize(100), 0);
+    }
+
+    #[test]
+    fn dtype_names_from_many_threads() {
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        for dtype in DTYPE::ALL {
+                            assert_eq!(DTYPE::from_name(dtype.name()), Some(dtype));
+                        }
+                    }
+                    assert_eq!(rusty_dtype_table_len(), DTYPE::ALL.len());
+                });
+            }
+        });
+        assert_eq!(DTYPE::from_name("float16"), None);
+    }
+
+    #[test]
+    fn validate_itemsizes_of_the_caller() {
+        use std::mem::size_of;
+        let word = size_of::<usize>();
+        assert!(rusty_validate_itemsizes(
+            4, 8, 1, 4, 8, 1, 4, 8, word, 1, 8, 16
+        ));
+        assert!(!rusty_validate_itemsizes(
+            4, 8, 1, 4, 8, 1, 4, 8, word, 4, 8, 16
+        ));
+        assert!(!rusty_validate_itemsizes(
+            4, 16, 1, 4, 8, 1, 4, 8, word, 1, 8, 16
+        ));
+        assert!(!rusty_validate_itemsizes(
+            4,
+            8,
+            1,
+            4,
+            8,
+            1,
+            4,
+            8,
+            word + 1,
+            1,
+            8,
+            16
+        ));
+    }
+
+    #[test]
+    fn zero_bytes_of_dtypes() {
+        assert_eq!(DTYPE::Float64.zero_bytes(), [0_u8; 8]);
+        assert_eq!(DTYPE::Complex32.zero_bytes(), [0_u8; 8]);
+        assert_eq!(
+            f64::from_ne_bytes(DTYPE::Float64.zero_bytes().try_into().unwrap()),
+            0.0
+        );
+
+        let mut container = RustyDataContainer::from_vec(vec![1.5_f64]);
+        container.resize(3).unwrap();
+        assert_eq!(
+            container.bytes()[8..],
+            [DTYPE::Float64.zero_bytes(), DTYPE::Float64.zero_bytes()].concat()
+        );
+        assert_eq!(container.typed::<f64>(), [1.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn dtype_group_predicates() {
+        let groups = [
+            (DTYPE::Float64, [true, false, false, false]),
+            (DTYPE::Int32, [false, true, true, false]),
+            (DTYPE::Unsigned8, [false, true, false, false]),
+            (DTYPE::Complex64, [false, false, false, true]),
+            (DTYPE::Bool, [false, false, false, false]),
+        ];
+        for (dtype, [float, integer, signed, complex]) in groups {
+            assert_eq!(
+                [
+                    dtype.is_float(),
+                    dtype.is_integer(),
+                    dtype.is_signed(),
+                    dtype.is_complex()
+                ],
+                [float, integer, signed, complex]
+            );
+            let code = dtype.discriminant();
+            assert_eq!(
+                [
+                    rusty_dtype_is_float(code),
+                    rusty_dtype_is_integer(code),
+                    rusty_dtype_is_signed(code),
+                    rusty_dtype_is_complex(code)
+                ],
+                [float, integer, signed, complex]
+            );
+        }
+        assert!(!rusty_dtype_is_float(100));
+    }
+
+    #[test]
+    fn ensure_dtype_matches_the_rust_type() {
+        assert_eq!(ensure_dtype::<f64>(DTYPE::Float64), Ok(()));
+        assert_eq!(ensure_dtype::<Complex32>(DTYPE::Complex32), Ok(()));
+        assert_eq!(
+            ensure_dtype::<f64>(DTYPE::Float32),
+            Err(crate::ContainerError::DtypeMismatch)
+        );
+        assert_eq!(
+            ensure_dtype::<usize>(DTYPE::Unsigned64),
+            Err(crate::ContainerError::DtypeMismatch)
+        );
+        assert_dtype::<u8>(DTYPE::Unsigned8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_dtype_panics_on_mismatch() {
+        assert_dtype::<i32>(DTYPE::Unsigned32);
+    }
+
+    fn qsort<T>(values: &mut [T], dtype: DTYPE) {
+        let compare = rusty_dtype_comparator(dtype as u32).unwrap();
+        unsafe {
+            libc::qsort(
+                values.as_mut_ptr() as *mut c_void,
+                values.len(),
+                std::mem::size_of::<T>(),
+                Some(compare),
+            )
+        };
+    }
+
+    #[test]
+    fn comparator_sorts_i32_with_qsort() {
+        let mut values = [3i32, -7, 0, i32::MAX, -1, i32::MIN, 3];
+        qsort(&mut values, DTYPE::Int32);
+        assert_eq!(values, [i32::MIN, -7, -1, 0, 3, 3, i32::MAX]);
+    }
+
+    #[test]
+    fn comparator_sorts_nan_last() {
+        let mut values = [f64::NAN, 1.0, -2.0, f64::INFINITY];
+        qsort(&mut values, DTYPE::Float64);
+        assert_eq!(values[..3], [-2.0, 1.0, f64::INFINITY]);
+        assert!(values[3].is_nan());
+        assert!(rusty_dtype_comparator(99).is_none());
     }
 }
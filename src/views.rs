@@ -0,0 +1,139 @@
+//! Views that keep the container they were created from alive.
+//!
+//! With the `safe-views` feature every non-owning view that is created from a boxed
+//! container, for example by [crate::rusty_data_container_slice_step] or
+//! [crate::rusty_data_container_row], stores a pointer to that parent and increments
+//! its view count. Views of views refer to the original parent. If the parent is
+//! destroyed with [crate::rusty_data_container_destroy] while views are alive, it
+//! is only freed together with its last view, so that the views never dangle.
+//!
+//! Only destruction through [crate::rusty_data_container_destroy] is deferred.
+//! Functions that move or free the data of the parent, like resizing, taking or
+//! swapping its data, or consume it, like [crate::rusty_data_container_into_shared],
+//! fail with [ContainerError::HasViews] while it has views. Without the feature views
+//! do not track their parent.
+
+#[cfg(feature = "safe-views")]
+use crate::OWNERSHIP;
+use crate::{ContainerError, RustyDataContainer};
+#[cfg(feature = "safe-views")]
+use std::collections::BTreeMap;
+#[cfg(feature = "safe-views")]
+use std::sync::Mutex;
+
+/// The number of live views of a parent and whether the parent was destroyed.
+#[cfg(feature = "safe-views")]
+#[derive(Default)]
+struct ViewCount {
+    views: usize,
+    destroyed: bool,
+}
+
+/// View counts of all parents with live views, keyed by their address.
+#[cfg(feature = "safe-views")]
+static VIEW_COUNTS: Mutex<BTreeMap<usize, ViewCount>> = Mutex::new(BTreeMap::new());
+
+/// Register a new view of `container` and return the parent to store in the view.
+///
+/// Returns null if the container is shared, since shared data is reference counted
+/// anyway, or if it is not boxed.
+#[cfg(feature = "safe-views")]
+pub(crate) fn retain(container: &RustyDataContainer) -> *mut RustyDataContainer {
+    let parent = if !container.parent.is_null() {
+        container.parent
+    } else if container.is_owner != OWNERSHIP::Shared && crate::is_live(container) {
+        container as *const RustyDataContainer as *mut RustyDataContainer
+    } else {
+        return std::ptr::null_mut();
+    };
+    VIEW_COUNTS
+        .lock()
+        .unwrap()
+        .entry(parent as usize)
+        .or_default()
+        .views += 1;
+    parent
+}
+
+#[cfg(not(feature = "safe-views"))]
+pub(crate) fn retain(_container: &RustyDataContainer) -> *mut RustyDataContainer {
+    std::ptr::null_mut()
+}
+
+/// Unregister a view of `parent`, freeing the parent if it was its last view and
+/// the parent was already destroyed. Does nothing for a null parent.
+#[cfg(feature = "safe-views")]
+pub(crate) fn release(parent: *mut RustyDataContainer) {
+    if parent.is_null() {
+        return;
+    }
+    let free = {
+        let mut counts = VIEW_COUNTS.lock().unwrap();
+        let Some(count) = counts.get_mut(&(parent as usize)) else {
+            return;
+        };
+        count.views -= 1;
+        count.views == 0 && counts.remove(&(parent as usize)).unwrap().destroyed
+    };
+    if free {
+        drop(unsafe { Box::from_raw(parent) });
+    }
+}
+
+#[cfg(not(feature = "safe-views"))]
+pub(crate) fn release(_parent: *mut RustyDataContainer) {}
+
+/// Mark `ptr` as destroyed if it has live views and return whether it has.
+///
+/// The container must then not be freed, this happens when its last view is dropped.
+#[cfg(feature = "safe-views")]
+pub(crate) fn defer_destroy(ptr: *mut RustyDataContainer) -> bool {
+    match VIEW_COUNTS.lock().unwrap().get_mut(&(ptr as usize)) {
+        Some(count) => {
+            count.destroyed = true;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(not(feature = "safe-views"))]
+pub(crate) fn defer_destroy(_ptr: *mut RustyDataContainer) -> bool {
+    false
+}
+
+/// The number of live views of the container at `ptr`.
+#[cfg(feature = "safe-views")]
+fn view_count(ptr: *const RustyDataContainer) -> usize {
+    VIEW_COUNTS
+        .lock()
+        .unwrap()
+        .get(&(ptr as usize))
+        .map_or(0, |count| count.views)
+}
+
+#[cfg(not(feature = "safe-views"))]
+fn view_count(_ptr: *const RustyDataContainer) -> usize {
+    0
+}
+
+/// Fail with [ContainerError::HasViews] if live views keep `container` alive.
+///
+/// Operations that move or free the data, like resizing or swapping, must not run
+/// while views reference it. Always succeeds without the `safe-views` feature.
+pub(crate) fn ensure_no_views(container: &RustyDataContainer) -> Result<(), ContainerError> {
+    if view_count(container) > 0 {
+        return Err(ContainerError::HasViews);
+    }
+    Ok(())
+}
+
+/// Get the number of live views that keep a container alive.
+///
+/// Always 0 without the `safe-views` feature.
+#[no_mangle]
+pub extern "C" fn rusty_data_container_view_count(
+    ptr: Option<Box<RustyDataContainer>>,
+) -> libc::size_t {
+    view_count(RustyDataContainer::leak(ptr))
+}